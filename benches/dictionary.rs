@@ -0,0 +1,300 @@
+//! Interns growing prefixes of the bundled dictionary and records the
+//! runtime and memory of each trie operation. Results are written to
+//! `plotters/bench.dat`, one gnuplot data block per series:
+//!
+//! | index  | series                             |
+//! |--------|------------------------------------|
+//! | 0, 1   | `DLB::get_or_intern`               |
+//! | 2, 3   | `DLB::get`                         |
+//! | 4, 5   | `DLB::resolve`                     |
+//! | 6, 7   | `StaticTrie::get`                  |
+//! | 8, 9   | `StaticTrie::resolve`              |
+//! | 10, 11 | `DLB::from_sorted_iter`            |
+//! | 12, 13 | `StaticTrie::from_sorted_iter`     |
+//! | 14, 15 | `FrontCodedDict::get`              |
+//! | 16, 17 | `FrontCodedDict::resolve`          |
+//! | 18, 19 | `TernarySearchTrie::get_or_intern` |
+//! | 20, 21 | `TernarySearchTrie::get`           |
+//! | 22, 23 | `AdaptiveRadixTrie::get_or_intern` |
+//! | 24, 25 | `AdaptiveRadixTrie::get`           |
+//!
+//! Even indices hold the sequential order and odd indices the shuffled
+//! order. The bulk builders need sorted keys, so they're handed a sorted
+//! copy of each prefix whatever its order, and their times can be read
+//! against `get_or_intern` to see what building in one pass saves.
+//! Every row is `keys,ns_per_op,bytes`.
+
+mod support;
+
+use rand::seq::SliceRandom;
+use rand_chacha::ChaCha8Rng;
+use rand_core::SeedableRng;
+use sdtrie::art::AdaptiveRadixTrie;
+use sdtrie::dtrie::{Identifier, Symbol, DLB};
+use sdtrie::fcd::FrontCodedDict;
+use sdtrie::strie::StaticTrie;
+use sdtrie::tst::TernarySearchTrie;
+use std::fs::File;
+use std::hint::black_box;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use support::series::{Sample, Series};
+use support::timer::time;
+
+const DICTIONARY: &str = "dictionaries/alphanumeric.txt";
+const OUTPUT: &str = "plotters/bench.dat";
+const SMALLEST: usize = 1000;
+/// Lookups are timed over at most this many keys per prefix.
+const LOOKUPS: usize = 2000;
+
+const OPERATIONS: [&str; 13] = [
+    "get_or_intern",
+    "get",
+    "resolve",
+    "static get",
+    "static resolve",
+    "from_sorted_iter",
+    "static from_sorted_iter",
+    "front-coded get",
+    "front-coded resolve",
+    "ternary get_or_intern",
+    "ternary get",
+    "adaptive get_or_intern",
+    "adaptive get",
+];
+
+fn read_words() -> Vec<String> {
+    let f = File::open(DICTIONARY).unwrap();
+    BufReader::new(&f)
+        .lines()
+        .map(|line| line.unwrap().trim().to_owned())
+        .collect()
+}
+
+/// `prefix_sizes` doubles from `SMALLEST` until the whole dictionary is covered.
+fn prefix_sizes(total: usize) -> Vec<usize> {
+    let mut sizes = Vec::new();
+    let mut size = SMALLEST;
+    while size < total {
+        sizes.push(size);
+        size *= 2;
+    }
+    sizes.push(total);
+    sizes
+}
+
+/// `bench_prefix` measures every operation over one prefix of the
+/// dictionary, appending a sample to each of the given series.
+fn bench_prefix(keys: &[String], series: &mut [Series]) {
+    let n = keys.len();
+    let ((dlb, ids), elapsed) = time(|timer| {
+        let mut dlb = DLB::new();
+        let ids: Vec<_> = keys
+            .iter()
+            .map(|key| dlb.get_or_intern(key.clone()))
+            .collect();
+        drop(timer);
+        (dlb, ids)
+    });
+    series[0].samples.push(Sample {
+        keys: n,
+        ops: n,
+        elapsed,
+        bytes: dlb.size_of(),
+    });
+
+    let stride = (n / LOOKUPS).max(1);
+    let sampled: Vec<usize> = (0..n).step_by(stride).collect();
+    let ops = sampled.len();
+
+    let (_, elapsed) = time(|_| {
+        for &i in sampled.iter() {
+            black_box(dlb.get(black_box(keys[i].clone())));
+        }
+    });
+    series[1].samples.push(Sample {
+        keys: n,
+        ops,
+        elapsed,
+        bytes: dlb.size_of(),
+    });
+
+    let (_, elapsed) = time(|_| {
+        for &i in sampled.iter() {
+            black_box(dlb.resolve(black_box(ids[i])));
+        }
+    });
+    series[2].samples.push(Sample {
+        keys: n,
+        ops,
+        elapsed,
+        bytes: dlb.size_of(),
+    });
+
+    let trie = dlb.freeze();
+    drop(dlb);
+    let (_, elapsed) = time(|_| {
+        for &i in sampled.iter() {
+            black_box(trie.get(black_box(keys[i].clone())));
+        }
+    });
+    series[3].samples.push(Sample {
+        keys: n,
+        ops,
+        elapsed,
+        bytes: trie.size_of(),
+    });
+
+    let (_, elapsed) = time(|_| {
+        for &i in sampled.iter() {
+            black_box(trie.resolve(black_box(ids[i])));
+        }
+    });
+    series[4].samples.push(Sample {
+        keys: n,
+        ops,
+        elapsed,
+        bytes: trie.size_of(),
+    });
+    drop(trie);
+
+    let mut sorted = keys.to_vec();
+    sorted.sort_unstable();
+    let (dlb, elapsed) = time(|_| DLB::<Identifier>::from_sorted_iter(&sorted).unwrap());
+    series[5].samples.push(Sample {
+        keys: n,
+        ops: n,
+        elapsed,
+        bytes: dlb.size_of(),
+    });
+    drop(dlb);
+
+    let (trie, elapsed) = time(|_| StaticTrie::<Identifier>::from_sorted_iter(&sorted).unwrap());
+    series[6].samples.push(Sample {
+        keys: n,
+        ops: n,
+        elapsed,
+        bytes: trie.size_of(),
+    });
+    drop(trie);
+
+    // The dictionary's identifiers are ranks, so look up the same
+    // keys by where they sort.
+    let dict: FrontCodedDict =
+        FrontCodedDict::from_sorted_iter(&sorted, FrontCodedDict::DEFAULT_BUCKET_SIZE).unwrap();
+    let (_, elapsed) = time(|_| {
+        for &i in sampled.iter() {
+            black_box(dict.get(black_box(sorted[i].clone())));
+        }
+    });
+    series[7].samples.push(Sample {
+        keys: n,
+        ops,
+        elapsed,
+        bytes: dict.size_of(),
+    });
+
+    let ranks: Vec<Identifier> = sampled
+        .iter()
+        .map(|&i| Identifier::from_usize(i).unwrap())
+        .collect();
+    let (_, elapsed) = time(|_| {
+        for &rank in ranks.iter() {
+            black_box(dict.resolve(black_box(rank)));
+        }
+    });
+    series[8].samples.push(Sample {
+        keys: n,
+        ops,
+        elapsed,
+        bytes: dict.size_of(),
+    });
+    drop(dict);
+
+    let (tst, elapsed) = time(|_| {
+        let mut tst = TernarySearchTrie::<Identifier>::new();
+        for key in keys.iter() {
+            tst.get_or_intern(key.clone());
+        }
+        tst
+    });
+    series[9].samples.push(Sample {
+        keys: n,
+        ops: n,
+        elapsed,
+        bytes: tst.size_of(),
+    });
+
+    let (_, elapsed) = time(|_| {
+        for &i in sampled.iter() {
+            black_box(tst.get(black_box(keys[i].clone())));
+        }
+    });
+    series[10].samples.push(Sample {
+        keys: n,
+        ops,
+        elapsed,
+        bytes: tst.size_of(),
+    });
+    drop(tst);
+
+    let (art, elapsed) = time(|_| {
+        let mut art = AdaptiveRadixTrie::<Identifier>::new();
+        for key in keys.iter() {
+            art.get_or_intern(key.clone());
+        }
+        art
+    });
+    series[11].samples.push(Sample {
+        keys: n,
+        ops: n,
+        elapsed,
+        bytes: art.size_of(),
+    });
+
+    let (_, elapsed) = time(|_| {
+        for &i in sampled.iter() {
+            black_box(art.get(black_box(keys[i].clone())));
+        }
+    });
+    series[12].samples.push(Sample {
+        keys: n,
+        ops,
+        elapsed,
+        bytes: art.size_of(),
+    });
+}
+
+fn main() {
+    let words = read_words();
+    let mut rng: ChaCha8Rng = SeedableRng::seed_from_u64(100);
+    let orders = ["sequential", "shuffled"];
+    let mut all_series: Vec<Vec<Series>> = orders
+        .iter()
+        .map(|order| {
+            OPERATIONS
+                .iter()
+                .map(|op| Series::new(&format!("{} {}", op, order)))
+                .collect()
+        })
+        .collect();
+
+    for size in prefix_sizes(words.len()) {
+        for (order, series) in orders.iter().zip(all_series.iter_mut()) {
+            let mut keys = words[..size].to_vec();
+            if *order == "shuffled" {
+                keys.shuffle(&mut rng);
+            }
+            eprintln!("Benchmarking {} {} keys…", size, order);
+            bench_prefix(&keys, series);
+        }
+    }
+
+    let mut out = BufWriter::new(File::create(OUTPUT).unwrap());
+    for op in 0..OPERATIONS.len() {
+        for series in all_series.iter() {
+            series[op].write_to(&mut out).unwrap();
+        }
+    }
+    out.flush().unwrap();
+    eprintln!("Wrote {}", OUTPUT);
+}
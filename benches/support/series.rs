@@ -0,0 +1,51 @@
+use std::io::{self, Write};
+use std::time::Duration;
+
+/// `Sample` is one row of a benchmark series.
+pub struct Sample {
+    pub keys: usize,
+    pub ops: usize,
+    pub elapsed: Duration,
+    pub bytes: usize,
+}
+
+impl Sample {
+    pub fn nanos_per_op(&self) -> f64 {
+        self.elapsed.as_nanos() as f64 / self.ops.max(1) as f64
+    }
+}
+
+/// `Series` is a named sequence of samples. Series are written as
+/// gnuplot data blocks, so each one can be selected with `index`.
+pub struct Series {
+    pub name: String,
+    pub samples: Vec<Sample>,
+}
+
+impl Series {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            samples: Vec::new(),
+        }
+    }
+
+    /// `write_to` emits a comment naming the series, then one
+    /// `keys,ns_per_op,bytes` row per sample, then the two blank
+    /// lines gnuplot uses to separate blocks.
+    pub fn write_to<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "# {}", self.name)?;
+        writeln!(out, "# keys,ns_per_op,bytes")?;
+        for sample in self.samples.iter() {
+            writeln!(
+                out,
+                "{},{:.2},{}",
+                sample.keys,
+                sample.nanos_per_op(),
+                sample.bytes
+            )?;
+        }
+        writeln!(out)?;
+        writeln!(out)
+    }
+}
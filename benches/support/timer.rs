@@ -0,0 +1,38 @@
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::time::{Duration, Instant};
+
+/// `Timer` measures the time between its creation and its drop.
+/// When it's dropped, the elapsed time is sent down a one-shot channel.
+pub struct Timer {
+    start: Instant,
+    sender: SyncSender<Duration>,
+}
+
+impl Timer {
+    pub fn start() -> (Timer, Receiver<Duration>) {
+        let (sender, receiver) = sync_channel(1);
+        let timer = Timer {
+            start: Instant::now(),
+            sender,
+        };
+        (timer, receiver)
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        // The receiver may have hung up; there's nobody left to tell.
+        let _ = self.sender.send(self.start.elapsed());
+    }
+}
+
+/// `time` runs `f`, injecting a running timer. `f` may drop the timer
+/// early to exclude any teardown from the measurement; otherwise it
+/// stops when `f` returns. The result of `f` is returned alongside
+/// the measured duration.
+pub fn time<T, F: FnOnce(Timer) -> T>(f: F) -> (T, Duration) {
+    let (timer, receiver) = Timer::start();
+    let result = f(timer);
+    let elapsed = receiver.recv().expect("the timer is dropped by now");
+    (result, elapsed)
+}
@@ -0,0 +1,9 @@
+#!/bin/sh
+# Regenerates bench.dat and every plot drawn from it.
+set -e
+cd "$(dirname "$0")/.."
+cargo bench --bench dictionary
+cd plotters
+for plot in sequential.plt shuffled.plt memory.plt; do
+    gnuplot "$plot"
+done
@@ -0,0 +1,16 @@
+set title "Memory used by the DLB"
+set xlabel "Words"
+set ylabel "bytes"
+set key left top
+set term png
+set datafile separator ","
+set output "words-vs-memory.png"
+plot "bench.dat" index 0 using 1:3 with line title "DLB (sequential)", \
+     "bench.dat" index 1 using 1:3 with line title "DLB (shuffled)", \
+     "bench.dat" index 6 using 1:3 with line title "StaticTrie (sequential)", \
+     "bench.dat" index 7 using 1:3 with line title "StaticTrie (shuffled)", \
+     "bench.dat" index 14 using 1:3 with line title "FrontCodedDict", \
+     "bench.dat" index 18 using 1:3 with line title "TernarySearchTrie (sequential)", \
+     "bench.dat" index 19 using 1:3 with line title "TernarySearchTrie (shuffled)", \
+     "bench.dat" index 22 using 1:3 with line title "AdaptiveRadixTrie (sequential)", \
+     "bench.dat" index 23 using 1:3 with line title "AdaptiveRadixTrie (shuffled)"
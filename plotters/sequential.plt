@@ -1,10 +1,16 @@
-set title "Benchmarking the DLB"
+set title "Benchmarking the DLB (sequential keys)"
 set xlabel "Words"
-set ylabel "time"
+set ylabel "ns per operation"
 set key left top
 set term png
 set datafile separator ","
 set output "words-vs-time.png"
-plot "bench.dat" index 0:0 with line, "bench.dat" index 1:1 with line
-set style line 1 linecolor rgb "violet"
-set style line 2 linecolor rgb "chartreuse"
+plot "bench.dat" index 0 using 1:2 with line title "get_or_intern", \
+     "bench.dat" index 2 using 1:2 with line title "get", \
+     "bench.dat" index 4 using 1:2 with line title "resolve", \
+     "bench.dat" index 6 using 1:2 with line title "static get", \
+     "bench.dat" index 8 using 1:2 with line title "static resolve", \
+     "bench.dat" index 10 using 1:2 with line title "from_sorted_iter", \
+     "bench.dat" index 12 using 1:2 with line title "static from_sorted_iter", \
+     "bench.dat" index 14 using 1:2 with line title "front-coded get", \
+     "bench.dat" index 16 using 1:2 with line title "front-coded resolve"
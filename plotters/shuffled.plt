@@ -0,0 +1,12 @@
+set title "Benchmarking the DLB (shuffled keys)"
+set xlabel "Words"
+set ylabel "ns per operation"
+set key left top
+set term png
+set datafile separator ","
+set output "shuffled-words-vs-time.png"
+plot "bench.dat" index 1 using 1:2 with line title "get_or_intern", \
+     "bench.dat" index 3 using 1:2 with line title "get", \
+     "bench.dat" index 5 using 1:2 with line title "resolve", \
+     "bench.dat" index 7 using 1:2 with line title "static get", \
+     "bench.dat" index 9 using 1:2 with line title "static resolve"
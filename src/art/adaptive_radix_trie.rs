@@ -0,0 +1,391 @@
+use crate::art::art_node::ArtNode;
+use crate::art::iter::Iter;
+use crate::dtrie::iter::into_string;
+use crate::dtrie::node_handle::NodeHandle;
+use crate::dtrie::{Exhausted, Identifier, MemoryUsage, Removed, Symbol};
+use std::iter::FromIterator;
+use std::mem::size_of;
+
+/// The root always lives at the front of the node arena, once there is
+/// one. Its label is empty, and it holds the empty string's identifier.
+const ROOT: usize = 0;
+
+/// `AdaptiveRadixTrie` is a dynamic trie whose nodes pick how to store
+/// their children by how many they have. Up to 4 children are kept in a
+/// small sorted array inside the node, up to 16 in a larger one, up to 48
+/// behind a table indexed by byte, and beyond that in an array with a
+/// place for every byte. Finding a child takes a short search at worst,
+/// however wide the node, where a `DLB` scans every sibling before it.
+/// Labels are compressed as in a `DLB`, so chains of single children
+/// take one node.
+///
+/// Identifiers are handed out in order, starting from zero. Removing a
+/// key retires its identifier rather than handing it out again, as with
+/// `IdPolicy::Sequential`.
+pub struct AdaptiveRadixTrie<S = Identifier> {
+    nodes: Vec<ArtNode<S>>,
+    // Arena slots left by removed nodes, reused before the arena grows.
+    free_nodes: Vec<NodeHandle>,
+    // The node completing each identifier, indexed by identifier.
+    ids: Vec<NodeHandle>,
+    len: usize,
+}
+
+impl<S: Symbol> Default for AdaptiveRadixTrie<S> {
+    fn default() -> Self {
+        Self {
+            nodes: Vec::new(),
+            free_nodes: Vec::new(),
+            ids: Vec::new(),
+            len: 0,
+        }
+    }
+}
+
+impl<S: Symbol, K: AsRef<str>> FromIterator<K> for AdaptiveRadixTrie<S> {
+    fn from_iter<I: IntoIterator<Item = K>>(keys: I) -> Self {
+        let mut trie = Self::default();
+        for key in keys {
+            trie.get_or_intern(key.as_ref().to_owned());
+        }
+        trie
+    }
+}
+
+impl<S: Symbol> AdaptiveRadixTrie<S> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn count_nodes(&self) -> u64 {
+        (self.nodes.len() - self.free_nodes.len()) as u64
+    }
+
+    /// `count_layouts` counts the nodes storing their children as a
+    /// `Node4`, `Node16`, `Node48` and `Node256`, in that order. Leaves
+    /// count as `Node4`s.
+    pub fn count_layouts(&self) -> [u64; 4] {
+        let mut counts = [0; 4];
+        let mut stack: Vec<NodeHandle> = self.root().into_iter().collect();
+        while let Some(handle) = stack.pop() {
+            let children = &self.node(handle).children;
+            counts[children.kind()] += 1;
+            stack.extend(children.handles());
+        }
+        counts
+    }
+
+    fn root(&self) -> Option<NodeHandle> {
+        Some(NodeHandle::new(ROOT)).filter(|_| !self.nodes.is_empty())
+    }
+
+    fn node(&self, handle: NodeHandle) -> &ArtNode<S> {
+        &self.nodes[handle.index()]
+    }
+
+    fn node_mut(&mut self, handle: NodeHandle) -> &mut ArtNode<S> {
+        &mut self.nodes[handle.index()]
+    }
+
+    /// `child` is the child of `handle` whose label starts like `rest`.
+    fn child(&self, handle: NodeHandle, rest: &[u8]) -> Option<NodeHandle> {
+        self.node(handle).children.find(*rest.first()?)
+    }
+
+    pub fn contains(&self, s: String) -> bool {
+        self.get(s).is_some()
+    }
+
+    pub fn get(&self, s: String) -> Option<S> {
+        let handle = self.find(s.as_bytes())?;
+        self.node(handle).id
+    }
+
+    /// `find` returns the node whose label ends exactly where `key` does.
+    fn find(&self, key: &[u8]) -> Option<NodeHandle> {
+        let mut handle = self.root()?;
+        let mut rest = key;
+        while !rest.is_empty() {
+            handle = self.child(handle, rest)?;
+            let label = &self.node(handle).label;
+            if !rest.starts_with(label) {
+                return None;
+            }
+            rest = &rest[label.len()..];
+        }
+        Some(handle)
+    }
+
+    /// `descend` follows `prefix` from the root. It returns the node whose
+    /// subtree holds every key extending the prefix, along with the bytes
+    /// which precede that node's label. The prefix may end partway
+    /// through the node's label.
+    fn descend(&self, prefix: &[u8]) -> Option<(NodeHandle, Vec<u8>)> {
+        let mut handle = self.root()?;
+        let mut rest = prefix;
+        let mut thus_far = Vec::new();
+        while !rest.is_empty() {
+            thus_far.extend_from_slice(&self.node(handle).label);
+            handle = self.child(handle, rest)?;
+            let label = &self.node(handle).label;
+            let shared = count_shared_prefix(label, rest);
+            if shared == rest.len() {
+                break;
+            }
+            if shared < label.len() {
+                return None;
+            }
+            rest = &rest[shared..];
+        }
+        Some((handle, thus_far))
+    }
+
+    /// `get_or_intern` returns the identifier of `s`, adding it if needed.
+    /// Panics if the symbol type has no identifiers left.
+    pub fn get_or_intern(&mut self, s: String) -> S {
+        match self.try_get_or_intern(s) {
+            Ok(id) => id,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// `try_get_or_intern` is `get_or_intern`, but returns an error
+    /// rather than panicking when the identifiers run out.
+    pub fn try_get_or_intern(&mut self, s: String) -> Result<S, Exhausted> {
+        let mut handle = match self.root() {
+            Some(root) => root,
+            None => self.push_node(&[], NodeHandle::NIL),
+        };
+        let mut rest = s.as_bytes();
+        while !rest.is_empty() {
+            let child = match self.child(handle, rest) {
+                Some(child) => child,
+                None => {
+                    // Nothing shares a prefix with these bytes; make a new leaf.
+                    let id = self.new_id()?;
+                    let leaf = self.push_node(rest, handle);
+                    self.node_mut(handle).children.insert(rest[0], leaf);
+                    self.assign(leaf, id);
+                    return Ok(id);
+                }
+            };
+            let shared = count_shared_prefix(&self.node(child).label, rest);
+            handle = if shared < self.node(child).label.len() {
+                // The key leaves the label partway through.
+                self.split(child, shared)
+            } else {
+                child
+            };
+            rest = &rest[shared..];
+        }
+        if let Some(id) = self.node(handle).id {
+            return Ok(id);
+        }
+        let id = self.new_id()?;
+        self.assign(handle, id);
+        Ok(id)
+    }
+
+    /// `split` cuts the label of the node at `handle` after `at` bytes,
+    /// moving the first part into a new parent, which it returns.
+    fn split(&mut self, handle: NodeHandle, at: usize) -> NodeHandle {
+        let label = std::mem::take(&mut self.node_mut(handle).label);
+        let parent = self.node(handle).parent;
+        let middle = self.push_node(&label[..at], parent);
+        self.node_mut(parent).children.replace(label[0], middle);
+        self.node_mut(middle).children.insert(label[at], handle);
+        let node = self.node_mut(handle);
+        node.label = label[at..].into();
+        node.parent = middle;
+        middle
+    }
+
+    /// `push_node` adds a node to the arena, reusing a free slot if
+    /// there is one. The caller must link it to its parent.
+    fn push_node(&mut self, label: &[u8], parent: NodeHandle) -> NodeHandle {
+        let node = ArtNode::new(label, parent);
+        match self.free_nodes.pop() {
+            Some(handle) => {
+                *self.node_mut(handle) = node;
+                handle
+            }
+            None => {
+                self.nodes.push(node);
+                NodeHandle::new(self.nodes.len() - 1)
+            }
+        }
+    }
+
+    /// `free_node` empties the node at `handle` and marks its slot for reuse.
+    fn free_node(&mut self, handle: NodeHandle) {
+        *self.node_mut(handle) = ArtNode::new(&[], NodeHandle::NIL);
+        self.free_nodes.push(handle);
+    }
+
+    /// `new_id` hands out the next identifier, unless the symbol
+    /// type has run out of them. The caller must `assign` it.
+    fn new_id(&mut self) -> Result<S, Exhausted> {
+        let id = S::from_usize(self.ids.len()).ok_or(Exhausted)?;
+        self.ids.push(NodeHandle::NIL);
+        self.len += 1;
+        Ok(id)
+    }
+
+    fn assign(&mut self, handle: NodeHandle, id: S) {
+        self.node_mut(handle).id = Some(id);
+        self.ids[id.to_usize()] = handle;
+    }
+
+    /// `remove` takes `s` out of the trie, returning what happened to its
+    /// identifier, or `None` if it wasn't present. The identifier isn't
+    /// handed out again. Nodes left with no key below them are freed,
+    /// and a node left with one child is merged into it, so the trie has
+    /// the same shape as if `s` had never been added.
+    pub fn remove(&mut self, s: String) -> Option<Removed<S>> {
+        let handle = self.find(s.as_bytes())?;
+        let id = self.node_mut(handle).id.take()?;
+        self.ids[id.to_usize()] = NodeHandle::NIL;
+        self.len -= 1;
+        self.prune(handle);
+        if self.len == 0 {
+            // Only the root is left, and a new trie has no nodes at all.
+            self.nodes.clear();
+            self.free_nodes.clear();
+        }
+        Some(Removed { id, moved: None })
+    }
+
+    /// `prune` frees the node at `handle` if it is a leaf without a key,
+    /// then does the same for its parent, and so on up the trie. A node
+    /// without a key but with a single child is merged into the child.
+    fn prune(&mut self, mut handle: NodeHandle) {
+        while handle.index() != ROOT && self.node(handle).id.is_none() {
+            let parent = self.node(handle).parent;
+            let first = self.node(handle).label[0];
+            match self.node(handle).children.len() {
+                0 => {
+                    self.node_mut(parent).children.remove(first);
+                    self.free_node(handle);
+                    handle = parent;
+                }
+                1 => {
+                    let child = self.node(handle).children.handles()[0];
+                    let mut label = self.node(handle).label.to_vec();
+                    label.extend_from_slice(&self.node(child).label);
+                    let node = self.node_mut(child);
+                    node.label = label.into();
+                    node.parent = parent;
+                    self.node_mut(parent).children.replace(first, child);
+                    self.free_node(handle);
+                    return;
+                }
+                _ => return,
+            }
+        }
+    }
+
+    /// `contains_id` reports whether `id` belongs to a key in the trie.
+    pub fn contains_id(&self, id: S) -> bool {
+        self.node_of(id).is_some()
+    }
+
+    fn node_of(&self, id: S) -> Option<NodeHandle> {
+        let handle = *self.ids.get(id.to_usize())?;
+        Some(handle).filter(|handle| !handle.is_nil())
+    }
+
+    /// `resolve` finds the node holding `id` and follows
+    /// the parent links up to the root to rebuild its key.
+    pub fn resolve(&self, id: S) -> Option<String> {
+        let mut handle = self.node_of(id)?;
+        let mut labels = Vec::new();
+        while !handle.is_nil() {
+            labels.push(&self.node(handle).label);
+            handle = self.node(handle).parent;
+        }
+        let bytes: Vec<u8> = labels
+            .into_iter()
+            .rev()
+            .flat_map(|l| l.iter())
+            .copied()
+            .collect();
+        Some(into_string(bytes))
+    }
+
+    /// `iter` visits every key in the trie, along with its identifier.
+    /// Keys are visited in lexicographic order.
+    pub fn iter(&self) -> Iter<'_, S> {
+        let stack = self.root().map(|root| (root, Vec::new()));
+        Iter::new(&self.nodes, stack.into_iter().collect())
+    }
+
+    /// `prefix_iter` visits every key which starts with `prefix`,
+    /// in lexicographic order.
+    pub fn prefix_iter(&self, prefix: String) -> Iter<'_, S> {
+        let stack = self.descend(prefix.as_bytes());
+        Iter::new(&self.nodes, stack.into_iter().collect())
+    }
+
+    /// `longest_prefix` is the longest key which `s` starts with, along
+    /// with its identifier, or `None` if no key is a prefix of `s`.
+    pub fn longest_prefix(&self, s: String) -> Option<(String, S)> {
+        let key = s.as_bytes();
+        let mut handle = self.root()?;
+        let mut longest = self.node(handle).id.map(|id| (0, id));
+        let mut depth = 0;
+        while let Some(child) = self.child(handle, &key[depth..]) {
+            let label = &self.node(child).label;
+            if !key[depth..].starts_with(label) {
+                break;
+            }
+            depth += label.len();
+            handle = child;
+            if let Some(id) = self.node(handle).id {
+                longest = Some((depth, id));
+            }
+        }
+        longest.map(|(len, id)| (into_string(key[..len].to_vec()), id))
+    }
+
+    /// `size_of` is the total memory used by this trie,
+    /// including the struct itself and everything on the heap.
+    pub fn size_of(&self) -> usize {
+        size_of::<Self>() + self.heap_size().total()
+    }
+
+    /// `heap_size` breaks down the heap memory owned by this trie. The
+    /// `Node16`, `Node48` and `Node256` layouts count as child containers,
+    /// as do the identifier table and free list. Arena slots awaiting
+    /// reuse count as wasted capacity.
+    pub fn heap_size(&self) -> MemoryUsage {
+        let node_size = size_of::<ArtNode<S>>();
+        let handle_size = size_of::<NodeHandle>();
+        let free = self.free_nodes.len();
+        let labels: usize = self.nodes.iter().map(|node| node.label.len()).sum();
+        let layouts: usize = self
+            .nodes
+            .iter()
+            .map(|node| node.children.heap_size())
+            .sum();
+        MemoryUsage {
+            nodes: (self.nodes.len() - free) * node_size,
+            labels,
+            child_containers: layouts + (self.ids.len() + free) * handle_size,
+            wasted_capacity: (self.nodes.capacity() - self.nodes.len() + free) * node_size
+                + (self.ids.capacity() - self.ids.len()) * handle_size
+                + (self.free_nodes.capacity() - free) * handle_size,
+        }
+    }
+}
+
+fn count_shared_prefix(left: &[u8], right: &[u8]) -> usize {
+    left.iter().zip(right).take_while(|(l, r)| l == r).count()
+}
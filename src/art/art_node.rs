@@ -0,0 +1,25 @@
+use crate::art::children::Children;
+use crate::dtrie::node_handle::NodeHandle;
+
+/// `ArtNode` is a node of an `AdaptiveRadixTrie`. Its label is the run
+/// of bytes on the edge leading into it, so a chain of nodes with one
+/// child each is stored as one node. The first byte of the label is
+/// the one its parent finds it by.
+pub(crate) struct ArtNode<S> {
+    pub label: Box<[u8]>,
+    /// `id` is set if a key ends at this node.
+    pub id: Option<S>,
+    pub parent: NodeHandle,
+    pub children: Children,
+}
+
+impl<S> ArtNode<S> {
+    pub fn new(label: &[u8], parent: NodeHandle) -> Self {
+        Self {
+            label: label.into(),
+            id: None,
+            parent,
+            children: Children::default(),
+        }
+    }
+}
@@ -0,0 +1,319 @@
+use crate::dtrie::node_handle::NodeHandle;
+use std::iter::FromIterator;
+use std::mem::size_of;
+
+/// `SHRINK_16`, `SHRINK_48` and `SHRINK_256` are the sizes at which a
+/// node moves down to the next smaller layout. They sit below the size
+/// the smaller layout is full at, so a node whose fan-out hovers around
+/// a boundary doesn't move back and forth on every change.
+const SHRINK_16: usize = 3;
+const SHRINK_48: usize = 12;
+const SHRINK_256: usize = 40;
+
+/// `Children` finds a node's children by the first byte of their labels.
+/// It starts as a `Node4` and moves to larger layouts as children are
+/// added, and back down as they are removed. Only the `Node4` is stored
+/// inline, which keeps leaves small; the rest live on the heap.
+pub(crate) enum Children {
+    Node4(Sorted<4>),
+    Node16(Box<Sorted<16>>),
+    Node48(Box<Node48>),
+    Node256(Box<Node256>),
+}
+
+impl Default for Children {
+    fn default() -> Self {
+        Children::Node4(Sorted::default())
+    }
+}
+
+impl Children {
+    pub fn len(&self) -> usize {
+        match self {
+            Children::Node4(node) => node.len as usize,
+            Children::Node16(node) => node.len as usize,
+            Children::Node48(node) => node.len as usize,
+            Children::Node256(node) => node.len as usize,
+        }
+    }
+
+    /// `kind` is which layout is in use, from zero for `Node4`
+    /// to three for `Node256`.
+    pub fn kind(&self) -> usize {
+        match self {
+            Children::Node4(_) => 0,
+            Children::Node16(_) => 1,
+            Children::Node48(_) => 2,
+            Children::Node256(_) => 3,
+        }
+    }
+
+    pub fn find(&self, byte: u8) -> Option<NodeHandle> {
+        match self {
+            Children::Node4(node) => node.find(byte),
+            Children::Node16(node) => node.find(byte),
+            Children::Node48(node) => node.find(byte),
+            Children::Node256(node) => node.find(byte),
+        }
+    }
+
+    /// `insert` adds a child which isn't present yet,
+    /// moving to a larger layout first if this one is full.
+    pub fn insert(&mut self, byte: u8, handle: NodeHandle) {
+        let grown = match self {
+            Children::Node4(node) if node.is_full() => {
+                Some(Children::Node16(Box::new(node.entries().collect())))
+            }
+            Children::Node16(node) if node.is_full() => {
+                Some(Children::Node48(Box::new(node.entries().collect())))
+            }
+            Children::Node48(node) if node.is_full() => {
+                Some(Children::Node256(Box::new(node.entries().collect())))
+            }
+            _ => None,
+        };
+        if let Some(grown) = grown {
+            *self = grown;
+        }
+        match self {
+            Children::Node4(node) => node.insert(byte, handle),
+            Children::Node16(node) => node.insert(byte, handle),
+            Children::Node48(node) => node.insert(byte, handle),
+            Children::Node256(node) => node.insert(byte, handle),
+        }
+    }
+
+    /// `replace` points the child found by `byte` at another node.
+    pub fn replace(&mut self, byte: u8, handle: NodeHandle) {
+        self.remove_entry(byte);
+        self.insert(byte, handle);
+    }
+
+    /// `remove` takes out the child found by `byte`, moving to a
+    /// smaller layout if few enough children are left.
+    pub fn remove(&mut self, byte: u8) {
+        self.remove_entry(byte);
+        let len = self.len();
+        let shrunk = match self {
+            Children::Node16(node) if len <= SHRINK_16 => {
+                Some(Children::Node4(node.entries().collect()))
+            }
+            Children::Node48(node) if len <= SHRINK_48 => {
+                Some(Children::Node16(Box::new(node.entries().collect())))
+            }
+            Children::Node256(node) if len <= SHRINK_256 => {
+                Some(Children::Node48(Box::new(node.entries().collect())))
+            }
+            _ => None,
+        };
+        if let Some(shrunk) = shrunk {
+            *self = shrunk;
+        }
+    }
+
+    fn remove_entry(&mut self, byte: u8) {
+        match self {
+            Children::Node4(node) => node.remove(byte),
+            Children::Node16(node) => node.remove(byte),
+            Children::Node48(node) => node.remove(byte),
+            Children::Node256(node) => node.remove(byte),
+        }
+    }
+
+    /// `handles` lists the children in order of their first bytes.
+    pub fn handles(&self) -> Vec<NodeHandle> {
+        match self {
+            Children::Node4(node) => node.entries().map(|(_, handle)| handle).collect(),
+            Children::Node16(node) => node.entries().map(|(_, handle)| handle).collect(),
+            Children::Node48(node) => node.entries().map(|(_, handle)| handle).collect(),
+            Children::Node256(node) => node.entries().map(|(_, handle)| handle).collect(),
+        }
+    }
+
+    /// `heap_size` is the size of the boxed layouts. A `Node4` is
+    /// stored in the node itself, so it owns no heap memory.
+    pub fn heap_size(&self) -> usize {
+        match self {
+            Children::Node4(_) => 0,
+            Children::Node16(_) => size_of::<Sorted<16>>(),
+            Children::Node48(_) => size_of::<Node48>(),
+            Children::Node256(_) => size_of::<Node256>(),
+        }
+    }
+}
+
+/// `Sorted` holds up to `N` children, keeping their first bytes sorted
+/// so they can be searched. It is the layout of `Node4` and `Node16`.
+#[derive(Clone)]
+pub(crate) struct Sorted<const N: usize> {
+    len: u8,
+    bytes: [u8; N],
+    handles: [NodeHandle; N],
+}
+
+impl<const N: usize> Default for Sorted<N> {
+    fn default() -> Self {
+        Self {
+            len: 0,
+            bytes: [0; N],
+            handles: [NodeHandle::NIL; N],
+        }
+    }
+}
+
+impl<const N: usize> Sorted<N> {
+    fn is_full(&self) -> bool {
+        self.len as usize == N
+    }
+
+    fn position(&self, byte: u8) -> Result<usize, usize> {
+        self.bytes[..self.len as usize].binary_search(&byte)
+    }
+
+    fn find(&self, byte: u8) -> Option<NodeHandle> {
+        let position = self.position(byte).ok()?;
+        Some(self.handles[position])
+    }
+
+    fn insert(&mut self, byte: u8, handle: NodeHandle) {
+        let len = self.len as usize;
+        let position = self.position(byte).unwrap_err();
+        self.bytes.copy_within(position..len, position + 1);
+        self.handles.copy_within(position..len, position + 1);
+        self.bytes[position] = byte;
+        self.handles[position] = handle;
+        self.len += 1;
+    }
+
+    fn remove(&mut self, byte: u8) {
+        let len = self.len as usize;
+        if let Ok(position) = self.position(byte) {
+            self.bytes.copy_within(position + 1..len, position);
+            self.handles.copy_within(position + 1..len, position);
+            self.len -= 1;
+        }
+    }
+
+    fn entries(&self) -> impl Iterator<Item = (u8, NodeHandle)> + '_ {
+        let len = self.len as usize;
+        self.bytes[..len]
+            .iter()
+            .copied()
+            .zip(self.handles[..len].iter().copied())
+    }
+}
+
+impl<const N: usize> FromIterator<(u8, NodeHandle)> for Sorted<N> {
+    /// `from_iter` expects the entries in order and no more than `N`.
+    fn from_iter<I: IntoIterator<Item = (u8, NodeHandle)>>(entries: I) -> Self {
+        let mut node = Self::default();
+        for (byte, handle) in entries {
+            let len = node.len as usize;
+            node.bytes[len] = byte;
+            node.handles[len] = handle;
+            node.len += 1;
+        }
+        node
+    }
+}
+
+/// `Node48` holds up to 48 children, with a table saying where each
+/// byte's child is kept. Lookups take one step, for less memory than
+/// a `Node256`. Children are kept at the front of `handles`.
+pub(crate) struct Node48 {
+    len: u8,
+    // One more than where each byte's child is, or zero if it has none.
+    slots: [u8; 256],
+    handles: [NodeHandle; 48],
+}
+
+impl Node48 {
+    fn is_full(&self) -> bool {
+        self.len == 48
+    }
+
+    fn find(&self, byte: u8) -> Option<NodeHandle> {
+        let slot = self.slots[byte as usize].checked_sub(1)?;
+        Some(self.handles[slot as usize])
+    }
+
+    fn insert(&mut self, byte: u8, handle: NodeHandle) {
+        self.handles[self.len as usize] = handle;
+        self.len += 1;
+        self.slots[byte as usize] = self.len;
+    }
+
+    /// `remove` fills the gap with the last child, to keep them together.
+    fn remove(&mut self, byte: u8) {
+        let slot = match self.slots[byte as usize].checked_sub(1) {
+            Some(slot) => slot,
+            None => return,
+        };
+        self.slots[byte as usize] = 0;
+        self.len -= 1;
+        if slot != self.len {
+            self.handles[slot as usize] = self.handles[self.len as usize];
+            let last = self.slots.iter().position(|&s| s == self.len + 1).unwrap();
+            self.slots[last] = slot + 1;
+        }
+    }
+
+    fn entries(&self) -> impl Iterator<Item = (u8, NodeHandle)> + '_ {
+        (0..=u8::MAX).filter_map(move |byte| self.find(byte).map(|handle| (byte, handle)))
+    }
+}
+
+impl FromIterator<(u8, NodeHandle)> for Node48 {
+    fn from_iter<I: IntoIterator<Item = (u8, NodeHandle)>>(entries: I) -> Self {
+        let mut node = Self {
+            len: 0,
+            slots: [0; 256],
+            handles: [NodeHandle::NIL; 48],
+        };
+        for (byte, handle) in entries {
+            node.insert(byte, handle);
+        }
+        node
+    }
+}
+
+/// `Node256` has a place for every byte's child.
+pub(crate) struct Node256 {
+    len: u16,
+    handles: [NodeHandle; 256],
+}
+
+impl Node256 {
+    fn find(&self, byte: u8) -> Option<NodeHandle> {
+        Some(self.handles[byte as usize]).filter(|handle| !handle.is_nil())
+    }
+
+    fn insert(&mut self, byte: u8, handle: NodeHandle) {
+        self.handles[byte as usize] = handle;
+        self.len += 1;
+    }
+
+    fn remove(&mut self, byte: u8) {
+        if !self.handles[byte as usize].is_nil() {
+            self.handles[byte as usize] = NodeHandle::NIL;
+            self.len -= 1;
+        }
+    }
+
+    fn entries(&self) -> impl Iterator<Item = (u8, NodeHandle)> + '_ {
+        (0..=u8::MAX).filter_map(move |byte| self.find(byte).map(|handle| (byte, handle)))
+    }
+}
+
+impl FromIterator<(u8, NodeHandle)> for Node256 {
+    fn from_iter<I: IntoIterator<Item = (u8, NodeHandle)>>(entries: I) -> Self {
+        let mut node = Self {
+            len: 0,
+            handles: [NodeHandle::NIL; 256],
+        };
+        for (byte, handle) in entries {
+            node.insert(byte, handle);
+        }
+        node
+    }
+}
@@ -0,0 +1,36 @@
+use crate::art::art_node::ArtNode;
+use crate::dtrie::iter::into_string;
+use crate::dtrie::node_handle::NodeHandle;
+use crate::dtrie::Symbol;
+
+/// `Iter` walks an `AdaptiveRadixTrie` depth-first, yielding every
+/// key in lexicographic order together with its identifier.
+pub struct Iter<'a, S> {
+    nodes: &'a [ArtNode<S>],
+    // Each entry holds a node and the bytes preceding its label.
+    stack: Vec<(NodeHandle, Vec<u8>)>,
+}
+
+impl<'a, S> Iter<'a, S> {
+    pub(crate) fn new(nodes: &'a [ArtNode<S>], stack: Vec<(NodeHandle, Vec<u8>)>) -> Self {
+        Self { nodes, stack }
+    }
+}
+
+impl<'a, S: Symbol> Iterator for Iter<'a, S> {
+    type Item = (String, S);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((handle, mut path)) = self.stack.pop() {
+            let node = &self.nodes[handle.index()];
+            path.extend_from_slice(&node.label);
+            for child in node.children.handles().into_iter().rev() {
+                self.stack.push((child, path.clone()));
+            }
+            if let Some(id) = node.id {
+                return Some((into_string(path), id));
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,7 @@
+pub use adaptive_radix_trie::AdaptiveRadixTrie;
+pub use iter::Iter;
+
+mod adaptive_radix_trie;
+mod art_node;
+mod children;
+mod iter;
@@ -0,0 +1,208 @@
+use sdtrie::dtrie::{Identifier, MemoryUsage, DLB};
+use sdtrie::strie::StaticTrie;
+use std::env;
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::process;
+
+const USAGE: &str = "Usage: sdtrie <command> [arguments]
+
+Commands:
+    build <words.txt> <out.trie>      Intern every line of a word list
+    stats <trie>                      Print node counts, depths and memory
+    get <trie> <key>                  Print the identifier of a key
+    resolve <trie> <id>               Print the key behind an identifier
+    prefix <trie> <prefix>            List every key starting with a prefix
+    dot <trie>                        Render a dynamic trie as Graphviz DOT
+    freeze <trie> <out.strie>         Pack a dynamic trie into a static one
+    freeze --ranked <trie> <out.strie>
+                                      Renumber keys by rank while packing,
+                                      printing each old and new identifier";
+
+type CliResult = Result<(), Box<dyn Error>>;
+
+/// `Trie` is whichever kind of trie was found in a file.
+enum Trie {
+    Dynamic(Box<DLB>),
+    Static(StaticTrie),
+}
+
+impl Trie {
+    fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let mut bytes = Vec::new();
+        File::open(path)?.read_to_end(&mut bytes)?;
+        if bytes.starts_with(DLB::MAGIC) {
+            Ok(Trie::Dynamic(Box::new(DLB::read_from(
+                &mut bytes.as_slice(),
+            )?)))
+        } else if bytes.starts_with(StaticTrie::MAGIC) {
+            Ok(Trie::Static(StaticTrie::read_from(&mut bytes.as_slice())?))
+        } else {
+            Err(format!("{} is not a trie file", path).into())
+        }
+    }
+
+    fn get(&self, key: String) -> Option<Identifier> {
+        match self {
+            Trie::Dynamic(dlb) => dlb.get(key),
+            Trie::Static(trie) => trie.get(key),
+        }
+    }
+
+    fn resolve(&self, id: Identifier) -> Option<String> {
+        match self {
+            Trie::Dynamic(dlb) => dlb.resolve(id),
+            Trie::Static(trie) => trie.resolve(id),
+        }
+    }
+
+    fn prefix_iter(&self, prefix: String) -> Box<dyn Iterator<Item = (String, Identifier)> + '_> {
+        match self {
+            Trie::Dynamic(dlb) => Box::new(dlb.prefix_iter(prefix)),
+            Trie::Static(trie) => Box::new(trie.prefix_iter(prefix)),
+        }
+    }
+
+    fn count_nodes(&self) -> u64 {
+        match self {
+            Trie::Dynamic(dlb) => dlb.count_nodes(),
+            Trie::Static(trie) => trie.count_nodes(),
+        }
+    }
+
+    fn depth_histogram(&self) -> Vec<u64> {
+        match self {
+            Trie::Dynamic(dlb) => dlb.depth_histogram(),
+            Trie::Static(trie) => trie.depth_histogram(),
+        }
+    }
+
+    fn heap_size(&self) -> MemoryUsage {
+        match self {
+            Trie::Dynamic(dlb) => dlb.heap_size(),
+            Trie::Static(trie) => trie.heap_size(),
+        }
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
+    let result = match args.as_slice() {
+        ["build", words, out] => build(words, out),
+        ["stats", trie] => stats(trie),
+        ["get", trie, key] => get(trie, key),
+        ["resolve", trie, id] => resolve(trie, id),
+        ["prefix", trie, key] => prefix(trie, key),
+        ["dot", trie] => dot(trie),
+        ["freeze", trie, out] => freeze(trie, out, false),
+        ["freeze", "--ranked", trie, out] => freeze(trie, out, true),
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+    if let Err(err) = result {
+        eprintln!("sdtrie: {}", err);
+        process::exit(1);
+    }
+}
+
+fn build(words: &str, out: &str) -> CliResult {
+    let mut dlb = DLB::new();
+    let file = BufReader::new(File::open(words)?);
+    // Keys are taken exactly as written, less the line ending.
+    for line in file.lines() {
+        dlb.get_or_intern(line?);
+    }
+    let mut writer = BufWriter::new(File::create(out)?);
+    dlb.write_to(&mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+fn stats(path: &str) -> CliResult {
+    let trie = Trie::load(path)?;
+    let kind = match trie {
+        Trie::Dynamic(_) => "dynamic",
+        Trie::Static(_) => "static",
+    };
+    println!("Kind:\t\t\t{}", kind);
+    println!("Nodes:\t\t\t{}", trie.count_nodes());
+    println!("Depth histogram:");
+    for (depth, count) in trie.depth_histogram().iter().enumerate() {
+        println!("\t{}\t\t{}", depth, count);
+    }
+    println!("{}", trie.heap_size());
+    if let Trie::Static(trie) = &trie {
+        println!("Unshared labels:\t{} bytes", trie.raw_label_size());
+    }
+    Ok(())
+}
+
+fn get(path: &str, key: &str) -> CliResult {
+    let trie = Trie::load(path)?;
+    match trie.get(key.to_owned()) {
+        Some(id) => println!("{}", id),
+        None => return Err(format!("{:?} is not in the trie", key).into()),
+    }
+    Ok(())
+}
+
+fn resolve(path: &str, id: &str) -> CliResult {
+    let trie = Trie::load(path)?;
+    let id = Identifier::new(id.parse::<u64>()?).ok_or("identifier is out of range")?;
+    match trie.resolve(id) {
+        Some(key) => println!("{}", key),
+        None => return Err(format!("no key has identifier {}", id).into()),
+    }
+    Ok(())
+}
+
+fn prefix(path: &str, prefix: &str) -> CliResult {
+    let trie = Trie::load(path)?;
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    for (key, id) in trie.prefix_iter(prefix.to_owned()) {
+        writeln!(out, "{}\t{}", id, key)?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+fn dot(path: &str) -> CliResult {
+    match Trie::load(path)? {
+        Trie::Dynamic(dlb) => {
+            let stdout = io::stdout();
+            let mut out = BufWriter::new(stdout.lock());
+            dlb.write_dot(&mut out)?;
+            out.flush()?;
+            Ok(())
+        }
+        Trie::Static(_) => Err("dot export needs a dynamic trie".into()),
+    }
+}
+
+fn freeze(path: &str, out: &str, ranked: bool) -> CliResult {
+    let dlb = match Trie::load(path)? {
+        Trie::Dynamic(dlb) => dlb,
+        Trie::Static(_) => return Err(format!("{} is already static", path).into()),
+    };
+    let trie = if ranked {
+        let (trie, remap) = dlb.freeze_ranked();
+        let stdout = io::stdout();
+        let mut remapped = BufWriter::new(stdout.lock());
+        for (old, new) in remap.iter() {
+            writeln!(remapped, "{}\t{}", old, new)?;
+        }
+        remapped.flush()?;
+        trie
+    } else {
+        dlb.freeze()
+    };
+    let mut writer = BufWriter::new(File::create(out)?);
+    trie.write_to(&mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
@@ -0,0 +1,364 @@
+use crate::dawg::builder::State;
+use crate::dawg::DawgBuilder;
+use crate::dawg::Iter;
+use crate::dtrie::iter::into_string;
+use crate::dtrie::{BuildError, Identifier, MemoryUsage, Symbol, DLB};
+use crate::encoding::{expect_magic, invalid_data, read_bytes, read_u32, write_bytes, write_u32};
+use std::io::{self, Read, Write};
+use std::marker::PhantomData;
+use std::mem::size_of;
+
+/// `Dawg` is a minimal acyclic automaton accepting a set of keys. Like a
+/// trie it shares prefixes, but equivalent states are merged as well, so
+/// common suffixes are stored once. Identifiers are the keys' ranks,
+/// found by adding up counts stored on the edges, so each key still gets
+/// a unique, dense identifier even though keys share their final states.
+///
+/// States are numbered so every edge leads to a lower state, and the
+/// root comes last. Edges are grouped by their source state and sorted
+/// by byte.
+pub struct Dawg<S = Identifier> {
+    // The edges leaving state `i` are `first_edge[i]..first_edge[i + 1]`.
+    first_edge: Vec<u32>,
+    finals: Vec<bool>,
+    edge_bytes: Vec<u8>,
+    edge_targets: Vec<u32>,
+    // The number of keys ranked before those reached through each edge,
+    // counted from its source state.
+    edge_ranks: Vec<u32>,
+    len: usize,
+    symbol: PhantomData<S>,
+}
+
+impl Dawg {
+    /// `MAGIC` is the header at the start of every serialised `Dawg`,
+    /// whatever its symbol type.
+    pub const MAGIC: &'static [u8] = b"SDTA\x01";
+}
+
+impl<S: Symbol> Dawg<S> {
+    /// `from_sorted_iter` builds an automaton from keys in strictly
+    /// increasing order with a `DawgBuilder`.
+    pub fn from_sorted_iter<I>(keys: I) -> Result<Self, BuildError>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let mut builder = DawgBuilder::default();
+        for key in keys {
+            builder.push(key.as_ref())?;
+        }
+        Ok(builder.finish())
+    }
+
+    /// `from_states` packs the states reachable from the root, which is
+    /// state zero, numbering them in postorder.
+    pub(crate) fn from_states(states: Vec<State>, len: usize) -> Self {
+        const UNSEEN: u32 = u32::MAX;
+        let mut numbers = vec![UNSEEN; states.len()];
+        let mut order = Vec::new();
+        // Each entry is a state and how many of its edges have been followed.
+        let mut stack = vec![(0, 0)];
+        while let Some((state, next)) = stack.pop() {
+            let edges = &states[state as usize].edges;
+            if let Some(&(_, target)) = edges.get(next) {
+                stack.push((state, next + 1));
+                if numbers[target as usize] == UNSEEN {
+                    // Mark it so it's only pushed once.
+                    numbers[target as usize] = 0;
+                    stack.push((target, 0));
+                }
+            } else {
+                numbers[state as usize] = order.len() as u32;
+                order.push(state);
+            }
+        }
+        let mut dawg = Self {
+            first_edge: Vec::with_capacity(order.len() + 1),
+            finals: Vec::with_capacity(order.len()),
+            edge_bytes: Vec::new(),
+            edge_targets: Vec::new(),
+            edge_ranks: Vec::new(),
+            len,
+            symbol: PhantomData,
+        };
+        for &state in order.iter() {
+            let state = &states[state as usize];
+            dawg.first_edge.push(dawg.edge_bytes.len() as u32);
+            dawg.finals.push(state.is_final);
+            for &(byte, target) in state.edges.iter() {
+                dawg.edge_bytes.push(byte);
+                dawg.edge_targets.push(numbers[target as usize]);
+            }
+        }
+        dawg.first_edge.push(dawg.edge_bytes.len() as u32);
+        dawg.edge_bytes.shrink_to_fit();
+        dawg.edge_targets.shrink_to_fit();
+        dawg.rank_edges()
+            .expect("the builder counts its keys in a u32");
+        dawg
+    }
+
+    /// `rank_edges` fills in the edge ranks, working up from the lowest
+    /// state since every edge leads downwards. Returns `None` if a state
+    /// accepts more keys than fit in a `u32`.
+    fn rank_edges(&mut self) -> Option<()> {
+        let mut counts: Vec<u32> = Vec::with_capacity(self.finals.len());
+        self.edge_ranks = Vec::with_capacity(self.edge_bytes.len());
+        for state in 0..self.finals.len() {
+            let mut count = self.finals[state] as u32;
+            for edge in self.edges(state) {
+                self.edge_ranks.push(count);
+                count = count.checked_add(counts[self.edge_targets[edge] as usize])?;
+            }
+            counts.push(count);
+        }
+        if counts.last().map(|&count| count as usize) != Some(self.len) {
+            return None;
+        }
+        Some(())
+    }
+
+    fn root(&self) -> usize {
+        self.finals.len() - 1
+    }
+
+    pub(crate) fn edges(&self, state: usize) -> std::ops::Range<usize> {
+        self.first_edge[state] as usize..self.first_edge[state + 1] as usize
+    }
+
+    pub(crate) fn is_final(&self, state: usize) -> bool {
+        self.finals[state]
+    }
+
+    /// `edge` is the byte an edge is labelled with, the state it leads
+    /// to, and the number of keys ranked before those reached through it.
+    pub(crate) fn edge(&self, edge: usize) -> (u8, usize, usize) {
+        (
+            self.edge_bytes[edge],
+            self.edge_targets[edge] as usize,
+            self.edge_ranks[edge] as usize,
+        )
+    }
+
+    /// `follow` finds the edge leaving `state` on `byte`.
+    fn follow(&self, state: usize, byte: u8) -> Option<usize> {
+        let edges = self.edges(state);
+        let start = edges.start;
+        let position = self.edge_bytes[edges].binary_search(&byte).ok()?;
+        Some(start + position)
+    }
+
+    /// `len` is the number of keys the automaton accepts.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn count_states(&self) -> u64 {
+        self.finals.len() as u64
+    }
+
+    pub fn count_edges(&self) -> u64 {
+        self.edge_bytes.len() as u64
+    }
+
+    pub fn contains(&self, s: String) -> bool {
+        self.get(s).is_some()
+    }
+
+    /// `get` is the identifier of `s`, which is its rank among the keys.
+    /// The ranks of the edges taken are added up along the way.
+    pub fn get(&self, s: String) -> Option<S> {
+        let mut state = self.root();
+        let mut rank = 0;
+        for &byte in s.as_bytes() {
+            let edge = self.follow(state, byte)?;
+            rank += self.edge_ranks[edge] as usize;
+            state = self.edge_targets[edge] as usize;
+        }
+        if !self.finals[state] {
+            return None;
+        }
+        S::from_usize(rank)
+    }
+
+    /// `resolve` rebuilds the key with identifier `id`. At each state, it
+    /// takes the last edge ranked no higher than what's left of the rank.
+    pub fn resolve(&self, id: S) -> Option<String> {
+        let mut rest = id.to_usize();
+        if rest >= self.len {
+            return None;
+        }
+        let mut state = self.root();
+        let mut key = Vec::new();
+        while !(self.finals[state] && rest == 0) {
+            let edges = self.edges(state);
+            let start = edges.start;
+            let ranks = &self.edge_ranks[edges];
+            let position = ranks.partition_point(|&rank| rank as usize <= rest);
+            // The rank is below `len`, so some key lies down one of the edges.
+            let edge = start + position.checked_sub(1)?;
+            rest -= self.edge_ranks[edge] as usize;
+            key.push(self.edge_bytes[edge]);
+            state = self.edge_targets[edge] as usize;
+        }
+        String::from_utf8(key).ok()
+    }
+
+    /// `iter` visits every key in order, along with its identifier.
+    pub fn iter(&self) -> Iter<'_, S> {
+        Iter::new(self, self.root(), 0, Vec::new())
+    }
+
+    /// `prefix_iter` visits every key which starts with `prefix`, in
+    /// order. It follows `prefix` from the root, adding up the ranks
+    /// of the edges taken, and walks every key below where it ends.
+    pub fn prefix_iter(&self, prefix: String) -> Iter<'_, S> {
+        let mut state = self.root();
+        let mut rank = 0;
+        for &byte in prefix.as_bytes() {
+            let edge = match self.follow(state, byte) {
+                Some(edge) => edge,
+                None => return Iter::empty(self),
+            };
+            rank += self.edge_ranks[edge] as usize;
+            state = self.edge_targets[edge] as usize;
+        }
+        Iter::new(self, state, rank, prefix.into_bytes())
+    }
+
+    /// `longest_prefix` is the longest key which `s` starts with, along
+    /// with its identifier, or `None` if no key is a prefix of `s`.
+    pub fn longest_prefix(&self, s: String) -> Option<(String, S)> {
+        let key = s.as_bytes();
+        let mut state = self.root();
+        let mut rank = 0;
+        let mut longest = None;
+        for depth in 0..=key.len() {
+            if self.finals[state] {
+                longest = Some((depth, rank));
+            }
+            let edge = match key.get(depth).and_then(|&byte| self.follow(state, byte)) {
+                Some(edge) => edge,
+                None => break,
+            };
+            rank += self.edge_ranks[edge] as usize;
+            state = self.edge_targets[edge] as usize;
+        }
+        let (len, rank) = longest?;
+        Some((into_string(key[..len].to_vec()), S::from_usize(rank)?))
+    }
+
+    /// `size_of` is the total memory used by this automaton,
+    /// including the struct itself and everything on the heap.
+    pub fn size_of(&self) -> usize {
+        size_of::<Self>() + self.heap_size().total()
+    }
+
+    /// `heap_size` breaks down the heap memory owned by this automaton.
+    /// States and edges count as nodes, and edge bytes as labels.
+    pub fn heap_size(&self) -> MemoryUsage {
+        let word = size_of::<u32>();
+        MemoryUsage {
+            nodes: self.first_edge.len() * word
+                + self.finals.len()
+                + (self.edge_targets.len() + self.edge_ranks.len()) * word,
+            labels: self.edge_bytes.len(),
+            child_containers: 0,
+            wasted_capacity: (self.first_edge.capacity() - self.first_edge.len()) * word
+                + (self.finals.capacity() - self.finals.len())
+                + (self.edge_bytes.capacity() - self.edge_bytes.len())
+                + (self.edge_targets.capacity() - self.edge_targets.len()) * word
+                + (self.edge_ranks.capacity() - self.edge_ranks.len()) * word,
+        }
+    }
+
+    /// `write_to` saves the automaton. Edge ranks are left out,
+    /// since they are recounted when it is read back.
+    pub fn write_to<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        out.write_all(Dawg::MAGIC)?;
+        write_u32(out, self.len as u32)?;
+        write_u32(out, self.finals.len() as u32)?;
+        for state in 0..self.finals.len() {
+            let edges = self.edges(state);
+            write_u32(out, (edges.len() as u32) << 1 | self.finals[state] as u32)?;
+        }
+        write_bytes(out, &self.edge_bytes)?;
+        for &target in self.edge_targets.iter() {
+            write_u32(out, target)?;
+        }
+        Ok(())
+    }
+
+    /// `read_from` loads an automaton saved by `write_to`, checking
+    /// that every edge leads to a lower state, so it has no cycles.
+    pub fn read_from<R: Read>(input: &mut R) -> io::Result<Self> {
+        expect_magic(input, Dawg::MAGIC)?;
+        let len = read_u32(input)? as usize;
+        let state_count = read_u32(input)? as usize;
+        if state_count == 0 {
+            return Err(invalid_data("missing root state"));
+        }
+        let mut first_edge = Vec::with_capacity(state_count.min(1 << 20) + 1);
+        let mut finals = Vec::with_capacity(state_count.min(1 << 20));
+        let mut edge_count: u64 = 0;
+        for _ in 0..state_count {
+            let packed = read_u32(input)?;
+            first_edge.push(edge_count as u32);
+            finals.push(packed & 1 == 1);
+            edge_count += (packed >> 1) as u64;
+            if edge_count > u32::MAX as u64 {
+                return Err(invalid_data("too many edges"));
+            }
+        }
+        first_edge.push(edge_count as u32);
+        let edge_bytes = read_bytes(input)?;
+        if edge_bytes.len() as u64 != edge_count {
+            return Err(invalid_data("edge count mismatch"));
+        }
+        let mut edge_targets = Vec::with_capacity(edge_bytes.len());
+        for _ in 0..edge_count {
+            edge_targets.push(read_u32(input)?);
+        }
+        let mut dawg = Self {
+            first_edge,
+            finals,
+            edge_bytes,
+            edge_targets,
+            edge_ranks: Vec::new(),
+            len,
+            symbol: PhantomData,
+        };
+        for state in 0..state_count {
+            let edges = dawg.edges(state);
+            if dawg.edge_targets[edges.clone()]
+                .iter()
+                .any(|&target| target as usize >= state)
+            {
+                return Err(invalid_data("edge does not lead to a lower state"));
+            }
+            if dawg.edge_bytes[edges]
+                .windows(2)
+                .any(|pair| pair[0] >= pair[1])
+            {
+                return Err(invalid_data("edges out of order"));
+            }
+        }
+        dawg.rank_edges()
+            .ok_or_else(|| invalid_data("key count mismatch"))?;
+        Ok(dawg)
+    }
+}
+
+/// The automaton's identifiers are ranks, so they match those given
+/// by `DLB::freeze_ranked` rather than the trie's own.
+impl<S: Symbol> From<&DLB<S>> for Dawg<S> {
+    fn from(dlb: &DLB<S>) -> Self {
+        Self::from_sorted_iter(dlb.iter().map(|(key, _)| key))
+            .expect("a trie iterates over its keys in order")
+    }
+}
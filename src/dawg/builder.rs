@@ -0,0 +1,125 @@
+use crate::dawg::Dawg;
+use crate::dtrie::{BuildError, Identifier, Symbol};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+/// `State` is a state of the automaton under construction. Its
+/// transitions are sorted by byte, so equal states compare equal.
+#[derive(Clone, PartialEq, Eq, Hash, Default)]
+pub(crate) struct State {
+    pub is_final: bool,
+    pub edges: Vec<(u8, u32)>,
+}
+
+/// `DawgBuilder` assembles a `Dawg` from keys in strictly increasing
+/// order, using Daciuk's incremental minimisation. The states along the
+/// latest key are left open; once a key turns away from them, they can
+/// gain no more transitions, so each is either merged with an equivalent
+/// state seen before or registered as a new one. Only the open path and
+/// the register are ever held, never the whole trie.
+pub struct DawgBuilder<S = Identifier> {
+    states: Vec<State>,
+    // Every closed state, so equivalent ones can be found.
+    register: HashMap<State, u32>,
+    // The states along the latest key, starting with the root.
+    path: Vec<u32>,
+    previous: Vec<u8>,
+    len: usize,
+    symbol: PhantomData<S>,
+}
+
+impl<S: Symbol> Default for DawgBuilder<S> {
+    fn default() -> Self {
+        Self {
+            states: vec![State::default()],
+            register: HashMap::new(),
+            path: vec![0],
+            previous: Vec::new(),
+            len: 0,
+            symbol: PhantomData,
+        }
+    }
+}
+
+impl DawgBuilder {
+    /// `new` makes a builder for an automaton using `Identifier`s.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<S: Symbol> DawgBuilder<S> {
+    /// `push` adds the next key, returning its identifier, which is its
+    /// rank among the keys. A key which doesn't sort after the previous
+    /// one is rejected, and the builder is left unchanged.
+    pub fn push(&mut self, key: &str) -> Result<S, BuildError> {
+        let key = key.as_bytes();
+        let index = self.len;
+        if index > 0 {
+            match key.cmp(&self.previous) {
+                Ordering::Less => return Err(BuildError::Unsorted(index)),
+                Ordering::Equal => return Err(BuildError::Duplicate(index)),
+                Ordering::Greater => {}
+            }
+        }
+        // Ranks are kept on the edges as `u32`s.
+        if index > u32::MAX as usize {
+            return Err(BuildError::Exhausted);
+        }
+        let id = S::from_usize(index).ok_or(BuildError::Exhausted)?;
+        let shared = self
+            .previous
+            .iter()
+            .zip(key.iter())
+            .take_while(|(x, y)| x == y)
+            .count();
+        self.close(shared);
+        for &byte in key[shared..].iter() {
+            let state = self.states.len() as u32;
+            self.states.push(State::default());
+            let parent = *self.path.last().unwrap();
+            self.states[parent as usize].edges.push((byte, state));
+            self.path.push(state);
+        }
+        let last = *self.path.last().unwrap();
+        self.states[last as usize].is_final = true;
+        self.previous.truncate(shared);
+        self.previous.extend_from_slice(&key[shared..]);
+        self.len += 1;
+        Ok(id)
+    }
+
+    /// `finish` closes the remaining states and returns the automaton.
+    pub fn finish(mut self) -> Dawg<S> {
+        self.close(0);
+        Dawg::from_states(self.states, self.len)
+    }
+
+    /// `close` minimises the open states deeper than `depth`, deepest
+    /// first, so every state's targets are closed before it is.
+    fn close(&mut self, depth: usize) {
+        while self.path.len() > depth + 1 {
+            let state = self.path.pop().unwrap();
+            let parent = *self.path.last().unwrap() as usize;
+            let closed = std::mem::take(&mut self.states[state as usize]);
+            let target = match self.register.get(&closed) {
+                Some(&equivalent) => {
+                    // The newest state can be given back straight away.
+                    // Any other is left empty and unreachable, and is
+                    // dropped when the automaton is packed.
+                    if state as usize == self.states.len() - 1 {
+                        self.states.pop();
+                    }
+                    equivalent
+                }
+                None => {
+                    self.states[state as usize] = closed.clone();
+                    self.register.insert(closed, state);
+                    state
+                }
+            };
+            self.states[parent].edges.last_mut().unwrap().1 = target;
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use crate::dawg::Dawg;
+use crate::dtrie::iter::into_string;
+use crate::dtrie::Symbol;
+
+/// `Iter` walks the keys accepted below a state of a `Dawg` depth-first,
+/// yielding them in lexicographic order together with their identifiers.
+/// States shared by several keys are visited once for each of them.
+pub struct Iter<'a, S> {
+    dawg: &'a Dawg<S>,
+    // Each entry holds a state, the rank of the first key accepted
+    // below it, and the bytes read on the way to it.
+    stack: Vec<(usize, usize, Vec<u8>)>,
+}
+
+impl<'a, S> Iter<'a, S> {
+    pub(crate) fn new(dawg: &'a Dawg<S>, state: usize, rank: usize, path: Vec<u8>) -> Self {
+        Self {
+            dawg,
+            stack: vec![(state, rank, path)],
+        }
+    }
+
+    pub(crate) fn empty(dawg: &'a Dawg<S>) -> Self {
+        Self {
+            dawg,
+            stack: Vec::new(),
+        }
+    }
+}
+
+impl<'a, S: Symbol> Iterator for Iter<'a, S> {
+    type Item = (String, S);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((state, rank, path)) = self.stack.pop() {
+            for edge in self.dawg.edges(state).rev() {
+                let (byte, target, below) = self.dawg.edge(edge);
+                let mut next = path.clone();
+                next.push(byte);
+                self.stack.push((target, rank + below, next));
+            }
+            if self.dawg.is_final(state) {
+                return Some((into_string(path), S::from_usize(rank)?));
+            }
+        }
+        None
+    }
+}
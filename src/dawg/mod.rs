@@ -0,0 +1,7 @@
+pub use automaton::Dawg;
+pub use builder::DawgBuilder;
+pub use iter::Iter;
+
+mod automaton;
+mod builder;
+mod iter;
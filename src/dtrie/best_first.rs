@@ -0,0 +1,130 @@
+use crate::dtrie::dlb_node::DLBNode;
+use crate::dtrie::iter::into_string;
+use crate::dtrie::{Symbol, DLB};
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+/// `Candidate` is an entry in the queue `best_first` works through.
+struct Candidate<'a, S> {
+    // The key's value, or for a subtree, a bound on
+    // the values of the keys inside it.
+    bound: u64,
+    // The subtree to explore, or `None` for the key at `path` itself.
+    node: Option<DLBNode<'a, S>>,
+    id: Option<S>,
+    // The bytes leading up to the key, or up to and including the
+    // subtree's label.
+    path: Vec<u8>,
+}
+
+impl<'a, S> Candidate<'a, S> {
+    /// `rank` orders candidates: higher values first, then subtrees before
+    /// keys, so that every key with the same value is queued before any of
+    /// them is taken, and then keys in lexicographic order.
+    fn rank(&self) -> (u64, bool, Reverse<&[u8]>) {
+        (self.bound, self.node.is_some(), Reverse(&self.path))
+    }
+}
+
+impl<'a, S> PartialEq for Candidate<'a, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.rank() == other.rank()
+    }
+}
+
+impl<'a, S> Eq for Candidate<'a, S> {}
+
+impl<'a, S> PartialOrd for Candidate<'a, S> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'a, S> Ord for Candidate<'a, S> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.rank().cmp(&other.rank())
+    }
+}
+
+/// `BestFirst` finds the keys with the highest values in some subtrees
+/// of a trie. `value` is the value of the key ending at a node, and
+/// `bound` is at least the value of any key in the subtree below a node,
+/// so whole subtrees can be left unexplored.
+pub(crate) struct BestFirst<'a, S, V, B> {
+    dlb: &'a DLB<S>,
+    queue: BinaryHeap<Candidate<'a, S>>,
+    value: V,
+    bound: B,
+}
+
+impl<'a, S, V, B> BestFirst<'a, S, V, B>
+where
+    S: Symbol,
+    V: Fn(DLBNode<'a, S>) -> u64,
+    B: Fn(DLBNode<'a, S>) -> u64,
+{
+    pub fn new(dlb: &'a DLB<S>, value: V, bound: B) -> Self {
+        Self {
+            dlb,
+            queue: BinaryHeap::new(),
+            value,
+            bound,
+        }
+    }
+
+    /// `push_key` queues a key which has no node, i.e. the empty string.
+    pub fn push_key(&mut self, id: S, value: u64, path: Vec<u8>) {
+        self.queue.push(Candidate {
+            bound: value,
+            node: None,
+            id: Some(id),
+            path,
+        });
+    }
+
+    /// `push_subtree` queues the subtree below `node`, whose
+    /// key starts with `path` followed by the node's label.
+    pub fn push_subtree(&mut self, node: DLBNode<'a, S>, mut path: Vec<u8>) {
+        path.extend_from_slice(node.label());
+        self.queue.push(Candidate {
+            bound: (self.bound)(node),
+            node: Some(node),
+            id: None,
+            path,
+        });
+    }
+
+    /// `take` is the `n` keys with the highest values, with their
+    /// identifiers and values, from highest to lowest. Ties are broken in
+    /// key order. Keys come back as `resolve` spells them.
+    pub fn take(mut self, n: usize) -> Vec<(String, S, u64)> {
+        let mut best = Vec::with_capacity(n.min(self.dlb.len()));
+        while best.len() < n {
+            let Candidate {
+                bound,
+                node,
+                id,
+                path,
+            } = match self.queue.pop() {
+                Some(candidate) => candidate,
+                None => break,
+            };
+            let node = match node {
+                Some(node) => node,
+                None => {
+                    let id = id.expect("keys are queued with their identifiers");
+                    let key = self.dlb.resolve(id).unwrap_or_else(|| into_string(path));
+                    best.push((key, id, bound));
+                    continue;
+                }
+            };
+            for child in node.children() {
+                self.push_subtree(child, path.clone());
+            }
+            if let Some(id) = node.id() {
+                self.push_key(id, (self.value)(node), path);
+            }
+        }
+        best
+    }
+}
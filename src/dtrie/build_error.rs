@@ -0,0 +1,33 @@
+use crate::dtrie::Exhausted;
+use std::error::Error;
+use std::fmt;
+
+/// `BuildError` is returned when a trie can't be built from sorted keys.
+/// Positions count from zero in the order the keys were given.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum BuildError {
+    /// The key at this position sorts before the one ahead of it.
+    Unsorted(usize),
+    /// The key at this position repeats the one ahead of it.
+    Duplicate(usize),
+    /// The symbol type ran out of identifiers.
+    Exhausted,
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BuildError::Unsorted(index) => write!(f, "key {} is out of order", index),
+            BuildError::Duplicate(index) => write!(f, "key {} is a duplicate", index),
+            BuildError::Exhausted => Exhausted.fmt(f),
+        }
+    }
+}
+
+impl Error for BuildError {}
+
+impl From<Exhausted> for BuildError {
+    fn from(_: Exhausted) -> Self {
+        BuildError::Exhausted
+    }
+}
@@ -1,4 +1,5 @@
-use crate::dtrie::Matchable;
+use crate::dtrie::Escaped;
+use crate::dtrie::MemoryUsage;
 use std::fmt;
 
 #[derive(Clone)]
@@ -22,50 +23,18 @@ impl CharList {
         self.0.as_slice()
     }
 
-    pub fn iter(&self) -> std::slice::Iter<u8> {
+    pub fn iter(&self) -> std::slice::Iter<'_, u8> {
         self.0.iter()
     }
 
-    pub fn split_off(&mut self, at: usize) -> CharList {
-        let res = self.0.split_off(at);
-        CharList::from(res)
-    }
-
-    pub fn append(&self, mut other: CharList) -> CharList {
-        let mut res = self.0.clone();
-        res.append(&mut other.0);
-        Self(res)
-    }
-
-    pub fn to_string(&self) -> String {
-        String::from_utf8(self.0.clone()).unwrap()
-    }
-
-    pub fn count_shared_prefix(&self, bytes: &[u8]) -> usize {
-        let mut count = 0;
-        for (x, y) in bytes.iter().zip(self.iter()) {
-            if x != y {
-                break;
-            }
-            count += 1
+    /// `heap_size` reports the buffer backing this list.
+    /// Any spare capacity is counted as waste.
+    pub fn heap_size(&self) -> MemoryUsage {
+        MemoryUsage {
+            labels: self.0.len(),
+            wasted_capacity: self.0.capacity() - self.0.len(),
+            ..MemoryUsage::default()
         }
-        return count;
-    }
-}
-
-impl Matchable for CharList {
-    fn similar_bytes(&self, pattern: CharList) -> usize {
-        let mut count = 0;
-        let i1 = self.0.iter();
-        let i2 = pattern.iter();
-        for (left, right) in i1.zip(i2) {
-            if *left == *right {
-                count += 1;
-            } else {
-                break;
-            }
-        }
-        count
     }
 }
 
@@ -78,19 +47,17 @@ impl IntoIterator for CharList {
     }
 }
 
+/// `Display` writes the bytes as text, escaping any which aren't
+/// printable UTF-8. Wrap the list in `Hex` to see the raw bytes.
 impl fmt::Display for CharList {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut res = String::new();
-        for character in self.0.clone() {
-            res = format!("{}{}", res, character);
-        }
-        write!(f, "{}", res)
+        fmt::Display::fmt(&Escaped(&self.0), f)
     }
 }
 
 impl fmt::Debug for CharList {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", String::from_utf8(self.0.clone()).unwrap())
+        fmt::Debug::fmt(&Escaped(&self.0), f)
     }
 }
 
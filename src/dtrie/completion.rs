@@ -0,0 +1,57 @@
+use crate::dtrie::best_first::BestFirst;
+use crate::dtrie::dlb_node::DLBNode;
+use crate::dtrie::{Symbol, DLB};
+
+impl<S: Symbol> DLB<S> {
+    /// `set_score` gives the key for `id` a score, for ranking it among
+    /// the completions of its prefixes. Keys start with a score of zero.
+    /// Returns `false` if `id` doesn't belong to a key in the trie.
+    pub fn set_score(&mut self, id: S, score: u64) -> bool {
+        if self.empty_id() == Some(id) {
+            self.set_empty_score(score);
+            return true;
+        }
+        match self.node_of(id) {
+            Some(handle) => {
+                self.set_score_of(handle, score);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// `score` is the score of the key for `id`,
+    /// or `None` if it doesn't belong to a key.
+    pub fn score(&self, id: S) -> Option<u64> {
+        if self.empty_id() == Some(id) {
+            return Some(self.empty_score());
+        }
+        self.node_of(id).map(|handle| self.scored(handle).score)
+    }
+
+    /// `top_k_completions` is the `k` highest scoring keys which start with
+    /// `prefix`, with their identifiers and scores, from highest to lowest.
+    /// Ties are broken in key order. Each node caches the best score below
+    /// it, and subtrees are explored best first, so only the subtrees
+    /// which might hold one of the `k` best keys are visited. Keys come
+    /// back as `resolve` spells them.
+    pub fn top_k_completions(&self, prefix: String, k: usize) -> Vec<(String, S, u64)> {
+        let prefix = self.normalize(&prefix);
+        let mut search = BestFirst::new(
+            self,
+            |node| self.scored(node.handle()).score,
+            |node| self.scored(node.handle()).best,
+        );
+        if prefix.is_empty() {
+            if let Some(id) = self.empty_id() {
+                search.push_key(id, self.empty_score(), Vec::new());
+            }
+            for node in self.top_level() {
+                search.push_subtree(node, Vec::new());
+            }
+        } else if let Some((handle, thus_far)) = self.descend(prefix.as_bytes()) {
+            search.push_subtree(DLBNode::new(self, handle), thus_far);
+        }
+        search.take(k)
+    }
+}
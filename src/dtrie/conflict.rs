@@ -0,0 +1,18 @@
+use std::error::Error;
+use std::fmt;
+
+/// `Conflict` is returned by `DLB::rename_prefix` when a renamed key
+/// would take the place of a key which isn't being renamed. It holds
+/// the key in the way, as stored.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Conflict {
+    pub key: String,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "the key {:?} is already in the trie", self.key)
+    }
+}
+
+impl Error for Conflict {}
@@ -0,0 +1,70 @@
+use crate::dtrie::best_first::BestFirst;
+use crate::dtrie::{Exhausted, Symbol, DLB};
+
+impl<S: Symbol> DLB<S> {
+    /// `intern_counted` interns `s` like `get_or_intern`, and adds one to
+    /// the number of times it has been seen. Panics if the symbol type
+    /// has no identifiers left.
+    pub fn intern_counted(&mut self, s: String) -> S {
+        match self.try_intern_counted(s) {
+            Ok(id) => id,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// `try_intern_counted` is `intern_counted`, but returns an error
+    /// rather than panicking when the identifiers run out.
+    pub fn try_intern_counted(&mut self, s: String) -> Result<S, Exhausted> {
+        let id = self.try_get_or_intern(s)?;
+        self.add_count(id, 1);
+        Ok(id)
+    }
+
+    /// `count` is how many times `s` has been counted by `intern_counted`.
+    /// Keys which were only interned, and missing keys, count zero times.
+    pub fn count(&self, s: String) -> u64 {
+        let key = self.normalize(&s);
+        if key.is_empty() {
+            return self.empty_count();
+        }
+        self.find(key.as_bytes())
+            .filter(|&handle| self.record(handle).id().is_some())
+            .map_or(0, |handle| self.tally(handle).count)
+    }
+
+    /// `prefix_count` is the sum of the counts of every key which starts
+    /// with `prefix`. Each node caches the sum for its subtree, so this
+    /// takes time proportional to the length of `prefix`.
+    pub fn prefix_count(&self, prefix: String) -> u64 {
+        let prefix = self.normalize(&prefix);
+        if prefix.is_empty() {
+            let below: u64 = self
+                .top_level()
+                .map(|node| self.tally(node.handle()).total)
+                .sum();
+            return self.empty_count() + below;
+        }
+        self.descend(prefix.as_bytes())
+            .map_or(0, |(handle, _)| self.tally(handle).total)
+    }
+
+    /// `top_k` is the `n` most often counted keys, with their identifiers
+    /// and counts, from most to least often. Ties are broken in key order.
+    /// Subtrees are explored best first, going by their cached totals, so
+    /// the search can skip those holding only rarely counted keys. Keys
+    /// come back as `resolve` spells them.
+    pub fn top_k(&self, n: usize) -> Vec<(String, S, u64)> {
+        let mut search = BestFirst::new(
+            self,
+            |node| self.tally(node.handle()).count,
+            |node| self.tally(node.handle()).total,
+        );
+        if let Some(id) = self.empty_id() {
+            search.push_key(id, self.empty_count(), Vec::new());
+        }
+        for node in self.top_level() {
+            search.push_subtree(node, Vec::new());
+        }
+        search.take(n)
+    }
+}
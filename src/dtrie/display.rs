@@ -0,0 +1,229 @@
+use crate::dtrie::char_list::CharList;
+use crate::dtrie::dlb_node::{Children, DLBNode};
+use crate::dtrie::{Symbol, DLB};
+use std::fmt::{self, Write};
+
+/// `Escaped` displays bytes as text. Whatever is valid UTF-8 is written
+/// as is, except for backslashes and control characters, which are
+/// escaped as in a Rust string literal. Other bytes are written as
+/// `\xNN`, so no two byte strings display alike. `Debug` adds quotes.
+#[derive(Clone, Copy)]
+pub struct Escaped<'a>(pub &'a [u8]);
+
+impl<'a> Escaped<'a> {
+    fn write(&self, f: &mut fmt::Formatter, quoted: bool) -> fmt::Result {
+        for chunk in self.0.utf8_chunks() {
+            for c in chunk.valid().chars() {
+                if c == '\\' || (quoted && c == '"') || c.is_control() {
+                    write!(f, "{}", c.escape_default())?;
+                } else {
+                    f.write_char(c)?;
+                }
+            }
+            for byte in chunk.invalid() {
+                write!(f, "\\x{:02x}", byte)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> fmt::Display for Escaped<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write(f, false)
+    }
+}
+
+impl<'a> fmt::Debug for Escaped<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_char('"')?;
+        self.write(f, true)?;
+        f.write_char('"')
+    }
+}
+
+/// `Hex` displays what it wraps with every key or label written as
+/// hexadecimal bytes, for keys which aren't text. It wraps byte slices,
+/// `CharList`s, `DLBNode`s and `DLB`s, and honours `{:#}` as they do.
+/// For bytes, `{:#}` is a hex dump with offsets and printable characters.
+#[derive(Clone, Copy)]
+pub struct Hex<T>(pub T);
+
+impl fmt::Display for Hex<&[u8]> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            return hex_dump(f, self.0);
+        }
+        for (i, byte) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_char(' ')?;
+            }
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Hex<&CharList> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&Hex(self.0.as_slice()), f)
+    }
+}
+
+impl<'a, S: Symbol> fmt::Display for Hex<DLBNode<'a, S>> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_node(f, self.0, Style::Hex)
+    }
+}
+
+impl<S: Symbol> fmt::Display for Hex<&DLB<S>> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_dlb(f, self.0, Style::Hex)
+    }
+}
+
+/// `hex_dump` writes 16 bytes to a line, each line starting with
+/// its offset and ending with the bytes which are printable ASCII.
+fn hex_dump(f: &mut fmt::Formatter, bytes: &[u8]) -> fmt::Result {
+    for (line, chunk) in bytes.chunks(16).enumerate() {
+        if line > 0 {
+            f.write_char('\n')?;
+        }
+        write!(f, "{:08x} ", line * 16)?;
+        for i in 0..16 {
+            match chunk.get(i) {
+                Some(byte) => write!(f, " {:02x}", byte)?,
+                None => f.write_str("   ")?,
+            }
+        }
+        f.write_str("  |")?;
+        for &byte in chunk {
+            let printable = byte.is_ascii_graphic() || byte == b' ';
+            f.write_char(if printable { byte as char } else { '.' })?;
+        }
+        f.write_char('|')?;
+    }
+    Ok(())
+}
+
+/// `Style` is how labels and keys are written.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Style {
+    Text,
+    Hex,
+}
+
+impl Style {
+    fn label(self, f: &mut fmt::Formatter, bytes: &[u8]) -> fmt::Result {
+        match self {
+            Style::Text => write!(f, "{}", Escaped(bytes)),
+            Style::Hex => write!(f, "{}", Hex(bytes)),
+        }
+    }
+
+    fn key(self, f: &mut fmt::Formatter, bytes: &[u8]) -> fmt::Result {
+        match self {
+            Style::Text => write!(f, "{:?}", Escaped(bytes)),
+            Style::Hex => write!(f, "[{}]", Hex(bytes)),
+        }
+    }
+}
+
+/// `write_node` writes a node's label, or with `{:#}`
+/// the tree of nodes rooted at it.
+pub(crate) fn write_node<S: Symbol>(
+    f: &mut fmt::Formatter,
+    node: DLBNode<S>,
+    style: Style,
+) -> fmt::Result {
+    style.label(f, node.label())?;
+    if !f.alternate() {
+        return Ok(());
+    }
+    if let Some(id) = node.id() {
+        write!(f, ": {}", id)?;
+    }
+    write_tree(f, node.children(), style, &mut String::new())
+}
+
+/// `write_dlb` writes every key and its identifier as a map,
+/// or with `{:#}` draws the trie as a tree.
+pub(crate) fn write_dlb<S: Symbol>(
+    f: &mut fmt::Formatter,
+    dlb: &DLB<S>,
+    style: Style,
+) -> fmt::Result {
+    if f.alternate() {
+        f.write_str("(root)")?;
+        if let Some(id) = dlb.empty_id() {
+            write!(f, ": {}", id)?;
+        }
+        return write_tree(f, dlb.top_level(), style, &mut String::new());
+    }
+    f.write_char('{')?;
+    let mut first = true;
+    if let Some(id) = dlb.empty_id() {
+        write_entry(f, &[], id, style, &mut first)?;
+    }
+    write_keys(f, dlb.top_level(), style, &mut Vec::new(), &mut first)?;
+    f.write_char('}')
+}
+
+/// `write_keys` writes the keys below a sibling list in order. It works
+/// on bytes, rather than strings, so binary keys are shown exactly.
+fn write_keys<S: Symbol>(
+    f: &mut fmt::Formatter,
+    children: Children<S>,
+    style: Style,
+    path: &mut Vec<u8>,
+    first: &mut bool,
+) -> fmt::Result {
+    for child in children {
+        let len = path.len();
+        path.extend_from_slice(child.label());
+        if let Some(id) = child.id() {
+            write_entry(f, path, id, style, first)?;
+        }
+        write_keys(f, child.children(), style, path, first)?;
+        path.truncate(len);
+    }
+    Ok(())
+}
+
+fn write_entry<S: Symbol>(
+    f: &mut fmt::Formatter,
+    key: &[u8],
+    id: S,
+    style: Style,
+    first: &mut bool,
+) -> fmt::Result {
+    if !std::mem::replace(first, false) {
+        f.write_str(", ")?;
+    }
+    style.key(f, key)?;
+    write!(f, ": {}", id)
+}
+
+/// `write_tree` draws each node below a sibling list on its own line,
+/// indented by `indent` and linked to its parent by box-drawing lines.
+fn write_tree<S: Symbol>(
+    f: &mut fmt::Formatter,
+    children: Children<S>,
+    style: Style,
+    indent: &mut String,
+) -> fmt::Result {
+    let mut children = children.peekable();
+    while let Some(child) = children.next() {
+        let last = children.peek().is_none();
+        write!(f, "\n{}{}", indent, if last { "└── " } else { "├── " })?;
+        style.label(f, child.label())?;
+        if let Some(id) = child.id() {
+            write!(f, ": {}", id)?;
+        }
+        let len = indent.len();
+        indent.push_str(if last { "    " } else { "│   " });
+        write_tree(f, child.children(), style, indent)?;
+        indent.truncate(len);
+    }
+    Ok(())
+}
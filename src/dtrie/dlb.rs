@@ -1,203 +1,1758 @@
 use crate::dtrie::char_list::CharList;
-use crate::dtrie::dlb_node::DLBNode;
-use crate::dtrie::leaf_data::LeafData;
-use crate::dtrie::matchable::Matchable;
-use crate::dtrie::Identifier;
+use crate::dtrie::display::{write_dlb, Style};
+use crate::dtrie::dlb_node::{Children, DLBNode, DebugChildren};
+use crate::dtrie::identifier::{read_maybe_id, write_maybe_id};
+use crate::dtrie::iter::Iter;
+use crate::dtrie::label::{Label, INLINE_CAPACITY};
+use crate::dtrie::node_handle::NodeHandle;
+use crate::dtrie::node_record::NodeRecord;
+use crate::dtrie::scored::Scored;
+use crate::dtrie::tally::Tally;
+use crate::dtrie::{
+    BuildError, Exhausted, IdPolicy, Identifier, KeyMode, KeyNormalizer, MemoryUsage, Remap,
+    Removed, Symbol,
+};
+use crate::encoding::{
+    expect_magic, invalid_data, read_bytes, read_u32, read_u64, read_u8, write_bytes, write_u32,
+    write_u64, write_u8,
+};
+use crate::sorted::SortedBuilder;
+use crate::strie::StaticTrie;
+use std::alloc::Layout;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::iter::FromIterator;
 use std::mem::size_of;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::ops::Index;
+use std::sync::{Arc, OnceLock};
 
-pub struct DLB {
-    root: Vec<DLBNode>,
-    next_id: AtomicU64,
+/// `Link` names a slot in the arena which holds a node handle:
+/// the head of the top-level list, a node's first child,
+/// or a node's next sibling.
+#[derive(Clone, Copy)]
+enum Link {
+    Root,
+    FirstChild(NodeHandle),
+    NextSibling(NodeHandle),
+}
+
+/// `Seek` is the outcome of searching a sibling list for a byte.
+enum Seek {
+    /// The link points at the sibling starting with the byte.
+    Found(Link),
+    /// No sibling starts with the byte. The link is
+    /// where one would be inserted to keep the list sorted.
+    Vacant(Link),
+}
+
+/// `DLB` is a path-compressed de la Briandais trie. Every node lives in
+/// a single arena and refers to the others by `NodeHandle`, so the
+/// whole trie is two flat buffers which can be cloned or saved directly.
+/// Keys are identified by the symbol type `S`.
+#[derive(Clone)]
+pub struct DLB<S = Identifier> {
+    nodes: Vec<NodeRecord<S>>,
+    // Labels too long to store inline are packed in here.
+    labels: Vec<u8>,
+    // Bytes of `labels` which no node's label covers any more. The pool
+    // is compacted once they make up more than half of it.
+    dead_labels: usize,
+    // The first top-level node, or `NodeHandle::NIL` if there are none.
+    root: NodeHandle,
+    // The node holding each identifier, indexed by `Symbol::to_usize`.
+    // Identifiers without a node belong to the empty string or are unused.
+    // Under `IdPolicy::Recycled`, this is indexed by the identifier's slot.
+    ids: Vec<NodeHandle>,
+    // Identifiers waiting to be recycled, as they were last handed out.
+    free_ids: Vec<S>,
+    // Slots in the arena left behind by removed nodes.
+    free_nodes: Vec<NodeHandle>,
+    len: usize,
+    policy: IdPolicy,
+    mode: KeyMode,
+    normalizer: Option<Arc<dyn KeyNormalizer>>,
+    // The spelling each key was first interned with, indexed like `ids`,
+    // where it differs from the normalised key stored in the trie.
+    // Trailing slots without a spelling may be missing.
+    spellings: Vec<Option<Box<str>>>,
+    // Keys rebuilt for `Index`, indexed like `ids`. The table is made the
+    // first time a key is indexed, and each key is kept until its slot
+    // changes hands.
+    resolved: OnceLock<Vec<OnceLock<Box<str>>>>,
+    // The number of keys completed at or below each node, indexed like
+    // `nodes`. A node's key count fits in a handle, since each key other
+    // than the empty string has its own node.
+    keys_below: Vec<u32>,
+    // How often each node's key was counted, and the sum over its
+    // subtree, indexed like `nodes`. Empty until a key is counted.
+    tallies: Vec<Tally>,
+    // How often the empty string was counted.
+    empty_count: u64,
+    // The score of each node's key, and the best score in its subtree,
+    // indexed like `nodes`. Empty until a key is given a score.
+    scores: Vec<Scored>,
+    // The score of the empty string.
+    empty_score: u64,
     // if the empty string is an element,
     // then this field contains it's ID.
-    contains_empty: Option<Identifier>,
+    contains_empty: Option<S>,
+}
+
+impl<S: Symbol> Default for DLB<S> {
+    fn default() -> Self {
+        Self::with_policy(IdPolicy::default())
+    }
+}
+
+/// `Debug` prints the trie as a nested tree of nodes, showing each
+/// node's label and identifier. Use `{:#?}` to indent it.
+impl<S: Symbol> fmt::Debug for DLB<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DLB")
+            .field("len", &self.len)
+            .field("policy", &self.policy)
+            .field("empty", &self.contains_empty)
+            .field("children", &DebugChildren(self.top_level()))
+            .finish()
+    }
+}
+
+/// `Display` writes the keys and their identifiers as a map, in order.
+/// `{:#}` draws the trie as a tree instead, with one node per line.
+/// Wrap the trie in `Hex` to write keys as bytes.
+impl<S: Symbol> fmt::Display for DLB<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_dlb(f, self, Style::Text)
+    }
+}
+
+/// Two tries are equal when they hold the same keys with the same
+/// identifiers, however their nodes happen to be arranged.
+impl<S: Symbol> PartialEq for DLB<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<S: Symbol> Eq for DLB<S> {}
+
+/// `FromIterator` interns each key in turn, so identifiers
+/// are handed out in the order the keys are given.
+impl<S: Symbol, K: AsRef<str>> FromIterator<K> for DLB<S> {
+    fn from_iter<I: IntoIterator<Item = K>>(keys: I) -> Self {
+        let mut dlb = Self::default();
+        dlb.extend(keys);
+        dlb
+    }
+}
+
+/// `Extend` interns every key which isn't already present.
+/// Panics if the symbol type runs out of identifiers.
+impl<S: Symbol, K: AsRef<str>> Extend<K> for DLB<S> {
+    fn extend<I: IntoIterator<Item = K>>(&mut self, keys: I) {
+        for key in keys {
+            self.get_or_intern(key.as_ref().to_owned());
+        }
+    }
+}
+
+/// `Index` borrows the key for an identifier, as `resolve` spells it.
+/// Panics if no key has the identifier.
+impl<S: Symbol> Index<S> for DLB<S> {
+    type Output = str;
+
+    fn index(&self, id: S) -> &str {
+        self.resolved_key(id).expect("no key has this identifier")
+    }
 }
 
 impl DLB {
+    /// `MAGIC` is the header at the start of every serialised `DLB`,
+    /// whatever its symbol type.
+    pub const MAGIC: &'static [u8] = b"SDTD\x07";
+
+    /// `new` makes an empty trie using `Identifier`s. For other
+    /// symbol types, use `DLB::<Sym32>::default()` and so on.
     pub fn new() -> Self {
-        return Self {
-            root: vec![],
-            next_id: AtomicU64::new(1),
+        Self::default()
+    }
+}
+
+impl<S: Symbol> DLB<S> {
+    /// `with_policy` makes an empty trie which hands out identifiers
+    /// according to `policy`.
+    pub fn with_policy(policy: IdPolicy) -> Self {
+        Self::with_options(policy, KeyMode::default())
+    }
+
+    /// `with_mode` makes an empty trie which splits labels according to `mode`.
+    pub fn with_mode(mode: KeyMode) -> Self {
+        Self::with_options(IdPolicy::default(), mode)
+    }
+
+    /// `with_options` makes an empty trie which hands out identifiers
+    /// according to `policy` and splits labels according to `mode`.
+    pub fn with_options(policy: IdPolicy, mode: KeyMode) -> Self {
+        Self {
+            nodes: Vec::new(),
+            labels: Vec::new(),
+            dead_labels: 0,
+            root: NodeHandle::NIL,
+            ids: Vec::new(),
+            free_ids: Vec::new(),
+            free_nodes: Vec::new(),
+            len: 0,
+            policy,
+            mode,
+            normalizer: None,
+            spellings: Vec::new(),
+            resolved: OnceLock::new(),
+            keys_below: Vec::new(),
+            tallies: Vec::new(),
+            empty_count: 0,
+            scores: Vec::new(),
+            empty_score: 0,
             contains_empty: None,
-        };
+        }
+    }
+
+    /// `with_normalizer` makes an empty trie which matches keys
+    /// after passing them through `normalizer`.
+    pub fn with_normalizer<N: KeyNormalizer + 'static>(normalizer: N) -> Self {
+        let mut dlb = Self::default();
+        dlb.set_normalizer(normalizer);
+        dlb
+    }
+
+    /// `from_sorted_iter` builds a trie from keys in strictly increasing
+    /// order, in a single pass and without splitting any labels. This is
+    /// much faster than interning the keys one at a time. Identifiers are
+    /// handed out sequentially in the order the keys are given. The trie
+    /// uses `KeyMode::Bytes`.
+    pub fn from_sorted_iter<I>(keys: I) -> Result<Self, BuildError>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let mut dlb = Self::default();
+        let mut builder = SortedBuilder::new();
+        for key in keys {
+            builder.push(key.as_ref().as_bytes(), |label, id, children| {
+                dlb.attach(label, id, &children)
+            })?;
+            dlb.ids.push(NodeHandle::NIL);
+            dlb.len += 1;
+        }
+        let (empty, top_level) =
+            builder.finish(|label, id, children| dlb.attach(label, id, &children));
+        dlb.contains_empty = empty;
+        dlb.root = dlb.link_siblings(&top_level, NodeHandle::NIL);
+        Ok(dlb)
+    }
+
+    pub fn policy(&self) -> IdPolicy {
+        self.policy
+    }
+
+    /// `mode` reports where this trie may split a label.
+    pub fn mode(&self) -> KeyMode {
+        self.mode
+    }
+
+    /// `set_normalizer` makes every later lookup, insertion and removal
+    /// pass its key through `normalizer` first. Keys already in the trie
+    /// are not renormalised. The normaliser isn't saved by `write_to`,
+    /// so set it again after `read_from`.
+    pub fn set_normalizer<N: KeyNormalizer + 'static>(&mut self, normalizer: N) {
+        self.normalizer = Some(Arc::new(normalizer));
+    }
+
+    /// `normalize` is `key` as it would be stored in this trie.
+    pub fn normalize<'a>(&self, key: &'a str) -> Cow<'a, str> {
+        match &self.normalizer {
+            Some(normalizer) => normalizer.normalize(key),
+            None => Cow::Borrowed(key),
+        }
+    }
+
+    /// `len` is the number of keys in the trie, including the empty
+    /// string. Under `IdPolicy::Dense`, the identifiers in use are
+    /// always exactly `0..len()`.
+    pub fn len(&self) -> usize {
+        self.len
     }
 
     pub fn is_empty(&self) -> bool {
-        self.root.len() == 0
+        self.len == 0
     }
 
     pub fn contains(&self, s: String) -> bool {
-        // Check if the string is empty.
-        if s.is_empty() {
-            println!("String is empty!");
-            return self.contains_empty.is_some();
+        self.get(s).is_some()
+    }
+
+    /// `new_id` hands out the next symbol, unless the symbol
+    /// type has run out of them. The caller must `assign` it.
+    fn new_id(&mut self) -> Result<S, Exhausted> {
+        let id = match self.free_ids.pop() {
+            Some(stale) => next_generation(stale),
+            None => {
+                let slot = self.ids.len();
+                if self.policy == IdPolicy::Recycled && slot >= recycled_slots::<S>() {
+                    return Err(Exhausted);
+                }
+                let id = S::from_usize(slot).ok_or(Exhausted)?;
+                self.ids.push(NodeHandle::NIL);
+                if let Some(resolved) = self.resolved.get_mut() {
+                    resolved.push(OnceLock::new());
+                }
+                id
+            }
+        };
+        self.len += 1;
+        Ok(id)
+    }
+
+    /// `slot_of` is where `id` is found in the identifier table.
+    fn slot_of(&self, id: S) -> usize {
+        match self.policy {
+            IdPolicy::Recycled => id.to_usize() & slot_mask::<S>(),
+            _ => id.to_usize(),
         }
+    }
+
+    /// `assign` marks the node at `handle` as completing the key for `id`.
+    fn assign(&mut self, handle: NodeHandle, id: S) {
+        self.record_mut(handle).set_id(Some(id));
+        let slot = self.slot_of(id);
+        self.ids[slot] = handle;
+    }
 
-        // Handle general case
-        let byte_pattern = s.as_bytes();
-        for child in self.root.iter() {
-            if child.contains(byte_pattern) {
-                return true;
+    /// `release_id` gives up the identifier of a removed key. Under
+    /// `IdPolicy::Dense`, the newest identifier moves into the gap.
+    /// Under `IdPolicy::Recycled`, it is set aside for reuse.
+    fn release_id(&mut self, id: S) -> Removed<S> {
+        let index = self.slot_of(id);
+        self.ids[index] = NodeHandle::NIL;
+        if let Some(spelling) = self.spellings.get_mut(index) {
+            *spelling = None;
+        }
+        self.forget_key(index);
+        self.len -= 1;
+        match self.policy {
+            IdPolicy::Sequential => return Removed { id, moved: None },
+            IdPolicy::Recycled => {
+                if generation_of(id) < max_generation::<S>() {
+                    self.free_ids.push(id);
+                }
+                return Removed { id, moved: None };
             }
+            IdPolicy::Dense => {}
+        }
+        let last = self.ids.len() - 1;
+        let handle = self.ids.pop().unwrap();
+        if last < self.spellings.len() {
+            self.spellings.swap_remove(index);
+        }
+        if let Some(resolved) = self.resolved.get_mut() {
+            resolved.swap_remove(index);
+        }
+        if last == index {
+            return Removed { id, moved: None };
+        }
+        // There are no gaps, so a missing node means the empty string.
+        if handle.is_nil() {
+            self.contains_empty = Some(id);
+        } else {
+            self.assign(handle, id);
+        }
+        Removed {
+            id,
+            moved: S::from_usize(last),
         }
-        false
     }
 
-    fn new_id(&mut self) -> Identifier {
-        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
-        Identifier::from(id)
+    pub(crate) fn record(&self, handle: NodeHandle) -> &NodeRecord<S> {
+        &self.nodes[handle.index()]
     }
 
-    pub fn get(&self, s: String) -> Option<Identifier> {
-        println!("Getting  string {}", s);
-        println!("Can't wait to see my strings!");
-        if self.is_empty() {
-            return None;
+    fn record_mut(&mut self, handle: NodeHandle) -> &mut NodeRecord<S> {
+        &mut self.nodes[handle.index()]
+    }
+
+    /// `label_of` borrows the label of the node at `handle`.
+    pub(crate) fn label_of(&self, handle: NodeHandle) -> &[u8] {
+        self.nodes[handle.index()].label().as_slice(&self.labels)
+    }
+
+    fn follow(&self, link: Link) -> NodeHandle {
+        match link {
+            Link::Root => self.root,
+            Link::FirstChild(parent) => self.record(parent).first_child(),
+            Link::NextSibling(sibling) => self.record(sibling).next_sibling(),
         }
+    }
 
-        if s.is_empty() {
-            return self.contains_empty;
+    fn relink(&mut self, link: Link, target: NodeHandle) {
+        match link {
+            Link::Root => self.root = target,
+            Link::FirstChild(parent) => self.record_mut(parent).set_first_child(target),
+            Link::NextSibling(sibling) => self.record_mut(sibling).set_next_sibling(target),
         }
+    }
+
+    /// `parent_of` is the node whose children include whatever `link` points at.
+    fn parent_of(&self, link: Link) -> NodeHandle {
+        match link {
+            Link::Root => NodeHandle::NIL,
+            Link::FirstChild(parent) => parent,
+            Link::NextSibling(sibling) => self.record(sibling).parent(),
+        }
+    }
 
-        let byte_pattern = s.as_bytes();
-        for child in self.root.iter() {
-            println!("Checking child {}", child.bytes());
-            if let Some(id) = child.get(byte_pattern) {
-                return Some(id);
+    /// `link_to` finds the link which points at `handle`.
+    fn link_to(&self, handle: NodeHandle) -> Link {
+        let parent = self.record(handle).parent();
+        let mut link = if parent.is_nil() {
+            Link::Root
+        } else {
+            Link::FirstChild(parent)
+        };
+        loop {
+            let next = self.follow(link);
+            if next == handle {
+                return link;
             }
+            link = Link::NextSibling(next);
         }
-        None
     }
 
+    /// `seek` walks the sibling list starting at `link`, looking for the
+    /// sibling whose label has the same lead as `pattern`: its first byte,
+    /// or its first character in `KeyMode::Utf8`. Because siblings are
+    /// sorted, it can stop as soon as it passes where that sibling would be.
+    fn seek(&self, mut link: Link, pattern: &[u8]) -> Seek {
+        let lead = self.mode.lead(pattern);
+        loop {
+            let handle = self.follow(link);
+            if handle.is_nil() {
+                return Seek::Vacant(link);
+            }
+            match self.mode.lead(self.label_of(handle)).cmp(lead) {
+                Ordering::Equal => return Seek::Found(link),
+                Ordering::Greater => return Seek::Vacant(link),
+                Ordering::Less => link = Link::NextSibling(handle),
+            }
+        }
+    }
+
+    /// `descend` follows `pattern` from the top of the trie. It returns the
+    /// node whose subtree holds every key extending the pattern, along with
+    /// the bytes which precede that node's label. The pattern may end
+    /// partway through the node's label.
+    pub(crate) fn descend(&self, pattern: &[u8]) -> Option<(NodeHandle, Vec<u8>)> {
+        let mut link = Link::Root;
+        let mut rest = pattern;
+        let mut thus_far = Vec::new();
+        loop {
+            let handle = match self.seek(link, rest) {
+                Seek::Found(link) => self.follow(link),
+                Seek::Vacant(_) => return None,
+            };
+            let label = self.label_of(handle);
+            let similarity = count_shared_prefix(label, rest);
+            if similarity == rest.len() {
+                return Some((handle, thus_far));
+            }
+            if similarity < label.len() {
+                return None;
+            }
+            thus_far.extend_from_slice(label);
+            rest = &rest[similarity..];
+            link = Link::FirstChild(handle);
+        }
+    }
+
+    /// `find` returns the node whose label ends exactly where `pattern` does.
+    pub(crate) fn find(&self, pattern: &[u8]) -> Option<NodeHandle> {
+        let mut link = Link::Root;
+        let mut rest = pattern;
+        loop {
+            let handle = match self.seek(link, rest) {
+                Seek::Found(link) => self.follow(link),
+                Seek::Vacant(_) => return None,
+            };
+            let label = self.label_of(handle);
+            if !rest.starts_with(label) {
+                return None;
+            }
+            rest = &rest[label.len()..];
+            if rest.is_empty() {
+                return Some(handle);
+            }
+            link = Link::FirstChild(handle);
+        }
+    }
+
+    pub fn get(&self, s: String) -> Option<S> {
+        let key = self.normalize(&s);
+        if key.is_empty() {
+            return self.contains_empty;
+        }
+        let handle = self.find(key.as_bytes())?;
+        self.record(handle).id()
+    }
+
+    /// `size_of` is the total memory used by this trie,
+    /// including the struct itself and everything on the heap.
     pub fn size_of(&self) -> usize {
-        let self_size = size_of::<Self>();
-        let child_size = self
-            .root
-            .iter()
-            .map(|child| child.size_of())
-            .fold(0, |x, acc| x + acc);
-        self_size + child_size
+        size_of::<Self>() + self.heap_size().total()
+    }
+
+    /// `heap_size` breaks down the heap memory owned by this trie.
+    /// Links and short labels sit inline in the node records, so
+    /// only the live part of the label pool, saved spellings and keys
+    /// kept for `Index` count as label bytes. Each node's key count,
+    /// tally and score count as part of its node record. The identifier
+    /// and spelling tables, the table of keys kept for `Index`, the free
+    /// lists and the normaliser's shared allocation count as container
+    /// overhead. The arena slots awaiting reuse and the dead bytes of
+    /// the label pool count as wasted capacity.
+    pub fn heap_size(&self) -> MemoryUsage {
+        let node_size = size_of::<NodeRecord<S>>();
+        let handle_size = size_of::<NodeHandle>();
+        let id_size = size_of::<S>();
+        let spelling_size = size_of::<Option<Box<str>>>();
+        let tally_size = size_of::<Tally>();
+        let free = self.free_nodes.len();
+        // Free slots in the arena have tallies too, once any key is counted.
+        let free_tallies = if self.tallies.is_empty() { 0 } else { free };
+        let scored_size = size_of::<Scored>();
+        let free_scores = if self.scores.is_empty() { 0 } else { free };
+        let count_size = size_of::<u32>();
+        let spelt: usize = self.spellings.iter().flatten().map(|s| s.len()).sum();
+        let resolved_size = size_of::<OnceLock<Box<str>>>();
+        let resolved = self.resolved.get().into_iter().flatten();
+        let kept: usize = resolved.flat_map(OnceLock::get).map(|key| key.len()).sum();
+        let (resolved, resolved_spare) = match self.resolved.get() {
+            Some(resolved) => (resolved.len(), resolved.capacity() - resolved.len()),
+            None => (0, 0),
+        };
+        // The normaliser sits behind its reference counts.
+        let normalizer = self.normalizer.as_ref().map_or(0, |normalizer| {
+            let counts = Layout::new::<[usize; 2]>();
+            let (layout, _) = counts
+                .extend(Layout::for_value(&**normalizer))
+                .expect("the normaliser fits in memory");
+            layout.pad_to_align().size()
+        });
+        MemoryUsage {
+            nodes: (self.nodes.len() - free) * (node_size + count_size)
+                + (self.tallies.len() - free_tallies) * tally_size
+                + (self.scores.len() - free_scores) * scored_size,
+            labels: self.labels.len() - self.dead_labels + spelt + kept,
+            child_containers: (self.ids.len() + free) * handle_size
+                + self.free_ids.len() * id_size
+                + self.spellings.len() * spelling_size
+                + resolved * resolved_size
+                + normalizer,
+            wasted_capacity: (self.nodes.capacity() - self.nodes.len() + free) * node_size
+                + (self.keys_below.capacity() - self.keys_below.len() + free) * count_size
+                + (self.labels.capacity() - self.labels.len() + self.dead_labels)
+                + (self.ids.capacity() - self.ids.len()) * handle_size
+                + (self.free_nodes.capacity() - free) * handle_size
+                + (self.free_ids.capacity() - self.free_ids.len()) * id_size
+                + (self.spellings.capacity() - self.spellings.len()) * spelling_size
+                + resolved_spare * resolved_size
+                + (self.tallies.capacity() - self.tallies.len() + free_tallies) * tally_size
+                + (self.scores.capacity() - self.scores.len() + free_scores) * scored_size,
+        }
     }
 
     pub fn count_nodes(&self) -> u64 {
-        if self.is_empty() {
-            return 0;
+        (self.nodes.len() - self.free_nodes.len()) as u64
+    }
+
+    /// `depth_histogram` counts the nodes found at each depth.
+    /// Top-level nodes sit at depth zero.
+    pub fn depth_histogram(&self) -> Vec<u64> {
+        let mut histogram = Vec::new();
+        let mut stack: Vec<(DLBNode<S>, usize)> = self.top_level().map(|node| (node, 0)).collect();
+        while let Some((node, depth)) = stack.pop() {
+            if histogram.len() <= depth {
+                histogram.resize(depth + 1, 0);
+            }
+            histogram[depth] += 1;
+            stack.extend(node.children().map(|child| (child, depth + 1)));
+        }
+        histogram
+    }
+
+    /// `iter` visits every key in the trie, along with its identifier.
+    /// Keys are visited in lexicographic order.
+    pub fn iter(&self) -> Iter<'_, S> {
+        let stack = self.top_level().map(|node| (node, Vec::new())).collect();
+        Iter::new(self.contains_empty, stack)
+    }
+
+    /// `prefix_iter` visits every key which starts with `prefix`.
+    /// The keys are visited as they are stored, in normal form.
+    pub fn prefix_iter(&self, prefix: String) -> Iter<'_, S> {
+        let prefix = self.normalize(&prefix);
+        if prefix.is_empty() {
+            return self.iter();
+        }
+        let stack = self
+            .descend(prefix.as_bytes())
+            .map(|(handle, thus_far)| (DLBNode::new(self, handle), thus_far))
+            .into_iter()
+            .collect();
+        Iter::new(None, stack)
+    }
+
+    /// `freeze` packs this trie into a read-only `StaticTrie`.
+    /// Identifiers are preserved.
+    pub fn freeze(&self) -> StaticTrie<S> {
+        StaticTrie::from(self)
+    }
+
+    /// `freeze_ranked` packs this trie into a `StaticTrie` whose identifiers
+    /// are the keys' lexicographic ranks, so comparing two identifiers
+    /// compares their keys, and the keys sharing a prefix have consecutive
+    /// identifiers. Returns the remap from old identifiers to new ones.
+    pub fn freeze_ranked(&self) -> (StaticTrie<S>, Remap<S>) {
+        let mut pairs = Vec::with_capacity(self.len);
+        // The empty string sorts before everything else.
+        let mut rank = |old: S| {
+            let new = S::from_usize(pairs.len()).expect("ranks fit wherever identifiers did");
+            pairs.push((old, new));
+        };
+        if let Some(id) = self.contains_empty {
+            rank(id);
+        }
+        // A preorder walk visits keys in lexicographic order.
+        let mut stack: Vec<DLBNode<S>> = self.top_level().collect();
+        stack.reverse();
+        while let Some(node) = stack.pop() {
+            if let Some(id) = node.id() {
+                rank(id);
+            }
+            let len = stack.len();
+            stack.extend(node.children());
+            stack[len..].reverse();
+        }
+        let remap = Remap::from_pairs(pairs);
+        let trie = self
+            .freeze()
+            .map_ids(|old| remap.get(old).expect("every identifier was ranked"));
+        (trie, remap)
+    }
+
+    /// `top_level` iterates over the nodes which hang directly off the root.
+    pub(crate) fn top_level(&self) -> Children<'_, S> {
+        Children::new(self, self.root)
+    }
+
+    pub(crate) fn empty_id(&self) -> Option<S> {
+        self.contains_empty
+    }
+
+    /// `write_to` saves the trie. The arena holds no pointers,
+    /// so it is written out just as it sits in memory. Parent links
+    /// and the identifier table are rebuilt when it is read back.
+    pub fn write_to<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        out.write_all(DLB::MAGIC)?;
+        write_u8(out, self.policy.to_raw())?;
+        write_u8(out, self.mode.to_raw())?;
+        write_u64(out, self.ids.len() as u64)?;
+        write_maybe_id(out, self.contains_empty)?;
+        write_u32(out, self.root.to_raw())?;
+        write_bytes(out, &self.labels)?;
+        write_u32(out, self.nodes.len() as u32)?;
+        for record in self.nodes.iter() {
+            let (len, payload) = record.label().to_raw();
+            write_u32(out, len)?;
+            out.write_all(&payload)?;
+            write_maybe_id(out, record.id())?;
+            write_u32(out, record.first_child().to_raw())?;
+            write_u32(out, record.next_sibling().to_raw())?;
+        }
+        write_u32(out, self.free_nodes.len() as u32)?;
+        for handle in self.free_nodes.iter() {
+            write_u32(out, handle.to_raw())?;
+        }
+        write_u64(out, self.free_ids.len() as u64)?;
+        for id in self.free_ids.iter() {
+            write_u64(out, id.to_usize() as u64)?;
+        }
+        let spellings: Vec<(usize, &str)> = self
+            .spellings
+            .iter()
+            .enumerate()
+            .filter_map(|(slot, spelling)| Some((slot, spelling.as_deref()?)))
+            .collect();
+        write_u64(out, spellings.len() as u64)?;
+        for (slot, spelling) in spellings {
+            write_u64(out, slot as u64)?;
+            write_bytes(out, spelling.as_bytes())?;
+        }
+        // The subtree totals and best scores are worked out again when reading.
+        write_u64(out, self.empty_count)?;
+        write_u32(out, self.tallies.len() as u32)?;
+        for tally in self.tallies.iter() {
+            write_u64(out, tally.count)?;
         }
+        write_u64(out, self.empty_score)?;
+        write_u32(out, self.scores.len() as u32)?;
+        for scored in self.scores.iter() {
+            write_u64(out, scored.score)?;
+        }
+        Ok(())
+    }
 
-        self.root
+    pub fn read_from<R: Read>(input: &mut R) -> io::Result<Self> {
+        expect_magic(input, DLB::MAGIC)?;
+        let policy = IdPolicy::from_raw(read_u8(input)?)
+            .ok_or_else(|| invalid_data("unknown identifier policy"))?;
+        let mode =
+            KeyMode::from_raw(read_u8(input)?).ok_or_else(|| invalid_data("unknown key mode"))?;
+        let id_count = usize::try_from(read_u64(input)?)
+            .map_err(|_| invalid_data("identifier out of range"))?;
+        let contains_empty = read_maybe_id(input)?;
+        let root = NodeHandle::from_raw(read_u32(input)?);
+        let labels = read_bytes(input)?;
+        let node_count = read_u32(input)? as usize;
+        let in_bounds = |handle: NodeHandle| handle.is_nil() || handle.index() < node_count;
+        if !in_bounds(root) {
+            return Err(invalid_data("node out of bounds"));
+        }
+        let mut nodes = Vec::with_capacity(node_count);
+        for _ in 0..node_count {
+            let len = read_u32(input)?;
+            let mut payload = [0; INLINE_CAPACITY];
+            input.read_exact(&mut payload)?;
+            let label = Label::from_raw(len, payload, labels.len())
+                .ok_or_else(|| invalid_data("malformed label"))?;
+            let mut record = NodeRecord::new(label, read_maybe_id(input)?);
+            record.set_first_child(NodeHandle::from_raw(read_u32(input)?));
+            record.set_next_sibling(NodeHandle::from_raw(read_u32(input)?));
+            if !in_bounds(record.first_child()) || !in_bounds(record.next_sibling()) {
+                return Err(invalid_data("node out of bounds"));
+            }
+            nodes.push(record);
+        }
+        let free_count = read_u32(input)? as usize;
+        let mut free_nodes = Vec::with_capacity(free_count.min(node_count));
+        for _ in 0..free_count {
+            let handle = NodeHandle::from_raw(read_u32(input)?);
+            if handle.is_nil() || !in_bounds(handle) {
+                return Err(invalid_data("node out of bounds"));
+            }
+            free_nodes.push(handle);
+        }
+        let free_count = read_u64(input)?;
+        let mut free_ids = Vec::new();
+        for _ in 0..free_count {
+            let id = usize::try_from(read_u64(input)?)
+                .ok()
+                .and_then(S::from_usize)
+                .ok_or_else(|| invalid_data("identifier out of range"))?;
+            free_ids.push(id);
+        }
+        let spelt_count = read_u64(input)?;
+        let mut spellings = Vec::new();
+        for _ in 0..spelt_count {
+            let slot = usize::try_from(read_u64(input)?)
+                .map_err(|_| invalid_data("identifier out of range"))?;
+            let spelling = String::from_utf8(read_bytes(input)?)
+                .map_err(|_| invalid_data("malformed spelling"))?;
+            spellings.push((slot, spelling.into_boxed_str()));
+        }
+        let empty_count = read_u64(input)?;
+        let tally_count = read_u32(input)? as usize;
+        if tally_count != 0 && tally_count != node_count {
+            return Err(invalid_data("malformed counts"));
+        }
+        let mut counts = Vec::with_capacity(tally_count);
+        for _ in 0..tally_count {
+            counts.push(read_u64(input)?);
+        }
+        let empty_score = read_u64(input)?;
+        let score_count = read_u32(input)? as usize;
+        if score_count != 0 && score_count != node_count {
+            return Err(invalid_data("malformed scores"));
+        }
+        let mut scores = Vec::with_capacity(score_count);
+        for _ in 0..score_count {
+            scores.push(read_u64(input)?);
+        }
+        let mut dlb = Self {
+            nodes,
+            labels,
+            dead_labels: 0,
+            root,
+            ids: Vec::new(),
+            free_ids,
+            free_nodes,
+            len: 0,
+            policy,
+            mode,
+            normalizer: None,
+            spellings: Vec::new(),
+            resolved: OnceLock::new(),
+            keys_below: Vec::new(),
+            tallies: Vec::new(),
+            empty_count: 0,
+            scores: Vec::new(),
+            empty_score: 0,
+            contains_empty: None,
+        };
+        dlb.relink_parents(id_count, contains_empty)
+            .ok_or_else(|| invalid_data("malformed trie"))?;
+        for handle in dlb.free_nodes.clone() {
+            dlb.record_mut(handle).set_label(Label::EMPTY);
+        }
+        let live: usize = dlb
+            .nodes
             .iter()
-            .map(|node| node.count_nodes())
-            .fold(0, |acc, inc| acc + inc)
+            .map(|record| record.label().pooled_len())
+            .sum();
+        dlb.dead_labels = dlb.labels.len().saturating_sub(live);
+        let empty_slot = dlb.contains_empty.map(|id| dlb.slot_of(id));
+        for (slot, spelling) in spellings {
+            let in_use = dlb.ids.get(slot).is_some_and(|handle| !handle.is_nil());
+            if !in_use && empty_slot != Some(slot) {
+                return Err(invalid_data("spelling for an unused identifier"));
+            }
+            if dlb.spellings.len() <= slot {
+                dlb.spellings.resize(slot + 1, None);
+            }
+            dlb.spellings[slot] = Some(spelling);
+        }
+        if empty_count > 0 && dlb.contains_empty.is_none() {
+            return Err(invalid_data("count for a missing key"));
+        }
+        dlb.empty_count = empty_count;
+        for (index, count) in counts.into_iter().enumerate() {
+            let handle = NodeHandle::new(index);
+            if count > 0 && dlb.record(handle).id().is_none() {
+                return Err(invalid_data("count for a missing key"));
+            }
+            dlb.set_count(handle, count);
+        }
+        if empty_score > 0 && dlb.contains_empty.is_none() {
+            return Err(invalid_data("score for a missing key"));
+        }
+        dlb.empty_score = empty_score;
+        for (index, score) in scores.into_iter().enumerate() {
+            let handle = NodeHandle::new(index);
+            if score > 0 && dlb.record(handle).id().is_none() {
+                return Err(invalid_data("score for a missing key"));
+            }
+            dlb.set_score_of(handle, score);
+        }
+        Ok(dlb)
+    }
+
+    /// `relink_parents` walks a freshly read arena, filling in the parent
+    /// links, identifier table and key counts. It checks that the nodes form a tree
+    /// with sorted siblings, that no label in the tree is empty, and in
+    /// `KeyMode::Utf8` that every label is valid UTF-8. It also checks that every identifier is used at most
+    /// once, and that each arena slot is either in the tree or free.
+    /// Likewise, each identifier slot must be in use or free unless it
+    /// is retired.
+    fn relink_parents(&mut self, id_count: usize, contains_empty: Option<S>) -> Option<()> {
+        let mut seen = vec![false; self.nodes.len()];
+        for handle in self.free_nodes.iter() {
+            if std::mem::replace(&mut seen[handle.index()], true) {
+                return None;
+            }
+        }
+        self.ids = vec![NodeHandle::NIL; id_count];
+        let mut taken = vec![false; id_count];
+        if self.policy == IdPolicy::Recycled {
+            if id_count > recycled_slots::<S>() {
+                return None;
+            }
+        } else if !self.free_ids.is_empty() {
+            return None;
+        }
+        for id in self.free_ids.iter() {
+            let retired = generation_of(*id) >= max_generation::<S>();
+            if retired || std::mem::replace(taken.get_mut(self.slot_of(*id))?, true) {
+                return None;
+            }
+        }
+        if let Some(id) = contains_empty {
+            if std::mem::replace(taken.get_mut(self.slot_of(id))?, true) {
+                return None;
+            }
+            self.contains_empty = Some(id);
+            self.len += 1;
+        }
+        let mut order = Vec::with_capacity(self.nodes.len());
+        let mut stack = vec![(NodeHandle::NIL, self.root)];
+        while let Some((parent, first)) = stack.pop() {
+            let mut previous = None;
+            let mut handle = first;
+            while !handle.is_nil() {
+                if std::mem::replace(&mut seen[handle.index()], true) {
+                    return None;
+                }
+                let label = self.label_of(handle);
+                if label.is_empty() {
+                    return None;
+                }
+                if self.mode == KeyMode::Utf8 && std::str::from_utf8(label).is_err() {
+                    return None;
+                }
+                let lead = self.mode.lead(label);
+                if previous.is_some_and(|previous| self.mode.lead(self.label_of(previous)) >= lead)
+                {
+                    return None;
+                }
+                previous = Some(handle);
+                order.push(handle);
+                let record = self.record_mut(handle);
+                record.set_parent(parent);
+                let id = record.id();
+                let (child, next) = (record.first_child(), record.next_sibling());
+                if let Some(id) = id {
+                    let slot = self.slot_of(id);
+                    if std::mem::replace(taken.get_mut(slot)?, true) {
+                        return None;
+                    }
+                    self.ids[slot] = handle;
+                    self.len += 1;
+                }
+                stack.push((handle, child));
+                handle = next;
+            }
+        }
+        // Children come after their parents, so count keys bottom up.
+        self.keys_below = vec![0; self.nodes.len()];
+        for handle in order.into_iter().rev() {
+            let record = self.record(handle);
+            let (parent, own) = (record.parent(), record.id().is_some() as u32);
+            let count = self.keys_below(handle) + own;
+            self.keys_below[handle.index()] = count;
+            if !parent.is_nil() {
+                self.keys_below[parent.index()] += count;
+            }
+        }
+        let dense = self.policy != IdPolicy::Dense || self.len == id_count;
+        if seen.into_iter().all(|seen| seen) && dense {
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    fn push_node(&mut self, record: NodeRecord<S>) -> NodeHandle {
+        if let Some(handle) = self.free_nodes.pop() {
+            *self.record_mut(handle) = record;
+            return handle;
+        }
+        let handle = NodeHandle::new(self.nodes.len());
+        self.nodes.push(record);
+        self.keys_below.push(0);
+        if !self.tallies.is_empty() {
+            self.tallies.push(Tally::default());
+        }
+        if !self.scores.is_empty() {
+            self.scores.push(Scored::default());
+        }
+        handle
+    }
+
+    /// `free_node` returns a node's slot to the arena for reuse.
+    /// Any pool bytes its label took up are counted as dead.
+    fn free_node(&mut self, handle: NodeHandle) {
+        let record = self.record_mut(handle);
+        let dead = record.label().pooled_len();
+        record.set_label(Label::EMPTY);
+        record.set_id(None);
+        record.set_first_child(NodeHandle::NIL);
+        record.set_next_sibling(NodeHandle::NIL);
+        record.set_parent(NodeHandle::NIL);
+        self.keys_below[handle.index()] = 0;
+        if let Some(tally) = self.tallies.get_mut(handle.index()) {
+            *tally = Tally::default();
+        }
+        if let Some(scored) = self.scores.get_mut(handle.index()) {
+            *scored = Scored::default();
+        }
+        self.free_nodes.push(handle);
+        self.dead_labels += dead;
+    }
+
+    /// `reclaim_labels` compacts the label pool once more than half of
+    /// it is dead, copying each live label into a new pool. Since every
+    /// copy must first have been made dead, this takes amortised
+    /// constant time per byte, and an emptied trie frees its pool.
+    fn reclaim_labels(&mut self) {
+        if self.dead_labels * 2 <= self.labels.len() {
+            return;
+        }
+        let mut labels = Vec::with_capacity(self.labels.len() - self.dead_labels);
+        for record in self.nodes.iter_mut() {
+            let label = *record.label();
+            if label.is_pooled() {
+                record.set_label(Label::new(label.as_slice(&self.labels), &mut labels));
+            }
+        }
+        self.labels = labels;
+        self.dead_labels = 0;
+    }
+
+    /// `attach` makes a finished node above `children`, which are sorted.
+    fn attach(&mut self, label: &[u8], id: Option<S>, children: &[NodeHandle]) -> NodeHandle {
+        let label = Label::new(label, &mut self.labels);
+        let handle = self.push_node(NodeRecord::new(label, None));
+        if let Some(id) = id {
+            self.assign(handle, id);
+        }
+        let first_child = self.link_siblings(children, handle);
+        self.record_mut(handle).set_first_child(first_child);
+        let below: u32 = children.iter().map(|child| self.keys_below(*child)).sum();
+        self.keys_below[handle.index()] = below + id.is_some() as u32;
+        handle
+    }
+
+    /// `link_siblings` chains `handles` into a sibling list under
+    /// `parent`, returning the head of the list.
+    fn link_siblings(&mut self, handles: &[NodeHandle], parent: NodeHandle) -> NodeHandle {
+        let mut next = NodeHandle::NIL;
+        for &handle in handles.iter().rev() {
+            let record = self.record_mut(handle);
+            record.set_next_sibling(next);
+            record.set_parent(parent);
+            next = handle;
+        }
+        next
+    }
+
+    /// `add_leaf` makes a new node and splices it into the sibling list at `link`.
+    fn add_leaf(&mut self, link: Link, label: &[u8], id: S) {
+        let label = Label::new(label, &mut self.labels);
+        let mut leaf = NodeRecord::new(label, None);
+        leaf.set_next_sibling(self.follow(link));
+        leaf.set_parent(self.parent_of(link));
+        let handle = self.push_node(leaf);
+        self.relink(link, handle);
+        self.complete(handle, id);
+    }
+
+    /// `complete` gives the node at `handle` the new key for `id`.
+    fn complete(&mut self, handle: NodeHandle, id: S) {
+        self.assign(handle, id);
+        self.count_keys_above(handle, |count| count + 1);
+    }
+
+    /// `count_keys_above` updates the key counts of the node at
+    /// `handle` and its ancestors after it gains or loses its key.
+    fn count_keys_above(&mut self, mut handle: NodeHandle, update: fn(u32) -> u32) {
+        while !handle.is_nil() {
+            let count = &mut self.keys_below[handle.index()];
+            *count = update(*count);
+            handle = self.record(handle).parent();
+        }
+    }
+
+    /// `keys_below` is the number of keys completed
+    /// by the node at `handle` and its descendants.
+    pub(crate) fn keys_below(&self, handle: NodeHandle) -> u32 {
+        self.keys_below[handle.index()]
     }
 
-    pub fn get_or_intern(&mut self, s: String) -> Identifier {
-        // Check if root is empty:
-        let bytes = CharList::from(s.clone().into_bytes());
+    /// `split` breaks the label of the node at `link` in two. A new node
+    /// takes the node's place among its siblings and keeps the first `at`
+    /// bytes; the original node becomes its only child and keeps the rest,
+    /// along with its identifier and children. Returns the new node.
+    fn split(&mut self, link: Link, at: usize) -> NodeHandle {
+        let handle = self.follow(link);
+        let record = *self.record(handle);
+        let (head, tail) = record.label().split_at(at);
+        let mut parent = NodeRecord::new(head, None);
+        parent.set_first_child(handle);
+        parent.set_next_sibling(record.next_sibling());
+        parent.set_parent(record.parent());
+        let parent = self.push_node(parent);
+
+        let record = self.record_mut(handle);
+        record.set_label(tail);
+        record.set_next_sibling(NodeHandle::NIL);
+        record.set_parent(parent);
+        self.relink(link, parent);
+        self.keys_below[parent.index()] = self.keys_below(handle);
+        if !self.tallies.is_empty() {
+            self.tallies[parent.index()].total = self.tallies[handle.index()].total;
+        }
+        if !self.scores.is_empty() {
+            self.scores[parent.index()].best = self.scores[handle.index()].best;
+        }
+        parent
+    }
+
+    /// `merge` undoes a split: the only child of the node at `handle`
+    /// takes the node's place, prepending the node's label to its own.
+    /// Like `split`, it leaves the node holding any identifier in place.
+    /// The node must not complete a key, so the child's subtree tallies
+    /// and scores the same as the node's did.
+    fn merge(&mut self, handle: NodeHandle) {
+        let record = *self.record(handle);
+        let child = record.first_child();
+        let tail = *self.record(child).label();
+        let pooled = self.labels.len();
+        let label = record.label().join(&tail, &mut self.labels);
+        if label.is_pooled() && self.labels.len() == pooled {
+            // The labels were joined in place, so the node's
+            // pool bytes now belong to its child.
+            self.record_mut(handle).set_label(Label::EMPTY);
+        } else {
+            self.dead_labels += tail.pooled_len();
+        }
+        let link = self.link_to(handle);
+
+        let child_record = self.record_mut(child);
+        child_record.set_label(label);
+        child_record.set_next_sibling(record.next_sibling());
+        child_record.set_parent(record.parent());
+        self.relink(link, child);
+        self.free_node(handle);
+    }
+
+    /// `prune` tidies up after the node at `handle` loses its identifier.
+    /// A leaf is no longer needed, and a node with one child is merged
+    /// into it, so the trie ends up as if the key had never been added.
+    fn prune(&mut self, handle: NodeHandle) {
+        let record = *self.record(handle);
+        if record.id().is_some() {
+            return;
+        }
+        if record.is_leaf() {
+            let link = self.link_to(handle);
+            self.relink(link, record.next_sibling());
+            self.free_node(handle);
+            if !record.parent().is_nil() {
+                self.prune(record.parent());
+            }
+        } else if self.record(record.first_child()).next_sibling().is_nil() {
+            self.merge(handle);
+        }
+    }
+
+    /// `remove` takes `s` out of the trie, returning what happened to its
+    /// identifier, or `None` if it wasn't present.
+    pub fn remove(&mut self, s: String) -> Option<Removed<S>> {
+        let key = self.normalize(&s);
+        if key.is_empty() {
+            let id = self.detach_empty()?;
+            return Some(self.release_id(id));
+        }
+        let handle = self.find(key.as_bytes())?;
+        self.record(handle).id()?;
+        Some(self.remove_node(handle))
+    }
+
+    /// `remove_node` takes out the key completed by the node at `handle`.
+    /// Other nodes holding identifiers keep their handles.
+    pub(crate) fn remove_node(&mut self, handle: NodeHandle) -> Removed<S> {
+        let id = self.detach_key(handle);
+        self.release_id(id)
+    }
+
+    /// `detach_key` takes the key completed by the node at `handle` out of
+    /// the tree, along with its count, score and spelling, but leaves its
+    /// identifier in use. The caller must either release the identifier or
+    /// give it to another key with `intern_as`.
+    pub(crate) fn detach_key(&mut self, handle: NodeHandle) -> S {
+        let id = self.record(handle).id().expect("the node completes a key");
+        self.record_mut(handle).set_id(None);
+        let slot = self.slot_of(id);
+        self.ids[slot] = NodeHandle::NIL;
+        if let Some(spelling) = self.spellings.get_mut(slot) {
+            *spelling = None;
+        }
+        self.forget_key(slot);
+        self.set_count(handle, 0);
+        self.set_score_of(handle, 0);
+        self.count_keys_above(handle, |count| count - 1);
+        self.prune(handle);
+        self.reclaim_labels();
+        id
+    }
+
+    /// `detach_empty` is `detach_key` for the empty string.
+    pub(crate) fn detach_empty(&mut self) -> Option<S> {
+        let id = self.contains_empty.take()?;
+        let slot = self.slot_of(id);
+        if let Some(spelling) = self.spellings.get_mut(slot) {
+            *spelling = None;
+        }
+        self.forget_key(slot);
+        self.empty_count = 0;
+        self.empty_score = 0;
+        Some(id)
+    }
+
+    /// `remove_subtree` takes out every key at or below the node at
+    /// `handle`, freeing the whole subtree at once rather than pruning
+    /// it key by key. Returns the number of keys removed.
+    pub(crate) fn remove_subtree(&mut self, handle: NodeHandle) -> usize {
+        let removed = self.keys_below(handle);
+        self.detach_subtree(handle);
+        let mut stack = vec![handle];
+        while let Some(node) = stack.pop() {
+            let record = *self.record(node);
+            stack.extend(Children::new(self, record.first_child()).map(|child| child.handle()));
+            // Under `IdPolicy::Dense`, releasing an identifier may move
+            // another one onto a node still to be visited, so the node's
+            // identifier is only read now.
+            if let Some(id) = record.id() {
+                self.record_mut(node).set_id(None);
+                self.release_id(id);
+            }
+            self.free_node(node);
+        }
+        self.reclaim_labels();
+        removed as usize
+    }
+
+    /// `detach_subtree` unlinks the node at `handle` from the tree, along
+    /// with everything below it. The subtree keeps its identifiers, counts
+    /// and scores, ready to be freed or grafted back in elsewhere.
+    pub(crate) fn detach_subtree(&mut self, handle: NodeHandle) {
+        let record = *self.record(handle);
+        let (removed, total) = (self.keys_below(handle), self.tally(handle).total);
+        let mut ancestor = record.parent();
+        while !ancestor.is_nil() {
+            self.keys_below[ancestor.index()] -= removed;
+            if let Some(tally) = self.tallies.get_mut(ancestor.index()) {
+                tally.total -= total;
+            }
+            ancestor = self.record(ancestor).parent();
+        }
+        let link = self.link_to(handle);
+        self.relink(link, record.next_sibling());
+        let detached = self.record_mut(handle);
+        detached.set_next_sibling(NodeHandle::NIL);
+        detached.set_parent(NodeHandle::NIL);
+        let parent = record.parent();
+        if !parent.is_nil() {
+            self.set_score_of(parent, self.scored(parent).score);
+            self.prune(parent);
+        }
+    }
+
+    /// `node_ending_at` is the node whose label ends exactly where
+    /// `prefix` does, splitting a label to make one if need be. It is
+    /// `None` if no key starts with `prefix`, which mustn't be empty.
+    pub(crate) fn node_ending_at(&mut self, prefix: &[u8]) -> Option<NodeHandle> {
+        let (handle, path) = self.descend(prefix)?;
+        let at = prefix.len() - path.len();
+        if at == self.label_of(handle).len() {
+            return Some(handle);
+        }
+        let link = self.link_to(handle);
+        Some(self.split(link, at))
+    }
+
+    /// `lift_top_level` detaches every node hanging off the root, along
+    /// with the empty string, and gathers them below a new node with an
+    /// empty label. Grafting that node back in moves every key at once.
+    pub(crate) fn lift_top_level(&mut self) -> NodeHandle {
+        let top_level: Vec<_> = self.top_level().map(|node| node.handle()).collect();
+        let mut record = NodeRecord::new(Label::EMPTY, None);
+        record.set_first_child(self.root);
+        let handle = self.push_node(record);
+        self.root = NodeHandle::NIL;
+        for child in top_level.iter() {
+            self.record_mut(*child).set_parent(handle);
+        }
+        let keys: u32 = top_level.iter().map(|child| self.keys_below(*child)).sum();
+        self.keys_below[handle.index()] = keys;
+        if !self.tallies.is_empty() {
+            let total = top_level.iter().map(|child| self.tally(*child).total).sum();
+            self.tallies[handle.index()].total = total;
+        }
+        if let Some(id) = self.contains_empty.take() {
+            self.complete(handle, id);
+            let (count, score) = (self.empty_count, self.empty_score);
+            self.set_count(handle, count);
+            self.set_score_of(handle, score);
+            self.empty_count = 0;
+            self.empty_score = 0;
+        }
+        self.set_score_of(handle, self.scored(handle).score);
+        handle
+    }
+
+    /// `graft` links the subtree detached at `handle` back into the tree,
+    /// so that its node ends where `key` does. Wherever the subtree meets
+    /// nodes already in the tree, the two are merged, so none of its keys
+    /// may be in the tree already.
+    pub(crate) fn graft(&mut self, key: &[u8], handle: NodeHandle) {
+        if key.is_empty() {
+            self.absorb(NodeHandle::NIL, handle);
+        } else {
+            self.graft_at(Link::Root, key, handle);
+        }
+        self.reclaim_labels();
+    }
+
+    /// `graft_at` is `graft`, where `key` is followed from the
+    /// sibling list at `link` rather than from the top of the trie.
+    fn graft_at(&mut self, mut link: Link, key: &[u8], handle: NodeHandle) {
+        let mut rest = key;
+        loop {
+            let found = match self.seek(link, rest) {
+                Seek::Found(found) => found,
+                Seek::Vacant(vacant) => return self.link_subtree(vacant, rest, handle),
+            };
+            let target = self.follow(found);
+            let label = self.label_of(target);
+            let similarity = self.mode.boundary(label, count_shared_prefix(label, rest));
+            let target = if similarity < label.len() {
+                self.split(found, similarity)
+            } else {
+                target
+            };
+            if similarity == rest.len() {
+                return self.absorb(target, handle);
+            }
+            rest = &rest[similarity..];
+            link = Link::FirstChild(target);
+        }
+    }
+
+    /// `link_subtree` splices the detached node at `handle` into the
+    /// sibling list at `link`, giving it `label`, and adds its keys to
+    /// those of its new ancestors.
+    fn link_subtree(&mut self, link: Link, label: &[u8], handle: NodeHandle) {
+        // A label ending in the same bytes keeps them where they are.
+        let old = *self.record(handle).label();
+        let label = if old.as_slice(&self.labels).ends_with(label) {
+            let (head, tail) = old.split_at(old.len() - label.len());
+            self.dead_labels += head.pooled_len();
+            tail
+        } else {
+            self.dead_labels += old.pooled_len();
+            Label::new(label, &mut self.labels)
+        };
+        let parent = self.parent_of(link);
+        let next = self.follow(link);
+        let record = self.record_mut(handle);
+        record.set_label(label);
+        record.set_next_sibling(next);
+        record.set_parent(parent);
+        self.relink(link, handle);
+        let (keys, total) = (self.keys_below(handle), self.tally(handle).total);
+        let mut ancestor = parent;
+        while !ancestor.is_nil() {
+            self.keys_below[ancestor.index()] += keys;
+            if let Some(tally) = self.tallies.get_mut(ancestor.index()) {
+                tally.total += total;
+            }
+            ancestor = self.record(ancestor).parent();
+        }
+        if !parent.is_nil() {
+            self.set_score_of(parent, self.scored(parent).score);
+        }
+        // A node split off to end at the old prefix may have
+        // nothing to keep it apart from its only child.
+        self.prune(handle);
+    }
+
+    /// `absorb` merges the detached node at `handle` into the node at
+    /// `target`, which ends at the same key, and frees it. A nil target
+    /// stands for the root, so the node's own key becomes the empty string.
+    fn absorb(&mut self, target: NodeHandle, handle: NodeHandle) {
+        let record = *self.record(handle);
+        if let Some(id) = record.id() {
+            let (count, score) = (self.tally(handle).count, self.scored(handle).score);
+            if target.is_nil() {
+                let slot = self.slot_of(id);
+                self.ids[slot] = NodeHandle::NIL;
+                self.contains_empty = Some(id);
+                self.empty_count = count;
+                self.empty_score = score;
+            } else {
+                debug_assert!(self.record(target).id().is_none(), "keys collided");
+                self.complete(target, id);
+                self.set_count(target, count);
+                self.set_score_of(target, score);
+            }
+        }
+        let link = if target.is_nil() {
+            Link::Root
+        } else {
+            Link::FirstChild(target)
+        };
+        let children: Vec<_> = Children::new(self, record.first_child())
+            .map(|child| child.handle())
+            .collect();
+        for child in children {
+            let label = self.label_of(child).to_vec();
+            self.graft_at(link, &label, child);
+        }
+        self.free_node(handle);
+        // The target may have been split off to end here, only for
+        // every key below it to merge into its one child.
+        if !target.is_nil() {
+            self.prune(target);
+        }
+    }
+
+    /// `get_or_intern` returns the identifier of `s`, adding it if needed.
+    /// Panics if the symbol type has no identifiers left.
+    pub fn get_or_intern(&mut self, s: String) -> S {
+        match self.try_get_or_intern(s) {
+            Ok(id) => id,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// `try_get_or_intern` is `get_or_intern`, but returns an error
+    /// rather than panicking when the identifiers run out. The trie
+    /// is left unchanged in that case.
+    /// A key added through a normaliser remembers its spelling, for `resolve`.
+    pub fn try_get_or_intern(&mut self, s: String) -> Result<S, Exhausted> {
+        let key = match self.normalize(&s) {
+            key if key == s.as_str() => None,
+            key => Some(key.into_owned()),
+        };
+        let key = match key {
+            Some(key) => key,
+            None => return self.intern_key(s),
+        };
+        let len = self.len;
+        let id = self.intern_key(key)?;
+        if self.len > len {
+            self.set_spelling(id, s.into_boxed_str());
+        }
+        Ok(id)
+    }
+
+    /// `intern_key` adds a key which is already in normal form.
+    fn intern_key(&mut self, key: String) -> Result<S, Exhausted> {
         // Special case where the input string is empty.
-        if s.is_empty() {
+        if key.is_empty() {
             return self.intern_empty_string();
         }
+        let bytes = CharList::from(key.into_bytes());
+        self.intern(bytes)
+    }
+
+    /// `set_spelling` records how the key for `id` was spelt before
+    /// it was normalised.
+    pub(crate) fn set_spelling(&mut self, id: S, spelling: Box<str>) {
+        self.respell(id, Some(spelling));
+    }
 
-        // Special case where the trie itself is empty.
-        if self.is_empty() {
-            return self.intern_empty_trie(bytes);
+    /// `respell` replaces the spelling saved for `id` after its key has
+    /// changed, dropping it if `spelling` is `None`.
+    pub(crate) fn respell(&mut self, id: S, spelling: Option<Box<str>>) {
+        let slot = self.slot_of(id);
+        if spelling.is_some() && self.spellings.len() <= slot {
+            self.spellings.resize(slot + 1, None);
+        }
+        if let Some(saved) = self.spellings.get_mut(slot) {
+            *saved = spelling;
         }
+        self.forget_key(slot);
+    }
 
-        // Now we know the root isn't empty.
-        // Because of our special casing above, we
-        // also know the root isn't a leaf.
-        self.intern(bytes)
+    /// `forget_key` drops the key kept for `Index` in `slot`,
+    /// once the key there has changed.
+    fn forget_key(&mut self, slot: usize) {
+        if let Some(resolved) = self.resolved.get_mut() {
+            resolved[slot] = OnceLock::new();
+        }
+    }
+
+    /// `spelling` is how the key for `id` was spelt before it was
+    /// normalised, if that differs from the key itself.
+    pub(crate) fn spelling(&self, id: S) -> Option<&str> {
+        let spelling = self.spellings.get(self.slot_of(id))?.as_deref()?;
+        Some(spelling).filter(|_| self.contains_id(id))
     }
 
-    pub fn intern_empty_string(&mut self) -> Identifier {
+    pub fn intern_empty_string(&mut self) -> Result<S, Exhausted> {
         match self.contains_empty {
-            Some(id) => id,
+            Some(id) => Ok(id),
             None => {
-                let id = self.new_id();
+                let id = self.new_id()?;
                 self.contains_empty = Some(id);
-                id
+                Ok(id)
             }
         }
     }
 
-    pub fn intern_empty_trie(&mut self, bytes: CharList) -> Identifier {
-        // Make a new leaf node.
-        let id = self.new_id();
-        let new_node_data = LeafData::new(id, bytes);
-        let new_leaf = DLBNode::Leaf(new_node_data);
+    /// `intern` walks down the trie consuming `bytes`. Wherever the bytes
+    /// part ways with an existing label, that label is split so the
+    /// remaining bytes can branch off as a new leaf. The bytes are
+    /// stored as given, so callers must have normalised them already,
+    /// and checked them in `KeyMode::Utf8`.
+    pub(crate) fn intern(&mut self, bytes: CharList) -> Result<S, Exhausted> {
+        self.intern_as(bytes.as_slice(), None)
+    }
 
-        self.root.push(new_leaf);
-        id
+    /// `intern_as` is `intern`, but a new key is given `fixed` if it is
+    /// set, rather than a new identifier. A fixed identifier must be in
+    /// use without belonging to any key, as `detach_key` leaves it.
+    pub(crate) fn intern_as(&mut self, bytes: &[u8], fixed: Option<S>) -> Result<S, Exhausted> {
+        let next_id = |dlb: &mut Self| match fixed {
+            Some(id) => Ok(id),
+            None => dlb.new_id(),
+        };
+        if bytes.is_empty() {
+            if let Some(id) = self.contains_empty {
+                return Ok(id);
+            }
+            let id = next_id(self)?;
+            self.contains_empty = Some(id);
+            return Ok(id);
+        }
+        let mut rest = bytes;
+        let mut link = Link::Root;
+        loop {
+            let found = match self.seek(link, rest) {
+                Seek::Found(found) => found,
+                Seek::Vacant(vacant) => {
+                    // Nothing shares a prefix with these bytes; make a new leaf.
+                    let id = next_id(self)?;
+                    self.add_leaf(vacant, rest, id);
+                    return Ok(id);
+                }
+            };
+            let handle = self.follow(found);
+            let label = self.label_of(handle);
+            let similarity = self.mode.boundary(label, count_shared_prefix(label, rest));
+            let consumes_label = similarity == label.len();
+            let consumes_pattern = similarity == rest.len();
+            match (consumes_label, consumes_pattern) {
+                // Exact match: this node completes the key.
+                (true, true) => {
+                    if let Some(id) = self.record(handle).id() {
+                        return Ok(id);
+                    }
+                    let id = next_id(self)?;
+                    self.complete(handle, id);
+                    return Ok(id);
+                }
+                // The label is consumed, so keep going with the children.
+                (true, false) => {
+                    rest = &rest[similarity..];
+                    link = Link::FirstChild(handle);
+                }
+                // The pattern ends partway through the label.
+                (false, true) => {
+                    let id = next_id(self)?;
+                    let parent = self.split(found, similarity);
+                    self.complete(parent, id);
+                    return Ok(id);
+                }
+                // Two roads diverge in a Yellow Wood.
+                (false, false) => {
+                    let id = next_id(self)?;
+                    let parent = self.split(found, similarity);
+                    let rest = &rest[similarity..];
+                    let vacant = match self.seek(Link::FirstChild(parent), rest) {
+                        Seek::Vacant(vacant) => vacant,
+                        Seek::Found(_) => unreachable!("the split left a single child"),
+                    };
+                    self.add_leaf(vacant, rest, id);
+                    return Ok(id);
+                }
+            }
+        }
     }
 
-    fn find_best_match(&self, bytes: CharList) -> (Option<usize>, usize) {
-        let mut matching_index = None;
-        let mut match_length = 0;
-        for (idx, child) in self.root.iter().enumerate() {
-            let matching = child.similar_bytes(bytes.clone());
-            if matching > 0 {
-                matching_index = Some(idx);
-                match_length = matching;
-                break;
+    /// `tally` is how often keys were counted at and below `handle`.
+    pub(crate) fn tally(&self, handle: NodeHandle) -> Tally {
+        self.tallies
+            .get(handle.index())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// `empty_count` is how often the empty string was counted.
+    pub(crate) fn empty_count(&self) -> u64 {
+        self.empty_count
+    }
+
+    /// `add_count` adds `by` to how often the key for `id` was counted.
+    pub(crate) fn add_count(&mut self, id: S, by: u64) {
+        if self.contains_empty == Some(id) {
+            self.empty_count = self.empty_count.saturating_add(by);
+        } else if let Some(handle) = self.node_of(id) {
+            let count = self.tally(handle).count.saturating_add(by);
+            self.set_count(handle, count);
+        }
+    }
+
+    /// `set_count` records how often the key ending at `handle` was
+    /// counted, correcting the totals of the subtrees holding it.
+    pub(crate) fn set_count(&mut self, handle: NodeHandle, count: u64) {
+        if self.tallies.is_empty() {
+            if count == 0 {
+                return;
             }
+            self.tallies.resize(self.nodes.len(), Tally::default());
+        }
+        let old = std::mem::replace(&mut self.tallies[handle.index()].count, count);
+        let mut ancestor = handle;
+        while !ancestor.is_nil() {
+            let tally = &mut self.tallies[ancestor.index()];
+            tally.total = tally.total - old + count;
+            ancestor = self.record(ancestor).parent();
         }
-        return (matching_index, match_length);
     }
 
-    fn add_new_leaf(&mut self, bytes: CharList) -> Identifier {
-        let id = self.new_id();
-        let new_leaf_data = LeafData::new(id, bytes);
-        let new_leaf = DLBNode::Leaf(new_leaf_data);
-        self.root.push(new_leaf);
-        return id;
+    /// `scored` is the score of the key at `handle`, and
+    /// the best score of any key in its subtree.
+    pub(crate) fn scored(&self, handle: NodeHandle) -> Scored {
+        self.scores.get(handle.index()).copied().unwrap_or_default()
+    }
+
+    /// `empty_score` is the score of the empty string.
+    pub(crate) fn empty_score(&self) -> u64 {
+        self.empty_score
     }
 
-    pub fn intern(&mut self, bytes: CharList) -> Identifier {
-        let (matching_index, _) = self.find_best_match(bytes.clone());
+    pub(crate) fn set_empty_score(&mut self, score: u64) {
+        self.empty_score = score;
+    }
 
-        // If we found nothing, make a new leaf.
-        if let None = matching_index {
-            return self.add_new_leaf(bytes);
+    /// `set_score_of` scores the key ending at `handle`, and updates
+    /// the best scores of the subtrees holding it. Where the score
+    /// drops, the siblings below each ancestor are checked again.
+    pub(crate) fn set_score_of(&mut self, handle: NodeHandle, score: u64) {
+        if self.scores.is_empty() {
+            if score == 0 {
+                return;
+            }
+            self.scores.resize(self.nodes.len(), Scored::default());
+        }
+        self.scores[handle.index()].score = score;
+        let mut node = handle;
+        while !node.is_nil() {
+            let record = *self.record(node);
+            let best = Children::new(self, record.first_child())
+                .map(|child| self.scored(child.handle()).best)
+                .fold(self.scored(node).score, u64::max);
+            if best == self.scored(node).best {
+                break;
+            }
+            self.scores[node.index()].best = best;
+            node = record.parent();
         }
-        // Else, add this pattern to the longest one we have.
-        let idx = matching_index.unwrap();
-        return self.root[idx].insert(bytes, &mut self.next_id);
     }
 
-    pub fn resolve(&self, id: Identifier) -> Option<String> {
-        if self.is_empty() {
+    /// `node_of` finds the node holding `id`.
+    pub(crate) fn node_of(&self, id: S) -> Option<NodeHandle> {
+        let handle = *self.ids.get(self.slot_of(id))?;
+        // A recycled slot may now hold a newer identifier.
+        if handle.is_nil() || self.record(handle).id() != Some(id) {
             return None;
         }
+        Some(handle)
+    }
 
-        if let Some(empty) = self.contains_empty {
-            if empty == id {
-                return Some("".to_owned());
-            }
+    /// `contains_id` reports whether `id` belongs to a key in the trie.
+    /// Identifiers of removed keys are rejected, unless another key
+    /// has since been given the same identifier.
+    pub fn contains_id(&self, id: S) -> bool {
+        self.contains_empty == Some(id) || self.node_of(id).is_some()
+    }
+
+    /// `resolve` finds the node holding `id` and follows
+    /// the parent links up to the root to rebuild its key.
+    /// Keys interned through a normaliser come back spelt as
+    /// they were when first interned.
+    pub fn resolve(&self, id: S) -> Option<String> {
+        if let Some(spelling) = self.spelling(id) {
+            return Some(spelling.to_owned());
         }
+        if self.contains_empty == Some(id) {
+            return Some(String::new());
+        }
+        let mut handle = self.node_of(id)?;
+        let mut labels = Vec::new();
+        while !handle.is_nil() {
+            labels.push(self.label_of(handle));
+            handle = self.record(handle).parent();
+        }
+        let bytes: Vec<u8> = labels.into_iter().rev().flatten().copied().collect();
+        String::from_utf8(bytes).ok()
+    }
 
-        let res = self
-            .root
-            .iter()
-            .map(|child| child.resolve(id, CharList::empty()))
-            .filter(|x| x.is_some())
-            .next();
-        match res {
-            None => None,
-            Some(x) => Some(x.unwrap()),
+    /// `resolved_key` is `resolve`, but keeps the key it builds
+    /// so that it can be borrowed from the trie.
+    fn resolved_key(&self, id: S) -> Option<&str> {
+        if !self.contains_id(id) {
+            return None;
+        }
+        let resolved = self
+            .resolved
+            .get_or_init(|| self.ids.iter().map(|_| OnceLock::new()).collect());
+        let key = &resolved[self.slot_of(id)];
+        if key.get().is_none() {
+            let _ = key.set(self.resolve(id)?.into_boxed_str());
         }
+        key.get().map(|key| &**key)
     }
 }
 
+/// `slot_bits` is how many low bits of an identifier pick its slot
+/// under `IdPolicy::Recycled`. The rest count its generation.
+fn slot_bits<S: Symbol>() -> u32 {
+    S::BITS - S::BITS / 4
+}
+
+fn slot_mask<S: Symbol>() -> usize {
+    (1 << slot_bits::<S>()) - 1
+}
+
+/// `recycled_slots` leaves out the last slot, so that no
+/// identifier has every bit set and they all fit in `S`.
+fn recycled_slots<S: Symbol>() -> usize {
+    slot_mask::<S>()
+}
+
+fn generation_of<S: Symbol>(id: S) -> usize {
+    id.to_usize() >> slot_bits::<S>()
+}
+
+fn max_generation<S: Symbol>() -> usize {
+    (1 << (S::BITS / 4)) - 1
+}
+
+/// `next_generation` is the identifier which replaces `stale` in its slot.
+fn next_generation<S: Symbol>(stale: S) -> S {
+    S::from_usize(stale.to_usize() + (1 << slot_bits::<S>())).unwrap()
+}
+
+pub(crate) fn count_shared_prefix(left: &[u8], right: &[u8]) -> usize {
+    left.iter()
+        .zip(right.iter())
+        .take_while(|(x, y)| x == y)
+        .count()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::dtrie::{Hex, KeyMode, Sym16, Sym32};
 
     #[test]
     fn test_is_empty() {
         println!("Running empty test");
         let dlb = DLB::new();
-        assert_eq!(dlb.is_empty(), true);
+        assert!(dlb.is_empty());
     }
 
     #[test]
@@ -206,9 +1761,9 @@ mod tests {
         let string = "foo".to_owned();
         let mut dlb = DLB::new();
         let id = dlb.get_or_intern(string.clone());
-        assert_eq!(dlb.is_empty(), false);
+        assert!(!dlb.is_empty());
         let found = dlb.contains(string.clone());
-        assert_eq!(found, true);
+        assert!(found);
         let found_id = dlb.get(string.clone()).unwrap();
         assert_eq!(id, found_id);
     }
@@ -233,15 +1788,342 @@ mod tests {
         let mut dlb = DLB::new();
         vec!["foo", "boo", "food", "god", "goodbye"]
             .into_iter()
-            .map(|x| String::from(x))
+            .map(String::from)
             .for_each(|x| {
                 dlb.get_or_intern(x);
             });
         let not_contained = vec!["foog", "fb", "boob", "foodstuff", "fish", "goodnight"];
         not_contained
             .into_iter()
-            .map(|x| String::from(x))
+            .map(String::from)
             .map(|x| dlb.contains(x))
-            .for_each(|x| assert_eq!(x, false));
+            .for_each(|x| assert!(!x));
+    }
+
+    #[test]
+    fn test_node_layout_is_compact() {
+        // Links and short labels are inline, and the identifier's
+        // niche means `Option<Identifier>` costs nothing extra.
+        assert_eq!(size_of::<Option<Identifier>>(), size_of::<Identifier>());
+        assert!(size_of::<NodeRecord<Identifier>>() <= 32);
+        assert_eq!(size_of::<Option<Sym32>>(), size_of::<Sym32>());
+        assert!(size_of::<NodeRecord<Sym32>>() <= 28);
+    }
+
+    #[test]
+    fn test_symbols_round_trip() {
+        assert_eq!(Sym16::from_usize(0).map(Sym16::to_usize), Some(0));
+        assert_eq!(Sym16::from_usize(65534).map(u16::from), Some(65534));
+        assert_eq!(Sym16::from_usize(65535), None);
+        assert_eq!(Sym32::from_usize(7).map(Sym32::to_usize), Some(7));
+        assert_eq!(Identifier::from_usize(0), Identifier::new(0));
+    }
+
+    #[test]
+    fn test_small_symbols_run_out() {
+        let mut dlb = DLB::<Sym16>::default();
+        for i in 0..=u16::MAX as usize - 1 {
+            let id = dlb.try_get_or_intern(i.to_string()).unwrap();
+            assert_eq!(id.to_usize(), i);
+        }
+        let nodes = dlb.count_nodes();
+        assert_eq!(dlb.try_get_or_intern(String::from("x")), Err(Exhausted));
+        assert_eq!(dlb.try_get_or_intern(String::from("65535")), Err(Exhausted));
+        assert_eq!(dlb.try_get_or_intern(String::new()), Err(Exhausted));
+        assert_eq!(dlb.count_nodes(), nodes);
+        // Keys which are already present can still be looked up.
+        let id = dlb.try_get_or_intern(String::from("42")).unwrap();
+        assert_eq!(id.to_usize(), 42);
+    }
+
+    #[test]
+    fn test_siblings_stay_sorted() {
+        let mut dlb = DLB::new();
+        vec!["pop", "bar", "foo", "fa", "food", "lizz", "f"]
+            .into_iter()
+            .map(String::from)
+            .for_each(|x| {
+                dlb.get_or_intern(x);
+            });
+        let keys: Vec<String> = dlb.iter().map(|(key, _)| key).collect();
+        assert_eq!(keys, vec!["bar", "f", "fa", "foo", "food", "lizz", "pop"]);
+    }
+
+    #[test]
+    fn test_clone_is_independent() {
+        let mut dlb = DLB::new();
+        let fizz = dlb.get_or_intern(String::from("fizz"));
+        let mut copy = dlb.clone();
+        let fang = copy.get_or_intern(String::from("fang"));
+        assert_eq!(copy.get(String::from("fizz")), Some(fizz));
+        assert_eq!(copy.resolve(fang), Some(String::from("fang")));
+        assert_eq!(dlb.get(String::from("fang")), None);
+        assert_eq!(dlb.count_nodes(), 1);
+    }
+
+    fn build(policy: IdPolicy, words: &[&str]) -> DLB {
+        let mut dlb = DLB::with_policy(policy);
+        dlb.extend(words);
+        dlb
+    }
+
+    #[test]
+    fn test_utf8_mode_splits_between_chars() {
+        let words = ["é", "è", "日本", "日曜", "x"];
+        for mode in [KeyMode::Bytes, KeyMode::Utf8].iter() {
+            let mut dlb: DLB = DLB::with_mode(*mode);
+            dlb.extend(words.iter());
+            let whole = dlb
+                .nodes
+                .iter()
+                .all(|record| std::str::from_utf8(record.label().as_slice(&dlb.labels)).is_ok());
+            assert_eq!(whole, *mode == KeyMode::Utf8);
+            for word in words.iter() {
+                let id = dlb.get(word.to_string()).unwrap();
+                assert_eq!(dlb.resolve(id).as_deref(), Some(*word));
+            }
+            assert_eq!(dlb.get(String::from("日")), None);
+            let keys: Vec<String> = dlb
+                .prefix_iter(String::from("日"))
+                .map(|(key, _)| key)
+                .collect();
+            assert_eq!(keys, vec!["日曜", "日本"]);
+        }
+        // "é" and "è" share a first byte but are still siblings.
+        let dlb: DLB = {
+            let mut dlb = DLB::with_mode(KeyMode::Utf8);
+            dlb.extend(["é", "è"].iter());
+            dlb
+        };
+        assert_eq!(dlb.count_nodes(), 2);
+    }
+
+    #[test]
+    fn test_collect_and_extend() {
+        let mut dlb: DLB = vec!["foo", "bar"].into_iter().collect();
+        dlb.extend(vec![String::from("baz"), String::from("foo")]);
+        assert_eq!(dlb.len(), 3);
+        let keys: Vec<(String, usize)> = dlb.iter().map(|(key, id)| (key, id.to_usize())).collect();
+        assert_eq!(
+            keys,
+            vec![
+                (String::from("bar"), 1),
+                (String::from("baz"), 2),
+                (String::from("foo"), 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_eq_ignores_layout() {
+        let forwards = build(IdPolicy::Sequential, &["ab", "a", "abc"]);
+        let mut backwards = build(IdPolicy::Sequential, &["abc", "ab", "a", "b"]);
+        // The same keys with different identifiers aren't equal.
+        assert_ne!(forwards, backwards);
+        backwards.remove(String::from("b"));
+        let renumbered: DLB = backwards.iter().map(|(key, _)| key).collect();
+        assert_ne!(forwards, renumbered);
+        let reordered = build(IdPolicy::Sequential, &["ab", "a", "abc"]);
+        assert_eq!(forwards, reordered);
+        assert_eq!(forwards, forwards.clone());
+    }
+
+    #[test]
+    fn test_debug_prints_tree() {
+        let dlb = build(IdPolicy::Sequential, &["", "ab", "abc", "abd"]);
+        let expected = concat!(
+            "DLB { len: 4, policy: Sequential, empty: Some(Sym64(1)), children: [",
+            "DLBNode { label: \"ab\", id: Sym64(2), children: [",
+            "DLBNode { label: \"c\", id: Sym64(3) }, ",
+            "DLBNode { label: \"d\", id: Sym64(4) }] }] }"
+        );
+        assert_eq!(format!("{:?}", dlb), expected);
+        assert!(format!("{:#?}", dlb).contains("label: \"c\",\n"));
+    }
+
+    #[test]
+    fn test_index_follows_changes() {
+        let mut dlb = build(IdPolicy::Dense, &["foo", "bar", "", "baz"]);
+        let bar = dlb.get(String::from("bar")).unwrap();
+        assert_eq!(&dlb[bar], "bar");
+        assert_eq!(&dlb[dlb.get(String::new()).unwrap()], "");
+        // The last key moves into the gap, and indexing follows it.
+        dlb.remove(String::from("bar"));
+        assert_eq!(&dlb[bar], "baz");
+        dlb.get_or_intern(String::from("qux"));
+        assert_eq!(&dlb[Identifier::from_usize(3).unwrap()], "qux");
+        assert_eq!(
+            dlb.rename_prefix(String::from("ba"), String::from("bo")),
+            Ok(1)
+        );
+        assert_eq!(&dlb[bar], "boz");
+        assert!(dlb.heap_size().labels >= "boz".len() + "qux".len());
+    }
+
+    #[test]
+    #[should_panic(expected = "no key has this identifier")]
+    fn test_index_panics_on_unknown_ids() {
+        let dlb = build(IdPolicy::Sequential, &["foo"]);
+        let _ = &dlb[Identifier::from_usize(1).unwrap()];
+    }
+
+    #[test]
+    fn test_display_escapes_and_draws_tree() {
+        let mut dlb = build(IdPolicy::Sequential, &["", "ab", "abc", "a\"d\n"]);
+        let binary = dlb.intern(CharList::from(vec![b'a', b'b', 0xff])).unwrap();
+        assert_eq!(binary.to_usize(), 4);
+        assert_eq!(
+            dlb.to_string(),
+            r#"{"": 0, "a\"d\n": 3, "ab": 1, "abc": 2, "ab\xff": 4}"#
+        );
+        let tree = concat!(
+            "(root): 0\n",
+            "└── a\n",
+            "    ├── \"d\\n: 3\n",
+            "    └── b: 1\n",
+            "        ├── c: 2\n",
+            "        └── \\xff: 4",
+        );
+        assert_eq!(format!("{:#}", dlb), tree);
+        let node = dlb.top_level().next().unwrap();
+        assert_eq!(node.to_string(), "a");
+        assert_eq!(node.count_keys(), 4);
+        assert!(format!("{:#}", node).starts_with("a\n├── \"d\\n: 3\n"));
+
+        assert_eq!(
+            Hex(&dlb).to_string(),
+            "{[]: 0, [61 22 64 0a]: 3, [61 62]: 1, [61 62 63]: 2, [61 62 ff]: 4}"
+        );
+        assert!(format!("{:#}", Hex(&dlb)).ends_with("├── 63: 2\n        └── ff: 4"));
+
+        let bytes = CharList::from(b"foo\x00bar".to_vec());
+        assert_eq!(bytes.to_string(), "foo\\u{0}bar");
+        assert_eq!(format!("{:?}", bytes), "\"foo\\u{0}bar\"");
+        assert_eq!(Hex(&bytes).to_string(), "66 6f 6f 00 62 61 72");
+        assert_eq!(
+            format!("{:#}", Hex(&bytes)),
+            "00000000  66 6f 6f 00 62 61 72                             |foo.bar|"
+        );
+    }
+
+    #[test]
+    fn test_remove_restores_shape() {
+        let words = ["1080", "10-point", "10th", "a", "aaa", "aa", "fizz", "fang"];
+        let mut dlb = build(IdPolicy::Sequential, &words);
+        let fresh = build(IdPolicy::Sequential, &["1080", "10th", "a", "aaa", "fizz"]);
+        for word in ["10-point", "aa", "fang"].iter() {
+            assert!(dlb.remove(word.to_string()).is_some());
+        }
+        assert_eq!(dlb.remove(String::from("fang")), None);
+        assert_eq!(dlb.remove(String::from("f")), None);
+        assert_eq!(dlb.count_nodes(), fresh.count_nodes());
+        assert_eq!(dlb.depth_histogram(), fresh.depth_histogram());
+        let keys: Vec<String> = dlb.iter().map(|(key, _)| key).collect();
+        assert_eq!(keys, vec!["1080", "10th", "a", "aaa", "fizz"]);
+        for (key, id) in dlb.iter() {
+            assert_eq!(dlb.resolve(id), Some(key));
+        }
+        // Freed nodes are reused before the arena grows.
+        let before = dlb.nodes.len();
+        dlb.get_or_intern(String::from("fang"));
+        assert_eq!(dlb.nodes.len(), before);
+    }
+
+    #[test]
+    fn test_remove_reclaims_label_pool() {
+        let mut dlb = DLB::<Identifier>::with_policy(IdPolicy::Dense);
+        let keys: Vec<String> = (0..10)
+            .map(|i| format!("interoperability-{}-characteristically", i))
+            .collect();
+        for round in 0..200 {
+            for key in keys.iter() {
+                dlb.get_or_intern(key.clone());
+            }
+            // Each removal merges the shared prefix into a sibling,
+            // copying both labels to the end of the pool.
+            for key in keys.iter().skip(round % 2).step_by(2) {
+                dlb.remove(key.clone()).unwrap();
+            }
+            let live: usize = dlb.nodes.iter().map(|r| r.label().pooled_len()).sum();
+            assert_eq!(dlb.labels.len() - dlb.dead_labels, live);
+            assert!(dlb.labels.len() <= 2 * live);
+        }
+        for key in keys.iter() {
+            dlb.remove(key.clone());
+        }
+        assert!(dlb.is_empty());
+        assert_eq!(dlb.labels.capacity(), 0);
+    }
+
+    #[test]
+    fn test_sequential_ids_leave_gaps() {
+        let mut dlb = build(IdPolicy::Sequential, &["foo", "bar", "baz"]);
+        let bar = dlb.get(String::from("bar")).unwrap();
+        let removed = dlb.remove(String::from("bar")).unwrap();
+        assert_eq!(
+            removed,
+            Removed {
+                id: bar,
+                moved: None
+            }
+        );
+        assert_eq!(dlb.len(), 2);
+        assert_eq!(dlb.resolve(bar), None);
+        let next = dlb.get_or_intern(String::from("bar"));
+        assert_eq!(next.to_usize(), 3);
+    }
+
+    #[test]
+    fn test_dense_ids_stay_contiguous() {
+        let mut dlb = build(IdPolicy::Dense, &["foo", "bar", "baz", ""]);
+        // The last key moves into the gap, just like `Vec::swap_remove`.
+        let removed = dlb.remove(String::from("bar")).unwrap();
+        assert_eq!(removed.id.to_usize(), 1);
+        assert_eq!(removed.moved.map(Identifier::to_usize), Some(3));
+        assert_eq!(dlb.get(String::new()), Some(removed.id));
+        assert_eq!(dlb.resolve(removed.id), Some(String::new()));
+        // Removing the last key moves nothing.
+        let removed = dlb.remove(String::from("baz")).unwrap();
+        assert_eq!(removed.id.to_usize(), 2);
+        assert_eq!(removed.moved, None);
+        let removed = dlb.remove(String::from("foo")).unwrap();
+        assert_eq!(removed.moved.map(Identifier::to_usize), Some(1));
+        assert_eq!(dlb.get(String::new()).map(Identifier::to_usize), Some(0));
+        assert_eq!(dlb.len(), 1);
+    }
+
+    #[test]
+    fn test_recycled_ids_detect_stale_handles() {
+        let mut dlb = build(IdPolicy::Recycled, &["foo", "bar", "baz"]);
+        let bar = dlb.get(String::from("bar")).unwrap();
+        dlb.remove(String::from("bar")).unwrap();
+        let fizz = dlb.get_or_intern(String::from("fizz"));
+        // The slot is reused, but the stale identifier doesn't alias the new key.
+        assert_ne!(fizz, bar);
+        assert_eq!(dlb.slot_of(fizz), dlb.slot_of(bar));
+        assert_eq!(dlb.resolve(bar), None);
+        assert!(!dlb.contains_id(bar));
+        assert_eq!(dlb.resolve(fizz), Some(String::from("fizz")));
+        assert_eq!(dlb.len(), 3);
+        assert_eq!(dlb.ids.len(), 3);
+    }
+
+    #[test]
+    fn test_recycled_ids_retire() {
+        let mut dlb = DLB::<Sym16>::with_policy(IdPolicy::Recycled);
+        let first = dlb.get_or_intern(String::from("key"));
+        let mut ids = vec![first];
+        for _ in 0..20 {
+            dlb.remove(String::from("key")).unwrap();
+            ids.push(dlb.get_or_intern(String::from("key")));
+        }
+        // Sixteen generations fit in the top four bits of a `Sym16`.
+        let slots: Vec<usize> = ids.iter().map(|id| dlb.slot_of(*id)).collect();
+        assert!(slots[..16].iter().all(|slot| *slot == 0));
+        assert_eq!(slots[16], 1);
+        let mut unique = ids.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), ids.len());
     }
 }
@@ -1,325 +1,120 @@
-use crate::dtrie::char_list::CharList;
-use crate::dtrie::internal_data::InternalData;
-use crate::dtrie::leaf_data::LeafData;
-use crate::dtrie::Identifier;
-use crate::dtrie::Matchable;
-use im::vector;
-use std::mem::size_of;
-use std::str::from_utf8;
-use std::sync::atomic::{AtomicU64, Ordering};
+use crate::dtrie::display::{write_node, Style};
+use crate::dtrie::node_handle::NodeHandle;
+use crate::dtrie::Escaped;
+use crate::dtrie::Symbol;
+use crate::dtrie::DLB;
+use std::fmt;
+
+/// `DLBNode` is a read-only view of one node in a `DLB`.
+pub struct DLBNode<'a, S> {
+    dlb: &'a DLB<S>,
+    handle: NodeHandle,
+}
 
-#[derive(Clone)]
-pub enum DLBNode {
-    Leaf(LeafData),
-    Internal(InternalData),
+// Derived `Clone` would needlessly require `S: Clone`.
+impl<'a, S> Clone for DLBNode<'a, S> {
+    fn clone(&self) -> Self {
+        *self
+    }
 }
 
-type NodeDescription = (Identifier, CharList);
+impl<'a, S> Copy for DLBNode<'a, S> {}
 
-impl DLBNode {
-    pub fn count_nodes(&self) -> u64 {
-        match self {
-            DLBNode::Leaf(data) => {
-                println!("…counting leaf pattern={}.", data.bytes().to_string());
-                return 1;
-            }
-            DLBNode::Internal(data) => {
-                println!(
-                    "…counting intern pattern={} with {} kids.",
-                    data.bytes().to_string(),
-                    data.children().len()
-                );
-                1 + data
-                    .children()
-                    .iter()
-                    .map(|node| node.count_nodes())
-                    .fold(0, |acc, inc| acc + inc)
-            }
-        }
+impl<'a, S: Symbol> DLBNode<'a, S> {
+    pub(crate) fn new(dlb: &'a DLB<S>, handle: NodeHandle) -> Self {
+        Self { dlb, handle }
     }
 
-    pub fn bytes(&self) -> CharList {
-        match self {
-            DLBNode::Leaf(data) => data.bytes().clone(),
-            DLBNode::Internal(data) => data.bytes().clone(),
-        }
+    pub(crate) fn handle(&self) -> NodeHandle {
+        self.handle
     }
 
-    fn build_full_match(left: NodeDescription, right: NodeDescription) -> Self {
-        // Order them according to length
-        let (smallest, mut largest) = if left.1.len() < right.1.len() {
-            (left, right)
-        } else {
-            (right, left)
-        };
-        let remaining = largest.1.split_off(smallest.1.len());
-        // Make a leaf with the remaining bytes.
-        let leaf_data = LeafData::new(largest.0, remaining);
-        let leaf = DLBNode::Leaf(leaf_data);
-        // Now build the internal node.
-        let child = vector![leaf];
-        let internal_data = InternalData::new(smallest.1, Some(smallest.0), child);
-        Self::Internal(internal_data)
+    /// `label` borrows the bytes on the edge leading into this node.
+    pub fn label(&self) -> &'a [u8] {
+        self.dlb.label_of(self.handle)
     }
 
-    pub fn insert(&mut self, mut pattern: CharList, next_id: &mut AtomicU64) -> Identifier {
-        match self {
-            DLBNode::Leaf(data) => {
-                // Consume any if the characters in pattern which match on
-                // data.bytes().
-                let similarity = data.similar_bytes(pattern.clone());
-                let consumes_entire_pattern = similarity == pattern.len();
-                let consumes_entire_leaf = similarity == data.bytes().len();
-                let no_match = similarity == 0;
-                // Case 1: No match.
-                if no_match {
-                    println!("My data: {:?}", data.bytes());
-                    println!("My pattern: {:?}", pattern);
-                    unreachable!("Similarity required to have gotten his far");
-                }
-                // Case 2: Exact match
-                // If no bytes remain, return my integer.
-                if consumes_entire_pattern && consumes_entire_leaf {
-                    return data.id();
-                }
-                // Case 3: Exactly one of the leaf or the pattern is entirely consumed
-                // i.e. pattern.len() > self.data.len() OR pattern.len() < self.data.len()
-                // Action:
-                //    Create an internal node. IsComplete is true since it matches either
-                //    the leaf entirely or the pattern entirely.
-                //    Have it's children be a leaf with the remaining bytes from the match.
-                if consumes_entire_leaf || consumes_entire_pattern {
-                    // get a new ID for the forcoming leaf node.
-                    let id = Identifier::from(next_id.fetch_add(1, Ordering::Relaxed));
-                    let left = (id, pattern);
-                    let right = (data.id(), data.bytes().clone());
-                    *self = Self::build_full_match(left, right);
-                    return id;
-                }
-                // Case 5: Two roads diverge in a Yellow Wood
-                // The match is complete for neither. Here, we create an internal node with
-                // two children: 1 for the leaf's remaining bytes and one for the pattern's
-                // remaining bytes.
-                // get a new ID for the forcoming leaf node.
-                let id = Identifier::from(next_id.fetch_add(1, Ordering::Relaxed));
-                let mut pattern_copy = pattern.clone();
-                let pattern_leftover = pattern_copy.split_off(similarity);
-                let leaf_leftover = data.bytes().clone().split_off(similarity);
-                let pattern_leaf = DLBNode::Leaf(LeafData::new(id, pattern_leftover));
-                let existing_leaf = DLBNode::Leaf(LeafData::new(data.id(), leaf_leftover));
-                let children = vector![pattern_leaf, existing_leaf];
-                let internal_data = InternalData::new(pattern_copy, None, children);
-                let internal_node = DLBNode::Internal(internal_data);
-                *self = internal_node;
-                return id;
-            }
-            DLBNode::Internal(data) => {
-                // Consume any of the characters in pattern which match on
-                // data.bytes().
-                let similarity = data.similar_bytes(pattern.clone());
-                let consumes_pattern_exactly = similarity == pattern.len();
-                let consumes_bytestring_exactly = similarity == data.bytes().len();
-                let no_match = similarity == 0;
-
-                match (
-                    no_match,
-                    consumes_pattern_exactly,
-                    consumes_bytestring_exactly,
-                ) {
-                    // Case 1: Exact match.
-                    // In this case, we just need to check the IsComplete field
-                    // and perhaps update it.
-                    (false, true, true) => {
-                        if let Some(id) = data.maybe_id() {
-                            return id;
-                        } else {
-                            let id = Identifier::from(next_id.fetch_add(1, Ordering::Relaxed));
-                            data.set_maybe_id(Some(id));
-                            return id;
-                        }
-                    }
-                    // Case 2: Similarity == pattern.len() && Similarity < bytestring.len()
-                    // Make a new internal node for the overlapping pattern. This internal
-                    // node has `IsComplete` set to true.
-                    // Take the leftover bytes from the bytestring and make an internal
-                    // node for that. That second internal node gets the children of this node.
-                    (false, true, false) => {
-                        let mut internal = data.bytes().clone();
-                        let internal_leftovers = internal.split_off(similarity);
+    /// `label_str` is the label as a `str`. It is always `Some` in
+    /// `KeyMode::Utf8`, but otherwise a label may begin or end
+    /// partway through a character.
+    pub fn label_str(&self) -> Option<&'a str> {
+        std::str::from_utf8(self.label()).ok()
+    }
 
-                        let second_layer_data = InternalData::new(
-                            internal_leftovers,
-                            data.maybe_id(),
-                            data.children().clone(),
-                        );
-                        let id = Identifier::from(next_id.fetch_add(1, Ordering::Relaxed));
-                        let second_layer = DLBNode::Internal(second_layer_data);
-                        let children = vector![second_layer];
-                        let internal_data = InternalData::new(pattern, Some(id), children);
-                        *self = DLBNode::Internal(internal_data);
-                        return id;
-                    }
-                    // Case 3: Similarity < pattern.len() && similarity == bytestring.len
-                    // Strip pattern of the similar bytes and recurse.
-                    (false, false, true) => {
-                        let remaining = pattern.split_off(similarity);
-                        let (best_index, _count) = data.find_best_child(remaining.clone());
-                        match best_index {
-                            Some(idx) => {
-                                return data.insert_at_index(idx, remaining, next_id);
-                            }
-                            None => {
-                                // Make a new leaf and add it as a child.
-                                let id = Identifier::from(next_id.fetch_add(1, Ordering::Relaxed));
-                                let new_leaf_data = LeafData::new(id, remaining);
-                                let new_leaf = DLBNode::Leaf(new_leaf_data);
-                                data.add_child(new_leaf);
-                                return id;
-                            }
-                        }
-                    }
-                    // Case 4: Similarity < pattern.len() && similarity < bytestring.len()
-                    // Make a new internal node for the overlapping pattern
-                    // Take the leftover bytes from the bytestring, and make an internal
-                    // node for that. That second internal node gets the childrens of this node
-                    // Make a new leaf for the leftover bytes from pattern
-                    (false, false, false) => {
-                        let mut internal = data.bytes().clone();
-                        let pattern_leftovers = pattern.split_off(similarity);
-                        let internal_leftovers = internal.split_off(similarity);
+    /// `id` is the identifier of the key ending at this node, if any.
+    pub fn id(&self) -> Option<S> {
+        self.dlb.record(self.handle).id()
+    }
 
-                        let second_layer_data = InternalData::new(
-                            internal_leftovers,
-                            data.maybe_id(),
-                            data.children().clone(),
-                        );
-                        let id = Identifier::from(next_id.fetch_add(1, Ordering::Relaxed));
-                        let second_layer = DLBNode::Internal(second_layer_data);
-                        let new_leaf_data = LeafData::new(id, pattern_leftovers);
-                        let new_leaf = DLBNode::Leaf(new_leaf_data);
-                        let children = vector![second_layer, new_leaf];
-                        let internal_data = InternalData::new(pattern, None, children);
-                        *self = DLBNode::Internal(internal_data);
-                        return id;
-                    }
-                    // Case 5: No match.
-                    (true, _, _) => unreachable!(), // Cannot reach this node without a match
-                }
-            }
-        }
+    /// `count_keys` is the number of keys ending at
+    /// this node or below it. It takes constant time.
+    pub fn count_keys(&self) -> usize {
+        self.dlb.keys_below(self.handle) as usize
     }
 
-    pub fn get(&self, pattern: &[u8]) -> Option<Identifier> {
-        println!("Checking node…");
-        match self {
-            DLBNode::Leaf(data) => {
-                println!("I am a leaf.");
-                // Check if the list matches the rest of the elements:
-                if data.bytes().as_slice() == pattern {
-                    return Some(data.id());
-                }
-            }
-            DLBNode::Internal(data) => {
-                println!("I am internal.");
+    pub fn is_leaf(&self) -> bool {
+        self.dlb.record(self.handle).is_leaf()
+    }
 
-                println!("My callee pattern is {}", from_utf8(pattern).unwrap());
-                let similarity = data.bytes().count_shared_prefix(pattern);
-                let consumes_pattern_exactly = similarity == pattern.len();
-                let consumes_bytestring_exactly = similarity == data.bytes().len();
-                let no_match = similarity == 0;
+    /// `children` visits this node's children in order of their first byte.
+    pub fn children(&self) -> Children<'a, S> {
+        Children::new(self.dlb, self.dlb.record(self.handle).first_child())
+    }
+}
 
-                match (
-                    no_match,
-                    consumes_pattern_exactly,
-                    consumes_bytestring_exactly,
-                ) {
-                    // Case 1: No match
-                    (true, _, _) => return None,
-                    // Case 2: Exact match.
-                    // In this case, we just need to check the IsComplete field
-                    // and perhaps update it.
-                    (false, true, true) => return data.maybe_id(),
-                    // Case 3: Matches pattern, bytes have leftover
-                    (false, true, false) => return None,
-                    // Case 4: Matches bytes, pattern has leftover
-                    (false, false, true) => {
-                        // Else, cut off the prefix from the pattern,
-                        // and iterate over the children, ORing the results together.
-                        let match_len = data.bytes().len();
-                        let suffix = &pattern[match_len..];
-                        for child in data.children() {
-                            if let Some(result) = child.get(&suffix) {
-                                return Some(result);
-                            }
-                        }
-                        return None;
-                    }
-                    (false, false, false) => return None,
-                }
-            }
-        }
-        None
+/// `Display` writes the node's label as text, or with `{:#}` draws
+/// the subtree below it. Wrap the node in `Hex` for binary labels.
+impl<'a, S: Symbol> fmt::Display for DLBNode<'a, S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_node(f, *self, Style::Text)
     }
+}
 
-    pub fn contains(&self, pattern: &[u8]) -> bool {
-        match self.get(pattern) {
-            Some(_) => true,
-            None => false,
+impl<'a, S: Symbol> fmt::Debug for DLBNode<'a, S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut node = f.debug_struct("DLBNode");
+        node.field("label", &Escaped(self.label()));
+        if let Some(id) = self.id() {
+            node.field("id", &id);
+        }
+        if !self.is_leaf() {
+            node.field("children", &DebugChildren(self.children()));
         }
+        node.finish()
     }
+}
 
-    pub fn size_of(&self) -> usize {
-        match self {
-            DLBNode::Leaf(_) => size_of::<Self>(),
-            DLBNode::Internal(data) => {
-                size_of::<Self>()
-                    + data
-                        .children()
-                        .iter()
-                        .map(|child| child.size_of())
-                        .fold(0, |x, acc| x + acc)
-            }
-        }
+/// `DebugChildren` prints a sibling list as a list of nodes.
+pub(crate) struct DebugChildren<'a, S>(pub Children<'a, S>);
+
+impl<'a, S: Symbol> fmt::Debug for DebugChildren<'a, S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let children = Children::new(self.0.dlb, self.0.next);
+        f.debug_list().entries(children).finish()
     }
+}
 
-    pub fn resolve(&self, id: Identifier, thus_far: CharList) -> Option<String> {
-        match self {
-            DLBNode::Leaf(data) => {
-                if data.id() == id {
-                    let result = thus_far.append(data.bytes().clone());
-                    return Some(result.to_string());
-                }
-                return None;
-            }
-            DLBNode::Internal(data) => {
-                if let Some(internal_id) = data.maybe_id() {
-                    if internal_id == id {
-                        let result = thus_far.append(data.bytes().clone());
-                        return Some(result.to_string());
-                    }
-                }
+/// `Children` follows a chain of sibling links through the arena.
+pub struct Children<'a, S> {
+    dlb: &'a DLB<S>,
+    next: NodeHandle,
+}
 
-                let next_string = thus_far.append(data.bytes().clone());
-                let res = data
-                    .children()
-                    .iter()
-                    .map(|child| child.resolve(id, next_string.clone()))
-                    .filter(|x| x.is_some())
-                    .next();
-                match res {
-                    None => None,
-                    Some(x) => Some(x.unwrap()),
-                }
-            }
-        }
+impl<'a, S> Children<'a, S> {
+    pub(crate) fn new(dlb: &'a DLB<S>, first: NodeHandle) -> Self {
+        Self { dlb, next: first }
     }
 }
 
-impl Matchable for DLBNode {
-    fn similar_bytes(&self, pattern: CharList) -> usize {
-        match self {
-            DLBNode::Leaf(data) => data.similar_bytes(pattern),
-            DLBNode::Internal(data) => data.similar_bytes(pattern),
+impl<'a, S: Symbol> Iterator for Children<'a, S> {
+    type Item = DLBNode<'a, S>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next.is_nil() {
+            return None;
         }
+        let node = DLBNode::new(self.dlb, self.next);
+        self.next = self.dlb.record(self.next).next_sibling();
+        Some(node)
     }
 }
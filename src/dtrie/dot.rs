@@ -0,0 +1,60 @@
+use crate::dtrie::dlb_node::DLBNode;
+use crate::dtrie::{Symbol, DLB};
+use std::io::{self, Write};
+
+impl<S: Symbol> DLB<S> {
+    /// `write_dot` renders the trie in Graphviz DOT format. Each node is
+    /// labelled with the bytes on its incoming edge, and nodes which
+    /// complete a key are drawn with a double border and their identifier.
+    pub fn write_dot<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "digraph DLB {{")?;
+        writeln!(out, "    node [shape=box];")?;
+        match self.empty_id() {
+            Some(id) => writeln!(out, "    n0 [label=\"ε\\n#{}\", peripheries=2];", id)?,
+            None => writeln!(out, "    n0 [label=\"ε\"];")?,
+        }
+        let mut counter = 0;
+        for node in self.top_level() {
+            write_node(out, node, 0, &mut counter)?;
+        }
+        writeln!(out, "}}")
+    }
+}
+
+fn write_node<W: Write, S: Symbol>(
+    out: &mut W,
+    node: DLBNode<S>,
+    parent: usize,
+    counter: &mut usize,
+) -> io::Result<()> {
+    *counter += 1;
+    let name = *counter;
+    let label = escape(node.label());
+    match node.id() {
+        Some(id) => writeln!(
+            out,
+            "    n{} [label=\"{}\\n#{}\", peripheries=2];",
+            name, label, id
+        )?,
+        None => writeln!(out, "    n{} [label=\"{}\"];", name, label)?,
+    }
+    writeln!(out, "    n{} -> n{};", parent, name)?;
+    for child in node.children() {
+        write_node(out, child, name, counter)?;
+    }
+    Ok(())
+}
+
+/// `escape` makes a label safe to embed in a quoted DOT string.
+fn escape(bytes: &[u8]) -> String {
+    let mut res = String::new();
+    for c in String::from_utf8_lossy(bytes).chars() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            _ => res.push(c),
+        }
+    }
+    res
+}
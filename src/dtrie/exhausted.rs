@@ -0,0 +1,15 @@
+use std::error::Error;
+use std::fmt;
+
+/// `Exhausted` is returned when a trie has handed out every
+/// identifier its symbol type can represent.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct Exhausted;
+
+impl fmt::Display for Exhausted {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "no identifiers are left for new keys")
+    }
+}
+
+impl Error for Exhausted {}
@@ -0,0 +1,36 @@
+/// `IdPolicy` decides how a trie hands out identifiers,
+/// and what happens to them when keys are removed.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub enum IdPolicy {
+    /// Identifiers count up from zero and are never reused.
+    /// Removing a key leaves a gap behind.
+    #[default]
+    Sequential,
+    /// Identifiers always cover `0..len()` exactly. Removing a key
+    /// moves the newest key into the gap, just like `Vec::swap_remove`.
+    Dense,
+    /// Identifiers of removed keys are reused. The top quarter of each
+    /// identifier's bits count how many times it has been reused, so an
+    /// identifier kept after its key was removed never matches a new key.
+    /// Once that count runs out, the identifier is retired for good.
+    Recycled,
+}
+
+impl IdPolicy {
+    pub(crate) fn to_raw(self) -> u8 {
+        match self {
+            IdPolicy::Sequential => 0,
+            IdPolicy::Dense => 1,
+            IdPolicy::Recycled => 2,
+        }
+    }
+
+    pub(crate) fn from_raw(raw: u8) -> Option<Self> {
+        match raw {
+            0 => Some(IdPolicy::Sequential),
+            1 => Some(IdPolicy::Dense),
+            2 => Some(IdPolicy::Recycled),
+            _ => None,
+        }
+    }
+}
@@ -1,16 +1,37 @@
-use std::fmt;
+use crate::dtrie::symbol::{Sym64, Symbol};
+use crate::encoding::{invalid_data, read_u64, read_u8, write_u64, write_u8};
+use std::convert::TryFrom;
+use std::io::{self, Read, Write};
 
-#[derive(PartialEq, Eq, Copy, Clone, Debug, Hash, Default, Ord, PartialOrd)]
-pub struct Identifier(u64);
+/// `Identifier` is the symbol type used when none is chosen.
+/// Identifiers are never zero, which lets `Option<Identifier>`
+/// fit in the same eight bytes as the identifier itself.
+pub type Identifier = Sym64;
 
-impl fmt::Display for Identifier {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.0)
+/// `write_maybe_id` writes a presence flag followed by the identifier.
+/// Symbols of every width are written as eight bytes, counting from one.
+pub(crate) fn write_maybe_id<W: Write, S: Symbol>(out: &mut W, id: Option<S>) -> io::Result<()> {
+    match id {
+        Some(id) => {
+            write_u8(out, 1)?;
+            write_u64(out, id.to_usize() as u64 + 1)
+        }
+        None => write_u8(out, 0),
     }
 }
 
-impl From<u64> for Identifier {
-    fn from(other: u64) -> Identifier {
-        Identifier(other)
+/// `read_maybe_id` reads an identifier written by `write_maybe_id`.
+pub(crate) fn read_maybe_id<R: Read, S: Symbol>(input: &mut R) -> io::Result<Option<S>> {
+    match read_u8(input)? {
+        0 => Ok(None),
+        1 => match read_u64(input)? {
+            0 => Err(invalid_data("zero identifier")),
+            value => usize::try_from(value - 1)
+                .ok()
+                .and_then(S::from_usize)
+                .map(Some)
+                .ok_or_else(|| invalid_data("identifier out of range")),
+        },
+        _ => Err(invalid_data("malformed identifier")),
     }
 }
@@ -1,3 +0,0 @@
-use crate::dtrie::Identifier;
-
-pub type IsComplete = Option<Identifier>;
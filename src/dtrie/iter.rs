@@ -0,0 +1,48 @@
+use crate::dtrie::dlb_node::DLBNode;
+use crate::dtrie::Symbol;
+
+/// `Iter` walks a trie depth-first, yielding every
+/// key together with its identifier.
+pub struct Iter<'a, S> {
+    // The empty string has no node, so it is yielded first.
+    empty: Option<S>,
+    // Each entry holds a node and the bytes preceding its label.
+    stack: Vec<(DLBNode<'a, S>, Vec<u8>)>,
+}
+
+impl<'a, S> Iter<'a, S> {
+    pub(crate) fn new(empty: Option<S>, stack: Vec<(DLBNode<'a, S>, Vec<u8>)>) -> Self {
+        let mut stack = stack;
+        // Nodes are popped from the back, so reverse them to keep their order.
+        stack.reverse();
+        Self { empty, stack }
+    }
+}
+
+impl<'a, S: Symbol> Iterator for Iter<'a, S> {
+    type Item = (String, S);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(id) = self.empty.take() {
+            return Some((String::new(), id));
+        }
+        while let Some((node, mut path)) = self.stack.pop() {
+            path.extend_from_slice(node.label());
+            let children: Vec<DLBNode<S>> = node.children().collect();
+            for child in children.into_iter().rev() {
+                self.stack.push((child, path.clone()));
+            }
+            if let Some(id) = node.id() {
+                return Some((into_string(path), id));
+            }
+        }
+        None
+    }
+}
+
+/// `into_string` never panics: a key which isn't valid UTF-8, as only
+/// one read from a damaged file could be, is decoded lossily.
+pub(crate) fn into_string(bytes: Vec<u8>) -> String {
+    String::from_utf8(bytes)
+        .unwrap_or_else(|err| String::from_utf8_lossy(err.as_bytes()).into_owned())
+}
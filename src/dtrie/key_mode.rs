@@ -0,0 +1,66 @@
+/// `KeyMode` decides where a trie may split a label in two.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub enum KeyMode {
+    /// Labels may be split between any two bytes, so a label can begin
+    /// or end partway through a character. Siblings start with distinct
+    /// bytes.
+    #[default]
+    Bytes,
+    /// Labels are only split between characters, so every label is valid
+    /// UTF-8 on its own. Siblings start with distinct characters, though
+    /// they may share a first byte, as "é" and "è" do.
+    Utf8,
+}
+
+impl KeyMode {
+    pub(crate) fn to_raw(self) -> u8 {
+        match self {
+            KeyMode::Bytes => 0,
+            KeyMode::Utf8 => 1,
+        }
+    }
+
+    pub(crate) fn from_raw(raw: u8) -> Option<Self> {
+        match raw {
+            0 => Some(KeyMode::Bytes),
+            1 => Some(KeyMode::Utf8),
+            _ => None,
+        }
+    }
+
+    /// `lead` is the unit siblings are told apart by: the first byte of
+    /// `bytes`, or in `Utf8` mode the first character.
+    pub(crate) fn lead(self, bytes: &[u8]) -> &[u8] {
+        let len = match self {
+            KeyMode::Bytes => 1,
+            KeyMode::Utf8 => char_width(bytes[0]),
+        };
+        &bytes[..len.min(bytes.len())]
+    }
+
+    /// `boundary` moves `at` back to the nearest place `bytes`
+    /// may be split.
+    pub(crate) fn boundary(self, bytes: &[u8], mut at: usize) -> usize {
+        if self == KeyMode::Utf8 {
+            while at > 0 && at < bytes.len() && is_continuation(bytes[at]) {
+                at -= 1;
+            }
+        }
+        at
+    }
+}
+
+/// `char_width` is the length of the UTF-8 sequence which `lead` starts.
+/// Stray continuation bytes count as one byte long.
+fn char_width(lead: u8) -> usize {
+    match lead.leading_ones() {
+        2 => 2,
+        3 => 3,
+        4 => 4,
+        _ => 1,
+    }
+}
+
+fn is_continuation(byte: u8) -> bool {
+    byte & 0xc0 == 0x80
+}
@@ -0,0 +1,141 @@
+use std::borrow::Cow;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::{is_nfc, UnicodeNormalization};
+
+/// `KeyNormalizer` maps each key to the form the trie stores it in, so
+/// keys which normalise alike share an identifier. A `DLB` applies it
+/// whenever a key is interned, looked up or removed.
+///
+/// Any `Fn(&str) -> String` is a `KeyNormalizer` too.
+pub trait KeyNormalizer: Send + Sync {
+    /// `normalize` returns `key` in normal form. It should borrow `key`
+    /// when it is already normal, and must be idempotent.
+    fn normalize<'a>(&self, key: &'a str) -> Cow<'a, str>;
+}
+
+impl<F> KeyNormalizer for F
+where
+    F: Fn(&str) -> String + Send + Sync,
+{
+    fn normalize<'a>(&self, key: &'a str) -> Cow<'a, str> {
+        Cow::Owned(self(key))
+    }
+}
+
+/// `Lowercase` maps every character to lower case.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub struct Lowercase;
+
+impl KeyNormalizer for Lowercase {
+    fn normalize<'a>(&self, key: &'a str) -> Cow<'a, str> {
+        if key.is_ascii() {
+            return ascii_lowercase(key);
+        }
+        if key.chars().flat_map(char::to_lowercase).eq(key.chars()) {
+            return Cow::Borrowed(key);
+        }
+        Cow::Owned(key.to_lowercase())
+    }
+}
+
+/// `AsciiFold` replaces accented letters and ligatures with their
+/// plain ASCII equivalents, so "Crème Brûlée" becomes "Creme Brulee".
+/// Case is kept, as are characters with no ASCII equivalent.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub struct AsciiFold;
+
+impl KeyNormalizer for AsciiFold {
+    fn normalize<'a>(&self, key: &'a str) -> Cow<'a, str> {
+        if key.is_ascii() {
+            return Cow::Borrowed(key);
+        }
+        let mut folded = String::with_capacity(key.len());
+        // Compatibility decomposition splits off the accents and
+        // spells out ligatures such as "ﬁ".
+        for c in key.nfkd().filter(|&c| !is_combining_mark(c)) {
+            match fold_letter(c) {
+                Some(spelling) => folded.push_str(spelling),
+                None => folded.push(c),
+            }
+        }
+        Cow::Owned(folded)
+    }
+}
+
+/// `fold_letter` spells out the letters which don't
+/// decompose into an ASCII letter and accents.
+fn fold_letter(c: char) -> Option<&'static str> {
+    let spelling = match c {
+        'ß' => "ss",
+        'ẞ' => "SS",
+        'æ' => "ae",
+        'Æ' => "AE",
+        'œ' => "oe",
+        'Œ' => "OE",
+        'ø' => "o",
+        'Ø' => "O",
+        'đ' | 'ð' => "d",
+        'Đ' | 'Ð' => "D",
+        'ł' => "l",
+        'Ł' => "L",
+        'þ' => "th",
+        'Þ' => "TH",
+        'ı' => "i",
+        _ => return None,
+    };
+    Some(spelling)
+}
+
+/// `Nfc` puts keys in Unicode Normalisation Form C, so a precomposed
+/// "é" matches an "e" followed by a combining acute accent.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub struct Nfc;
+
+impl KeyNormalizer for Nfc {
+    fn normalize<'a>(&self, key: &'a str) -> Cow<'a, str> {
+        if is_nfc(key) {
+            return Cow::Borrowed(key);
+        }
+        Cow::Owned(key.nfc().collect())
+    }
+}
+
+/// `NfkcCasefold` puts keys in Normalisation Form KC and folds their
+/// case, which is how Unicode suggests matching identifiers. It also
+/// merges compatibility variants, so "ｆｕｌｌ" matches "full" and "ﬁ"
+/// matches "fi". Case folding is approximated by lowercasing, plus the
+/// letters whose folded form differs from their lowercase form in common
+/// text: "ß" folds to "ss" and a final "ς" to "σ".
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub struct NfkcCasefold;
+
+impl KeyNormalizer for NfkcCasefold {
+    fn normalize<'a>(&self, key: &'a str) -> Cow<'a, str> {
+        if key.is_ascii() {
+            return ascii_lowercase(key);
+        }
+        let mut folded = String::with_capacity(key.len());
+        for c in key.nfkd() {
+            match c {
+                'ß' | 'ẞ' => folded.push_str("ss"),
+                'ς' => folded.push('σ'),
+                _ => folded.extend(c.to_lowercase()),
+            }
+        }
+        let folded: String = folded.nfkc().collect();
+        if folded == key {
+            return Cow::Borrowed(key);
+        }
+        Cow::Owned(folded)
+    }
+}
+
+/// `ascii_lowercase` lowercases an ASCII key, borrowing it if it has
+/// no capitals.
+fn ascii_lowercase(key: &str) -> Cow<'_, str> {
+    if key.bytes().any(|b| b.is_ascii_uppercase()) {
+        Cow::Owned(key.to_ascii_lowercase())
+    } else {
+        Cow::Borrowed(key)
+    }
+}
@@ -0,0 +1,142 @@
+/// Labels up to this many bytes are stored inside the node itself.
+pub const INLINE_CAPACITY: usize = 8;
+
+/// Set on `len` when the label lives in the label pool.
+const POOLED: u32 = 1 << 31;
+
+/// `Label` holds the bytes on the edge leading into a node. Short labels
+/// are stored inline. Longer ones live in the trie's label pool, and the
+/// label records their offset into it.
+#[derive(Clone, Copy)]
+pub struct Label {
+    len: u32,
+    payload: [u8; INLINE_CAPACITY],
+}
+
+impl Label {
+    /// `EMPTY` is the label left on a node whose slot has been freed.
+    pub const EMPTY: Label = Label {
+        len: 0,
+        payload: [0; INLINE_CAPACITY],
+    };
+
+    /// `new` makes a label, appending its bytes to `pool` if they don't fit inline.
+    pub fn new(bytes: &[u8], pool: &mut Vec<u8>) -> Self {
+        if bytes.len() <= INLINE_CAPACITY {
+            let mut payload = [0; INLINE_CAPACITY];
+            payload[..bytes.len()].copy_from_slice(bytes);
+            return Label {
+                len: bytes.len() as u32,
+                payload,
+            };
+        }
+        let start = pool.len();
+        assert!(start < u32::MAX as usize, "the label pool is full");
+        pool.extend_from_slice(bytes);
+        Self::pooled(start as u32, bytes.len() as u32)
+    }
+
+    fn pooled(start: u32, len: u32) -> Self {
+        let mut payload = [0; INLINE_CAPACITY];
+        payload[..4].copy_from_slice(&start.to_le_bytes());
+        Label {
+            len: len | POOLED,
+            payload,
+        }
+    }
+
+    pub fn is_pooled(&self) -> bool {
+        self.len & POOLED != 0
+    }
+
+    fn start(&self) -> usize {
+        let mut start = [0; 4];
+        start.copy_from_slice(&self.payload[..4]);
+        u32::from_le_bytes(start) as usize
+    }
+
+    /// `pooled_len` is the number of pool bytes the label takes up.
+    pub fn pooled_len(&self) -> usize {
+        if self.is_pooled() {
+            self.len()
+        } else {
+            0
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        (self.len & !POOLED) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn as_slice<'a>(&'a self, pool: &'a [u8]) -> &'a [u8] {
+        if self.is_pooled() {
+            &pool[self.start()..self.start() + self.len()]
+        } else {
+            &self.payload[..self.len()]
+        }
+    }
+
+    /// `split_at` divides the label in two without touching the pool.
+    pub fn split_at(&self, at: usize) -> (Label, Label) {
+        assert!(at <= self.len());
+        if self.is_pooled() {
+            let start = self.start() as u32;
+            let head = Self::pooled(start, at as u32);
+            let tail = Self::pooled(start + at as u32, (self.len() - at) as u32);
+            return (head, tail);
+        }
+        let mut head = [0; INLINE_CAPACITY];
+        let mut tail = [0; INLINE_CAPACITY];
+        head[..at].copy_from_slice(&self.payload[..at]);
+        tail[..self.len() - at].copy_from_slice(&self.payload[at..self.len()]);
+        let head = Label {
+            len: at as u32,
+            payload: head,
+        };
+        let tail = Label {
+            len: (self.len() - at) as u32,
+            payload: tail,
+        };
+        (head, tail)
+    }
+
+    /// `join` makes the label spelling `self` followed by `other`. Labels
+    /// which sit side by side in the pool, as the halves of a split
+    /// label do, are joined without copying.
+    pub fn join(&self, other: &Label, pool: &mut Vec<u8>) -> Label {
+        if self.is_pooled() && other.is_pooled() && self.start() + self.len() == other.start() {
+            return Self::pooled(self.start() as u32, (self.len() + other.len()) as u32);
+        }
+        let mut bytes = self.as_slice(pool).to_vec();
+        bytes.extend_from_slice(other.as_slice(pool));
+        Label::new(&bytes, pool)
+    }
+
+    /// `to_raw` encodes the label as it is stored in the node record.
+    pub(crate) fn to_raw(self) -> (u32, [u8; INLINE_CAPACITY]) {
+        (self.len, self.payload)
+    }
+
+    /// `from_raw` rebuilds a label, checking that it fits inside a pool of `pool_len` bytes.
+    pub(crate) fn from_raw(
+        len: u32,
+        payload: [u8; INLINE_CAPACITY],
+        pool_len: usize,
+    ) -> Option<Self> {
+        let label = Label { len, payload };
+        let fits = if label.is_pooled() {
+            label.start() + label.len() <= pool_len
+        } else {
+            label.len() <= INLINE_CAPACITY
+        };
+        if fits {
+            Some(label)
+        } else {
+            None
+        }
+    }
+}
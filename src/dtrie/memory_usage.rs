@@ -0,0 +1,58 @@
+use std::fmt;
+use std::ops::{Add, AddAssign};
+
+/// `MemoryUsage` is a breakdown of the heap memory owned by a trie.
+/// Every byte is attributed to exactly one category, so `total()`
+/// is the number of heap bytes the structure keeps alive.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub struct MemoryUsage {
+    /// Bytes occupied by the node structs themselves.
+    pub nodes: usize,
+    /// Bytes of key material stored in node labels.
+    pub labels: usize,
+    /// Bookkeeping owned by the child containers, such as
+    /// reference counts and chunk headers.
+    pub child_containers: usize,
+    /// Bytes which have been allocated but hold nothing.
+    pub wasted_capacity: usize,
+}
+
+impl MemoryUsage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `total` is the sum of every category.
+    pub fn total(&self) -> usize {
+        self.nodes + self.labels + self.child_containers + self.wasted_capacity
+    }
+}
+
+impl Add for MemoryUsage {
+    type Output = MemoryUsage;
+
+    fn add(self, other: MemoryUsage) -> MemoryUsage {
+        MemoryUsage {
+            nodes: self.nodes + other.nodes,
+            labels: self.labels + other.labels,
+            child_containers: self.child_containers + other.child_containers,
+            wasted_capacity: self.wasted_capacity + other.wasted_capacity,
+        }
+    }
+}
+
+impl AddAssign for MemoryUsage {
+    fn add_assign(&mut self, other: MemoryUsage) {
+        *self = *self + other;
+    }
+}
+
+impl fmt::Display for MemoryUsage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Node structs:\t\t{} bytes", self.nodes)?;
+        writeln!(f, "Label bytes:\t\t{} bytes", self.labels)?;
+        writeln!(f, "Child containers:\t{} bytes", self.child_containers)?;
+        writeln!(f, "Wasted capacity:\t{} bytes", self.wasted_capacity)?;
+        write!(f, "Total:\t\t\t{} bytes", self.total())
+    }
+}
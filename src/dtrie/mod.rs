@@ -1,14 +1,45 @@
 #![allow(dead_code)]
 
+pub use build_error::BuildError;
+pub use conflict::Conflict;
+pub use display::{Escaped, Hex};
 pub use dlb::DLB;
+pub use exhausted::Exhausted;
+pub use id_policy::IdPolicy;
 pub use identifier::Identifier;
-pub use matchable::Matchable;
+pub use iter::Iter;
+pub use key_mode::KeyMode;
+pub use key_normalizer::{AsciiFold, KeyNormalizer, Lowercase, Nfc, NfkcCasefold};
+pub use memory_usage::MemoryUsage;
+pub use remap::Remap;
+pub use removed::Removed;
+pub use symbol::{Sym16, Sym32, Sym64, Symbol};
 
+mod best_first;
+mod build_error;
 mod char_list;
+mod completion;
+mod conflict;
+mod counting;
+mod display;
 mod dlb;
 mod dlb_node;
-mod identifier;
-mod internal_data;
-mod is_complete;
-mod leaf_data;
-mod matchable;
+mod dot;
+mod exhausted;
+mod id_policy;
+pub(crate) mod identifier;
+pub(crate) mod iter;
+mod key_mode;
+mod key_normalizer;
+mod label;
+mod memory_usage;
+pub(crate) mod node_handle;
+mod node_record;
+mod prefix_ops;
+mod rank;
+mod remap;
+mod removed;
+mod scored;
+mod set_ops;
+mod symbol;
+mod tally;
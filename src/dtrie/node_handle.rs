@@ -0,0 +1,29 @@
+/// `NodeHandle` addresses a node by its index into the trie's node arena.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Hash)]
+pub struct NodeHandle(u32);
+
+impl NodeHandle {
+    /// `NIL` marks a missing child or sibling.
+    pub const NIL: NodeHandle = NodeHandle(u32::MAX);
+
+    pub fn new(index: usize) -> Self {
+        assert!(index < u32::MAX as usize, "the node arena is full");
+        NodeHandle(index as u32)
+    }
+
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+
+    pub fn is_nil(self) -> bool {
+        self == Self::NIL
+    }
+
+    pub(crate) fn from_raw(raw: u32) -> Self {
+        NodeHandle(raw)
+    }
+
+    pub(crate) fn to_raw(self) -> u32 {
+        self.0
+    }
+}
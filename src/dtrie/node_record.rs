@@ -0,0 +1,75 @@
+use crate::dtrie::label::Label;
+use crate::dtrie::node_handle::NodeHandle;
+
+/// `NodeRecord` is the storage for a single node in the trie's arena.
+/// Rather than owning its children, a node links to its first child,
+/// and each child links to the next sibling. Siblings are kept sorted
+/// by their first byte, and no two siblings share a first byte.
+/// Each node also links back to its parent, so keys can be
+/// rebuilt from the bottom up.
+#[derive(Clone, Copy)]
+pub struct NodeRecord<S> {
+    label: Label,
+    id: Option<S>,
+    first_child: NodeHandle,
+    next_sibling: NodeHandle,
+    parent: NodeHandle,
+}
+
+impl<S: Copy> NodeRecord<S> {
+    pub fn new(label: Label, id: Option<S>) -> Self {
+        Self {
+            label,
+            id,
+            first_child: NodeHandle::NIL,
+            next_sibling: NodeHandle::NIL,
+            parent: NodeHandle::NIL,
+        }
+    }
+
+    pub fn label(&self) -> &Label {
+        &self.label
+    }
+
+    pub fn set_label(&mut self, label: Label) {
+        self.label = label;
+    }
+
+    /// `id` is the identifier of the key ending at this node, if any.
+    pub fn id(&self) -> Option<S> {
+        self.id
+    }
+
+    pub fn set_id(&mut self, id: Option<S>) {
+        self.id = id;
+    }
+
+    pub fn first_child(&self) -> NodeHandle {
+        self.first_child
+    }
+
+    pub fn set_first_child(&mut self, child: NodeHandle) {
+        self.first_child = child;
+    }
+
+    pub fn next_sibling(&self) -> NodeHandle {
+        self.next_sibling
+    }
+
+    pub fn set_next_sibling(&mut self, sibling: NodeHandle) {
+        self.next_sibling = sibling;
+    }
+
+    /// `parent` is `NodeHandle::NIL` for top-level nodes.
+    pub fn parent(&self) -> NodeHandle {
+        self.parent
+    }
+
+    pub fn set_parent(&mut self, parent: NodeHandle) {
+        self.parent = parent;
+    }
+
+    pub fn is_leaf(&self) -> bool {
+        self.first_child.is_nil()
+    }
+}
@@ -0,0 +1,242 @@
+use crate::dtrie::dlb_node::{Children, DLBNode};
+use crate::dtrie::iter::into_string;
+use crate::dtrie::node_handle::NodeHandle;
+use crate::dtrie::{Conflict, Symbol, DLB};
+
+/// `Cursor` is a place in the trie, `depth` bytes into the label of the
+/// node at `handle`. A nil handle stands for the root.
+#[derive(Clone, Copy)]
+struct Cursor {
+    handle: NodeHandle,
+    depth: usize,
+}
+
+impl Cursor {
+    const ROOT: Cursor = Cursor {
+        handle: NodeHandle::NIL,
+        depth: 0,
+    };
+}
+
+impl<S: Symbol> DLB<S> {
+    /// `count_prefix` is the number of keys which start with `prefix`,
+    /// including `prefix` itself. Each node knows how many keys lie
+    /// below it, so this takes time proportional to the prefix's depth.
+    pub fn count_prefix(&self, prefix: String) -> usize {
+        let prefix = self.normalize(&prefix);
+        if prefix.is_empty() {
+            return self.len();
+        }
+        self.descend(prefix.as_bytes())
+            .map_or(0, |(handle, _)| self.keys_below(handle) as usize)
+    }
+
+    /// `longest_prefix` is the longest key which `s` starts with, along
+    /// with its identifier, or `None` if no key is a prefix of `s`. The
+    /// key is given as it is stored, in normal form.
+    pub fn longest_prefix(&self, s: String) -> Option<(String, S)> {
+        let key = self.normalize(&s);
+        let key = key.as_bytes();
+        let mut longest = self.empty_id().map(|id| (0, id));
+        let mut depth = 0;
+        let mut children = self.top_level();
+        while depth < key.len() {
+            let rest = &key[depth..];
+            let child = match children.find(|child| rest.starts_with(child.label())) {
+                Some(child) => child,
+                None => break,
+            };
+            depth += child.label().len();
+            if let Some(id) = child.id() {
+                longest = Some((depth, id));
+            }
+            children = child.children();
+        }
+        longest.map(|(len, id)| (into_string(key[..len].to_vec()), id))
+    }
+
+    /// `remove_prefix` removes every key which starts with `prefix`,
+    /// including `prefix` itself, and returns how many were removed. The
+    /// subtree holding them is cut off and freed in one go. As with
+    /// `remove`, their identifiers are released according to the policy.
+    pub fn remove_prefix(&mut self, prefix: String) -> usize {
+        let prefix = self.normalize(&prefix).into_owned();
+        if !prefix.is_empty() {
+            return match self.descend(prefix.as_bytes()) {
+                Some((handle, _)) => self.remove_subtree(handle),
+                None => 0,
+            };
+        }
+        let top_level: Vec<_> = self.top_level().map(|node| node.handle()).collect();
+        let mut removed = self.remove(String::new()).map_or(0, |_| 1);
+        for handle in top_level {
+            removed += self.remove_subtree(handle);
+        }
+        removed
+    }
+
+    /// `rename_prefix` replaces the prefix `old` with `new` in every key
+    /// which starts with `old`, returning how many keys were renamed. The
+    /// subtree below `old` is moved in one piece, and only merged with
+    /// the nodes already below `new` where the two overlap. The keys keep
+    /// their identifiers, counts and scores. A spelling saved by a
+    /// normaliser is kept too, with `new` spelt as given in place of
+    /// however the key spelt `old`.
+    ///
+    /// If a renamed key would collide with a key which isn't itself being
+    /// renamed, the trie is left unchanged and the colliding key returned.
+    pub fn rename_prefix(&mut self, old: String, new: String) -> Result<usize, Conflict> {
+        let old_key = self.normalize(&old).into_owned().into_bytes();
+        let new_key = self.normalize(&new).into_owned().into_bytes();
+        if old_key == new_key {
+            return Ok(self.count_prefix(old));
+        }
+        if let Some(key) = self.collision(&old_key, &new_key) {
+            return Err(Conflict {
+                key: into_string(key),
+            });
+        }
+        let handle = if old_key.is_empty() {
+            if self.is_empty() {
+                return Ok(0);
+            }
+            self.lift_top_level()
+        } else {
+            match self.node_ending_at(&old_key) {
+                Some(handle) => {
+                    self.detach_subtree(handle);
+                    handle
+                }
+                None => return Ok(0),
+            }
+        };
+        let mut moved = Vec::new();
+        let mut stack = vec![DLBNode::new(self, handle)];
+        while let Some(node) = stack.pop() {
+            moved.extend(node.id());
+            stack.extend(node.children());
+        }
+        for id in moved.iter() {
+            let spelling = self.spelling(*id).map(|spelling| {
+                let key = self.normalize(spelling).into_owned();
+                self.respelt(spelling, &key, &old_key, &new)
+            });
+            self.respell(*id, spelling.flatten());
+        }
+        self.graft(&new_key, handle);
+        Ok(moved.len())
+    }
+
+    /// `respelt` is how `spelling`, which normalises to `key`, is spelt
+    /// once the prefix `old` of the key is renamed to `new`. It is `None`
+    /// if no prefix of the spelling normalises to `old`, or if the new
+    /// spelling doesn't normalise to the renamed key, or needn't be kept.
+    fn respelt(&self, spelling: &str, key: &str, old: &[u8], new: &str) -> Option<Box<str>> {
+        let at = (0..=spelling.len())
+            .filter(|at| spelling.is_char_boundary(*at))
+            .find(|at| self.normalize(&spelling[..*at]).as_bytes() == old)?;
+        let respelt = format!("{}{}", new, &spelling[at..]);
+        let renamed = format!("{}{}", self.normalize(new), &key[old.len()..]);
+        if respelt == renamed || self.normalize(&respelt) != renamed.as_str() {
+            return None;
+        }
+        Some(respelt.into_boxed_str())
+    }
+
+    /// `collision` finds a key which would collide with a renamed key if
+    /// `old` were renamed to `new`, but isn't itself being renamed. It
+    /// walks the keys below `old` and those below `new` side by side, so
+    /// it only visits the parts of the two subtrees which overlap.
+    fn collision(&self, old: &[u8], new: &[u8]) -> Option<Vec<u8>> {
+        let renamed = |suffix: &[u8]| {
+            new.starts_with(old) || (old.starts_with(new) && suffix.starts_with(&old[new.len()..]))
+        };
+        let mut stack = vec![(self.cursor(old)?, self.cursor(new)?, Vec::new())];
+        while let Some((moving, staying, suffix)) = stack.pop() {
+            if renamed(&suffix) {
+                continue;
+            }
+            if self.ends_key(moving) && self.ends_key(staying) {
+                let mut key = new.to_vec();
+                key.extend_from_slice(&suffix);
+                return Some(key);
+            }
+            for byte in self.next_bytes(moving) {
+                if let Some(next) = self.step(staying, byte) {
+                    let mut suffix = suffix.clone();
+                    suffix.push(byte);
+                    let moving = self
+                        .step(moving, byte)
+                        .expect("the byte follows the cursor");
+                    stack.push((moving, next, suffix));
+                }
+            }
+        }
+        None
+    }
+
+    /// `cursor` is the place in the trie where `prefix` ends,
+    /// or `None` if no key starts with it.
+    fn cursor(&self, prefix: &[u8]) -> Option<Cursor> {
+        if prefix.is_empty() {
+            return Some(Cursor::ROOT);
+        }
+        let (handle, path) = self.descend(prefix)?;
+        Some(Cursor {
+            handle,
+            depth: prefix.len() - path.len(),
+        })
+    }
+
+    /// `ends_key` reports whether a key ends at `cursor`.
+    fn ends_key(&self, cursor: Cursor) -> bool {
+        if cursor.handle.is_nil() {
+            return self.empty_id().is_some();
+        }
+        cursor.depth == self.label_of(cursor.handle).len()
+            && self.record(cursor.handle).id().is_some()
+    }
+
+    /// `next_bytes` lists the bytes which may follow `cursor`.
+    fn next_bytes(&self, cursor: Cursor) -> Vec<u8> {
+        if !cursor.handle.is_nil() {
+            let label = self.label_of(cursor.handle);
+            if cursor.depth < label.len() {
+                return vec![label[cursor.depth]];
+            }
+        }
+        self.children_at(cursor)
+            .map(|child| child.label()[0])
+            .collect()
+    }
+
+    /// `step` moves `cursor` on by `byte`, if any key continues that way.
+    fn step(&self, cursor: Cursor, byte: u8) -> Option<Cursor> {
+        if !cursor.handle.is_nil() {
+            let label = self.label_of(cursor.handle);
+            if cursor.depth < label.len() {
+                return Some(Cursor {
+                    depth: cursor.depth + 1,
+                    ..cursor
+                })
+                .filter(|_| label[cursor.depth] == byte);
+            }
+        }
+        let child = self
+            .children_at(cursor)
+            .find(|child| child.label()[0] == byte)?;
+        Some(Cursor {
+            handle: child.handle(),
+            depth: 1,
+        })
+    }
+
+    /// `children_at` visits the children of the node `cursor` is at the end of.
+    fn children_at(&self, cursor: Cursor) -> Children<'_, S> {
+        if cursor.handle.is_nil() {
+            self.top_level()
+        } else {
+            DLBNode::new(self, cursor.handle).children()
+        }
+    }
+}
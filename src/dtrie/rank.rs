@@ -0,0 +1,77 @@
+use crate::dtrie::dlb::count_shared_prefix;
+use crate::dtrie::iter::into_string;
+use crate::dtrie::{Symbol, DLB};
+use std::cmp::Ordering;
+
+impl<S: Symbol> DLB<S> {
+    /// `rank` is the number of keys in the trie which sort before `s`,
+    /// whether or not `s` is a key itself. Each node knows how many keys
+    /// lie below it, so whole subtrees are skipped at once and this takes
+    /// time proportional to the depth of `s`, times the number of siblings
+    /// passed on the way.
+    pub fn rank(&self, s: String) -> usize {
+        let key = self.normalize(&s);
+        let mut rest = key.as_bytes();
+        if rest.is_empty() {
+            return 0;
+        }
+        let mut rank = self.empty_id().map_or(0, |_| 1);
+        let mut children = self.top_level();
+        'descend: loop {
+            for child in children {
+                let label = child.label();
+                let shared = count_shared_prefix(label, rest);
+                if shared == rest.len() {
+                    // Every key from here on starts with `s`.
+                    return rank;
+                }
+                if shared == label.len() {
+                    // The child's key is a proper prefix of `s`.
+                    rank += child.id().map_or(0, |_| 1);
+                    rest = &rest[shared..];
+                    children = child.children();
+                    continue 'descend;
+                }
+                match label[shared].cmp(&rest[shared]) {
+                    Ordering::Less => rank += child.count_keys(),
+                    _ => return rank,
+                }
+            }
+            return rank;
+        }
+    }
+
+    /// `select` is the `k`th key in lexicographic order, counting from
+    /// zero, along with its identifier. Like `rank`, it skips whole
+    /// subtrees at a time. The key is given as it is stored, as `iter`
+    /// gives it.
+    pub fn select(&self, mut k: usize) -> Option<(String, S)> {
+        if let Some(id) = self.empty_id() {
+            if k == 0 {
+                return Some((String::new(), id));
+            }
+            k -= 1;
+        }
+        let mut key = Vec::new();
+        let mut children = self.top_level();
+        loop {
+            // Skip the subtrees holding only earlier keys.
+            let child = loop {
+                let child = children.next()?;
+                let below = child.count_keys();
+                if k < below {
+                    break child;
+                }
+                k -= below;
+            };
+            key.extend_from_slice(child.label());
+            if let Some(id) = child.id() {
+                if k == 0 {
+                    return Some((into_string(key), id));
+                }
+                k -= 1;
+            }
+            children = child.children();
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use crate::dtrie::Symbol;
+
+/// `Remap` records how identifiers changed when keys were copied into
+/// another trie. It maps each old identifier to its replacement.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Remap<S> {
+    // Sorted by the old identifier.
+    pairs: Vec<(S, S)>,
+}
+
+impl<S: Symbol> Remap<S> {
+    /// `from_pairs` builds a remap from `(old, new)` pairs in any order.
+    pub(crate) fn from_pairs(mut pairs: Vec<(S, S)>) -> Self {
+        pairs.sort_unstable();
+        Self { pairs }
+    }
+
+    /// `get` is the new identifier for `old`, if it was remapped.
+    pub fn get(&self, old: S) -> Option<S> {
+        let position = self
+            .pairs
+            .binary_search_by_key(&old, |(old, _)| *old)
+            .ok()?;
+        Some(self.pairs[position].1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    /// `iter` visits each `(old, new)` pair, ordered by the old identifier.
+    pub fn iter(&self) -> impl Iterator<Item = (S, S)> + '_ {
+        self.pairs.iter().copied()
+    }
+}
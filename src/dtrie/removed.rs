@@ -0,0 +1,12 @@
+/// `Removed` describes what happened to the identifiers
+/// when a key was taken out of a trie.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct Removed<S> {
+    /// `id` is the identifier the removed key had.
+    pub id: S,
+    /// `moved` is set when another key took over `id`, and holds
+    /// the identifier that key had until now. This only happens under
+    /// `IdPolicy::Dense`, where side tables indexed by identifier can
+    /// follow along with `Vec::swap_remove(id.to_usize())`.
+    pub moved: Option<S>,
+}
@@ -0,0 +1,7 @@
+/// `Scored` is the score given to a node's key, along with the
+/// highest score given to any key in its subtree.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub(crate) struct Scored {
+    pub score: u64,
+    pub best: u64,
+}
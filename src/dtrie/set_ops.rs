@@ -0,0 +1,277 @@
+use crate::dtrie::char_list::CharList;
+use crate::dtrie::dlb::count_shared_prefix;
+use crate::dtrie::dlb_node::DLBNode;
+use crate::dtrie::{Remap, Symbol, DLB};
+use std::iter::Peekable;
+
+/// `Cursor` is a position partway through a node's label. Only the
+/// bytes from `offset` on are still to be compared.
+#[derive(Clone, Copy)]
+struct Cursor<'a, S> {
+    node: DLBNode<'a, S>,
+    offset: usize,
+}
+
+impl<'a, S: Symbol> Cursor<'a, S> {
+    fn new(node: DLBNode<'a, S>) -> Self {
+        Self { node, offset: 0 }
+    }
+
+    fn label(&self) -> &'a [u8] {
+        &self.node.label()[self.offset..]
+    }
+
+    fn advance(&self, by: usize) -> Self {
+        Self {
+            node: self.node,
+            offset: self.offset + by,
+        }
+    }
+
+    /// `below` lists the cursors one level down: the rest of this
+    /// label if any is left, or else the node's children.
+    fn below(&self, by: usize) -> Vec<Self> {
+        if by < self.label().len() {
+            vec![self.advance(by)]
+        } else {
+            self.node.children().map(Cursor::new).collect()
+        }
+    }
+}
+
+/// `Pairing` is where a key was found when walking two tries together.
+enum Pairing<'a, S> {
+    /// The key is only in the first trie.
+    Left(DLBNode<'a, S>),
+    /// The key is only in the second trie, with this identifier.
+    Right(S),
+    /// The key is in both tries.
+    Both(DLBNode<'a, S>, S),
+}
+
+/// `zip` walks two sibling lists side by side, sorted by first byte.
+/// Labels are compared once for both tries, splitting them virtually
+/// wherever they part ways. Each non-empty key in either list is passed
+/// to `visit`, along with its bytes, in lexicographic order.
+fn zip<'a, 'b, S, F>(
+    left: Vec<Cursor<'a, S>>,
+    right: Vec<Cursor<'b, S>>,
+    path: &mut Vec<u8>,
+    visit: &mut F,
+) where
+    S: Symbol,
+    F: FnMut(Pairing<'a, S>, &[u8]),
+{
+    let mut left = left.into_iter().peekable();
+    let mut right = right.into_iter().peekable();
+    loop {
+        let byte = match (left.peek(), right.peek()) {
+            (None, None) => return,
+            (Some(l), None) => l.label()[0],
+            (None, Some(r)) => r.label()[0],
+            (Some(l), Some(r)) => l.label()[0].min(r.label()[0]),
+        };
+        let lefts = take_group(&mut left, byte);
+        let rights = take_group(&mut right, byte);
+        if rights.is_empty() {
+            for cursor in lefts {
+                walk(cursor, path, &mut |node, path| {
+                    visit(Pairing::Left(node), path)
+                });
+            }
+            continue;
+        }
+        if lefts.is_empty() {
+            for cursor in rights {
+                walk(cursor, path, &mut |node, path| {
+                    visit(Pairing::Right(node.id().unwrap()), path)
+                });
+            }
+            continue;
+        }
+        // Siblings from a `KeyMode::Utf8` trie may share a first byte.
+        // Then only that byte is certain to be shared, so step past it in
+        // all of them together, as if it belonged to a node of its own.
+        let step = match (lefts.as_slice(), rights.as_slice()) {
+            ([l], [r]) => count_shared_prefix(l.label(), r.label()),
+            _ => 1,
+        };
+        let len = path.len();
+        path.extend_from_slice(&lefts[0].label()[..step]);
+        let ends = |cursor: &&Cursor<S>| cursor.label().len() == step;
+        let ours = lefts
+            .iter()
+            .find(ends)
+            .and_then(|l| l.node.id().map(|_| l.node));
+        let theirs = rights.iter().find(ends).and_then(|r| r.node.id());
+        match (ours, theirs) {
+            (Some(ours), Some(theirs)) => visit(Pairing::Both(ours, theirs), path),
+            (Some(ours), None) => visit(Pairing::Left(ours), path),
+            (None, Some(theirs)) => visit(Pairing::Right(theirs), path),
+            (None, None) => {}
+        }
+        let lefts = lefts.iter().flat_map(|l| l.below(step)).collect();
+        let rights = rights.iter().flat_map(|r| r.below(step)).collect();
+        zip(lefts, rights, path, visit);
+        path.truncate(len);
+    }
+}
+
+/// `take_group` takes the cursors at the front of `cursors`
+/// whose labels start with `byte`.
+fn take_group<'a, S, I>(cursors: &mut Peekable<I>, byte: u8) -> Vec<Cursor<'a, S>>
+where
+    S: Symbol,
+    I: Iterator<Item = Cursor<'a, S>>,
+{
+    let mut group = Vec::new();
+    while let Some(cursor) = cursors.next_if(|cursor| cursor.label()[0] == byte) {
+        group.push(cursor);
+    }
+    group
+}
+
+/// `walk` visits every key below a cursor found in only one of the tries.
+fn walk<'a, S, F>(cursor: Cursor<'a, S>, path: &mut Vec<u8>, visit: &mut F)
+where
+    S: Symbol,
+    F: FnMut(DLBNode<'a, S>, &[u8]),
+{
+    let len = path.len();
+    path.extend_from_slice(cursor.label());
+    if cursor.node.id().is_some() {
+        visit(cursor.node, path);
+    }
+    for child in cursor.node.children() {
+        walk(Cursor::new(child), path, visit);
+    }
+    path.truncate(len);
+}
+
+impl<S: Symbol> DLB<S> {
+    /// `union` is a new trie holding the keys of both tries. Keys from
+    /// `self` keep their identifiers, and keys found only in `other` are
+    /// given new ones. Returns the remap from `other`'s identifiers to
+    /// those in the new trie. Panics if the identifiers run out.
+    pub fn union(&self, other: &Self) -> (Self, Remap<S>) {
+        let mut union = self.clone();
+        let remap = union.union_with(other);
+        (union, remap)
+    }
+
+    /// `union_with` adds the keys of `other` to this trie, returning
+    /// the remap from `other`'s identifiers to those in this trie.
+    /// New keys keep the spellings `other` saved for them.
+    pub fn union_with(&mut self, other: &Self) -> Remap<S> {
+        let mut pairs = Vec::with_capacity(other.len());
+        let mut missing = Vec::new();
+        zip_tries(self, other, &mut |pairing, key| match pairing {
+            Pairing::Left(_) => {}
+            Pairing::Right(theirs) => missing.push((theirs, key.to_vec())),
+            Pairing::Both(ours, theirs) => pairs.push((theirs, ours.id().unwrap())),
+        });
+        if let Some(theirs) = other.empty_id() {
+            let len = self.len();
+            let ours = self
+                .intern_empty_string()
+                .unwrap_or_else(|err| panic!("{}", err));
+            if self.len() > len {
+                copy_spelling(self, other, theirs, ours);
+            }
+            pairs.push((theirs, ours));
+        }
+        for (theirs, key) in missing {
+            let ours = self
+                .intern(CharList::from(key))
+                .unwrap_or_else(|err| panic!("{}", err));
+            copy_spelling(self, other, theirs, ours);
+            pairs.push((theirs, ours));
+        }
+        Remap::from_pairs(pairs)
+    }
+
+    /// `intersection` is a new trie holding the keys found in both tries,
+    /// with their identifiers from `self`. Returns the remap from `other`'s
+    /// identifiers to those in the new trie.
+    pub fn intersection(&self, other: &Self) -> (Self, Remap<S>) {
+        let mut intersection = self.clone();
+        let remap = intersection.intersect_with(other);
+        (intersection, remap)
+    }
+
+    /// `intersect_with` removes the keys which aren't in `other`, returning
+    /// the remap from `other`'s identifiers to those in this trie. As with
+    /// `remove`, under `IdPolicy::Dense` the newest keys take over the
+    /// identifiers of the keys removed.
+    pub fn intersect_with(&mut self, other: &Self) -> Remap<S> {
+        let mut shared = Vec::new();
+        let mut dropped = Vec::new();
+        zip_tries(self, other, &mut |pairing, _| match pairing {
+            Pairing::Left(ours) => dropped.push(ours.handle()),
+            Pairing::Right(_) => {}
+            Pairing::Both(ours, theirs) => shared.push((theirs, ours.handle())),
+        });
+        let empty = match other.empty_id() {
+            Some(theirs) => self.empty_id().map(|_| theirs),
+            None => {
+                self.remove(String::new());
+                None
+            }
+        };
+        for handle in dropped {
+            self.remove_node(handle);
+        }
+        // Handles with identifiers survive removals, but under
+        // `IdPolicy::Dense` the identifiers themselves may have moved.
+        let mut pairs: Vec<(S, S)> = shared
+            .into_iter()
+            .map(|(theirs, handle)| (theirs, self.record(handle).id().unwrap()))
+            .collect();
+        if let (Some(theirs), Some(ours)) = (empty, self.empty_id()) {
+            pairs.push((theirs, ours));
+        }
+        Remap::from_pairs(pairs)
+    }
+
+    /// `difference` is a new trie holding the keys of `self` which aren't
+    /// in `other`. No keys of `other` remain, so there is nothing to remap.
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut difference = self.clone();
+        difference.difference_with(other);
+        difference
+    }
+
+    /// `difference_with` removes every key which is also in `other`.
+    pub fn difference_with(&mut self, other: &Self) {
+        let mut shared = Vec::new();
+        zip_tries(self, other, &mut |pairing, _| {
+            if let Pairing::Both(ours, _) = pairing {
+                shared.push(ours.handle());
+            }
+        });
+        if other.empty_id().is_some() {
+            self.remove(String::new());
+        }
+        for handle in shared {
+            self.remove_node(handle);
+        }
+    }
+}
+
+/// `copy_spelling` gives `ours` the spelling `other` saved for `theirs`.
+fn copy_spelling<S: Symbol>(dlb: &mut DLB<S>, other: &DLB<S>, theirs: S, ours: S) {
+    if let Some(spelling) = other.spelling(theirs) {
+        dlb.set_spelling(ours, spelling.into());
+    }
+}
+
+/// `zip_tries` walks the top levels of both tries with `zip`.
+fn zip_tries<'a, S, F>(left: &'a DLB<S>, right: &DLB<S>, visit: &mut F)
+where
+    S: Symbol,
+    F: FnMut(Pairing<'a, S>, &[u8]),
+{
+    let left = left.top_level().map(Cursor::new).collect();
+    let right = right.top_level().map(Cursor::new).collect();
+    zip(left, right, &mut Vec::new(), visit);
+}
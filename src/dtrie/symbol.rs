@@ -0,0 +1,80 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::hash::Hash;
+use std::num::{NonZeroU16, NonZeroU32, NonZeroU64};
+
+/// `Symbol` is a compact handle for an interned key. Symbols count
+/// from zero wherever they are seen: they are made from, converted to,
+/// printed and parsed as zero-based indices. Implementations are
+/// expected to have a niche so that `Option<S>` is no larger than `S`.
+pub trait Symbol: Copy + Eq + Ord + Hash + fmt::Debug + fmt::Display {
+    /// `BITS` is the width of the symbol.
+    const BITS: u32;
+
+    /// `from_usize` makes the symbol for `index`, or returns `None`
+    /// if the index cannot be represented by this symbol type.
+    fn from_usize(index: usize) -> Option<Self>;
+
+    /// `to_usize` recovers the index the symbol was made from.
+    fn to_usize(self) -> usize;
+}
+
+macro_rules! symbol {
+    ($(#[$doc:meta])* $name:ident, $nonzero:ty, $int:ty) => {
+        $(#[$doc])*
+        #[derive(PartialEq, Eq, Copy, Clone, Debug, Hash, Ord, PartialOrd)]
+        pub struct $name($nonzero);
+
+        impl $name {
+            /// `new` makes the symbol for `index`, or returns `None` for
+            /// the largest index. The symbol stores `index + 1`, so that
+            /// zero is free to mean `None`.
+            pub fn new(index: $int) -> Option<Self> {
+                index.checked_add(1).and_then(<$nonzero>::new).map($name)
+            }
+        }
+
+        impl Symbol for $name {
+            const BITS: u32 = <$int>::BITS;
+
+            fn from_usize(index: usize) -> Option<Self> {
+                Self::new(<$int>::try_from(index).ok()?)
+            }
+
+            fn to_usize(self) -> usize {
+                self.0.get() as usize - 1
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}", <$int>::from(*self))
+            }
+        }
+
+        impl From<$name> for $int {
+            fn from(other: $name) -> $int {
+                other.0.get() - 1
+            }
+        }
+    };
+}
+
+symbol!(
+    /// `Sym16` holds up to 65,535 keys in two bytes.
+    Sym16,
+    NonZeroU16,
+    u16
+);
+symbol!(
+    /// `Sym32` holds up to about four billion keys in four bytes.
+    Sym32,
+    NonZeroU32,
+    u32
+);
+symbol!(
+    /// `Sym64` holds as many keys as will fit in memory, in eight bytes.
+    Sym64,
+    NonZeroU64,
+    u64
+);
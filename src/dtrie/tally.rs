@@ -0,0 +1,7 @@
+/// `Tally` is what a node knows about how often keys were counted:
+/// how often its own key was, and the sum of that over its subtree.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub(crate) struct Tally {
+    pub count: u64,
+    pub total: u64,
+}
@@ -0,0 +1,61 @@
+//! Little-endian helpers shared by the on-disk trie formats.
+
+use std::io::{self, Read, Write};
+
+pub(crate) fn write_u8<W: Write>(out: &mut W, value: u8) -> io::Result<()> {
+    out.write_all(&[value])
+}
+
+pub(crate) fn write_u32<W: Write>(out: &mut W, value: u32) -> io::Result<()> {
+    out.write_all(&value.to_le_bytes())
+}
+
+pub(crate) fn write_u64<W: Write>(out: &mut W, value: u64) -> io::Result<()> {
+    out.write_all(&value.to_le_bytes())
+}
+
+/// `write_bytes` writes a length-prefixed byte string.
+pub(crate) fn write_bytes<W: Write>(out: &mut W, bytes: &[u8]) -> io::Result<()> {
+    write_u32(out, bytes.len() as u32)?;
+    out.write_all(bytes)
+}
+
+pub(crate) fn read_u8<R: Read>(input: &mut R) -> io::Result<u8> {
+    let mut buf = [0; 1];
+    input.read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+
+pub(crate) fn read_u32<R: Read>(input: &mut R) -> io::Result<u32> {
+    let mut buf = [0; 4];
+    input.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+pub(crate) fn read_u64<R: Read>(input: &mut R) -> io::Result<u64> {
+    let mut buf = [0; 8];
+    input.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+/// `read_bytes` reads a byte string written by `write_bytes`.
+pub(crate) fn read_bytes<R: Read>(input: &mut R) -> io::Result<Vec<u8>> {
+    let len = read_u32(input)? as usize;
+    let mut buf = vec![0; len];
+    input.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+/// `expect_magic` consumes a file header and fails if it doesn't match.
+pub(crate) fn expect_magic<R: Read>(input: &mut R, magic: &[u8]) -> io::Result<()> {
+    let mut buf = vec![0; magic.len()];
+    input.read_exact(&mut buf)?;
+    if buf != magic {
+        return Err(invalid_data("unrecognised file header"));
+    }
+    Ok(())
+}
+
+pub(crate) fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
@@ -0,0 +1,307 @@
+use crate::dtrie::{BuildError, Identifier, MemoryUsage, Symbol, DLB};
+use crate::encoding::{
+    expect_magic, invalid_data, read_bytes, read_u32, read_u64, write_bytes, write_u32, write_u64,
+};
+use crate::fcd::varint::{push_varint, read_varint};
+use std::cmp::Ordering;
+use std::io::{self, Read, Write};
+use std::marker::PhantomData;
+use std::mem::size_of;
+
+/// `FrontCodedDict` stores sorted keys in buckets of `bucket_size`. The
+/// first key of each bucket, its head, is stored whole. Each of the others
+/// is stored as the length of the prefix it shares with the key before it,
+/// followed by the rest of its bytes. Lookups binary search the heads and
+/// then decode a single bucket, so larger buckets save space at the cost
+/// of slower lookups. Identifiers are the keys' ranks.
+pub struct FrontCodedDict<S = Identifier> {
+    bytes: Vec<u8>,
+    // Where each bucket starts in `bytes`.
+    buckets: Vec<u32>,
+    bucket_size: usize,
+    len: usize,
+    symbol: PhantomData<S>,
+}
+
+impl FrontCodedDict {
+    /// `MAGIC` is the header at the start of every serialised
+    /// `FrontCodedDict`, whatever its symbol type.
+    pub const MAGIC: &'static [u8] = b"SDTF\x01";
+
+    /// `DEFAULT_BUCKET_SIZE` is the bucket size used by `From<&DLB>`.
+    pub const DEFAULT_BUCKET_SIZE: usize = 16;
+}
+
+impl<S: Symbol> FrontCodedDict<S> {
+    /// `from_sorted_iter` front codes keys given in strictly increasing
+    /// order. Panics if `bucket_size` is zero.
+    pub fn from_sorted_iter<I>(keys: I, bucket_size: usize) -> Result<Self, BuildError>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        assert!(bucket_size > 0, "buckets must hold at least one key");
+        let mut dict = Self {
+            bytes: Vec::new(),
+            buckets: Vec::new(),
+            bucket_size,
+            len: 0,
+            symbol: PhantomData,
+        };
+        let mut previous: Vec<u8> = Vec::new();
+        for key in keys {
+            let key = key.as_ref().as_bytes();
+            let index = dict.len;
+            if index > 0 {
+                match key.cmp(&previous) {
+                    Ordering::Less => return Err(BuildError::Unsorted(index)),
+                    Ordering::Equal => return Err(BuildError::Duplicate(index)),
+                    Ordering::Greater => {}
+                }
+            }
+            S::from_usize(index).ok_or(BuildError::Exhausted)?;
+            if index.is_multiple_of(bucket_size) {
+                dict.buckets.push(dict.bytes.len() as u32);
+                push_varint(&mut dict.bytes, key.len());
+                dict.bytes.extend_from_slice(key);
+            } else {
+                let shared = previous
+                    .iter()
+                    .zip(key.iter())
+                    .take_while(|(x, y)| x == y)
+                    .count();
+                push_varint(&mut dict.bytes, shared);
+                push_varint(&mut dict.bytes, key.len() - shared);
+                dict.bytes.extend_from_slice(&key[shared..]);
+            }
+            previous.clear();
+            previous.extend_from_slice(key);
+            dict.len += 1;
+        }
+        dict.bytes.shrink_to_fit();
+        dict.buckets.shrink_to_fit();
+        Ok(dict)
+    }
+
+    /// `from_dlb` front codes the keys of `dlb` with the given bucket size.
+    /// The identifiers are ranks, matching those from `DLB::freeze_ranked`.
+    pub fn from_dlb(dlb: &DLB<S>, bucket_size: usize) -> Self {
+        Self::from_sorted_iter(dlb.iter().map(|(key, _)| key), bucket_size)
+            .expect("a trie iterates over its keys in order")
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn bucket_size(&self) -> usize {
+        self.bucket_size
+    }
+
+    /// `head` borrows the first key of the bucket starting at `offset`,
+    /// which is stored whole.
+    fn head(&self, offset: u32) -> &[u8] {
+        let mut pos = offset as usize;
+        let len = read_varint(&self.bytes, &mut pos).unwrap();
+        &self.bytes[pos..pos + len]
+    }
+
+    /// `bucket_len` is how many keys are in `bucket`; only the last
+    /// bucket may be short.
+    fn bucket_len(&self, bucket: usize) -> usize {
+        (self.len - bucket * self.bucket_size).min(self.bucket_size)
+    }
+
+    /// `decode` reads the key at `*pos` into `key`, which holds the key
+    /// before it, and moves past it. Returns `None` on malformed bytes.
+    fn decode(&self, pos: &mut usize, key: &mut Vec<u8>, is_head: bool) -> Option<()> {
+        let shared = if is_head {
+            0
+        } else {
+            read_varint(&self.bytes, pos)?
+        };
+        let rest = read_varint(&self.bytes, pos)?;
+        if shared > key.len() {
+            return None;
+        }
+        let suffix = self.bytes.get(*pos..pos.checked_add(rest)?)?;
+        key.truncate(shared);
+        key.extend_from_slice(suffix);
+        *pos += rest;
+        Some(())
+    }
+
+    pub fn contains(&self, s: String) -> bool {
+        self.get(s).is_some()
+    }
+
+    /// `get` finds the last bucket whose head isn't after `s`,
+    /// then decodes it until it reaches or passes `s`.
+    pub fn get(&self, s: String) -> Option<S> {
+        self.get_bytes(s.as_bytes())
+    }
+
+    fn get_bytes(&self, s: &[u8]) -> Option<S> {
+        let bucket = self.bucket_of(s)?;
+        let mut pos = self.buckets[bucket] as usize;
+        let mut key = Vec::new();
+        for index in 0..self.bucket_len(bucket) {
+            self.decode(&mut pos, &mut key, index == 0).unwrap();
+            match key.as_slice().cmp(s) {
+                Ordering::Less => {}
+                Ordering::Equal => return S::from_usize(bucket * self.bucket_size + index),
+                Ordering::Greater => return None,
+            }
+        }
+        None
+    }
+
+    /// `bucket_of` is the last bucket whose head isn't after `s`,
+    /// which holds `s` if any bucket does.
+    fn bucket_of(&self, s: &[u8]) -> Option<usize> {
+        self.buckets
+            .partition_point(|&offset| self.head(offset) <= s)
+            .checked_sub(1)
+    }
+
+    /// `resolve` decodes the bucket holding `id` up to its key.
+    pub fn resolve(&self, id: S) -> Option<String> {
+        let index = id.to_usize();
+        if index >= self.len {
+            return None;
+        }
+        let bucket = index / self.bucket_size;
+        let mut pos = self.buckets[bucket] as usize;
+        let mut key = Vec::new();
+        for position in 0..=index % self.bucket_size {
+            self.decode(&mut pos, &mut key, position == 0).unwrap();
+        }
+        String::from_utf8(key).ok()
+    }
+
+    /// `iter` decodes every key in order, along with its identifier.
+    pub fn iter(&self) -> impl Iterator<Item = (String, S)> + '_ {
+        self.iter_from(0)
+    }
+
+    /// `iter_from` decodes every key in order from the start of `bucket`.
+    fn iter_from(&self, bucket: usize) -> impl Iterator<Item = (String, S)> + '_ {
+        let mut pos = self
+            .buckets
+            .get(bucket)
+            .map_or(0, |&offset| offset as usize);
+        let mut key = Vec::new();
+        (bucket * self.bucket_size..self.len).map(move |index| {
+            self.decode(&mut pos, &mut key, index.is_multiple_of(self.bucket_size))
+                .unwrap();
+            let id = S::from_usize(index).unwrap();
+            (String::from_utf8_lossy(&key).into_owned(), id)
+        })
+    }
+
+    /// `prefix_iter` decodes every key which starts with `prefix`, in
+    /// order. It starts from the bucket where `prefix` would be, and stops
+    /// at the first key after those which start with it.
+    pub fn prefix_iter(&self, prefix: String) -> impl Iterator<Item = (String, S)> + '_ {
+        let bucket = self.bucket_of(prefix.as_bytes()).unwrap_or(0);
+        self.iter_from(bucket)
+            .skip_while({
+                let prefix = prefix.clone();
+                move |(key, _)| *key < prefix
+            })
+            .take_while(move |(key, _)| key.starts_with(&prefix))
+    }
+
+    /// `longest_prefix` is the longest key which `s` starts with, along
+    /// with its identifier, or `None` if no key is a prefix of `s`. Each
+    /// prefix of `s` is looked up in turn, from the longest.
+    pub fn longest_prefix(&self, s: String) -> Option<(String, S)> {
+        let ends = s.char_indices().map(|(end, _)| end).chain(Some(s.len()));
+        let ends: Vec<usize> = ends.collect();
+        ends.into_iter().rev().find_map(|end| {
+            let id = self.get_bytes(&s.as_bytes()[..end])?;
+            Some((s[..end].to_owned(), id))
+        })
+    }
+
+    /// `size_of` is the total memory used by this dictionary,
+    /// including the struct itself and everything on the heap.
+    pub fn size_of(&self) -> usize {
+        size_of::<Self>() + self.heap_size().total()
+    }
+
+    /// `heap_size` breaks down the heap memory owned by this dictionary.
+    /// The encoded keys count as labels and the bucket offsets as nodes.
+    pub fn heap_size(&self) -> MemoryUsage {
+        let offset_size = size_of::<u32>();
+        MemoryUsage {
+            nodes: self.buckets.len() * offset_size,
+            labels: self.bytes.len(),
+            child_containers: 0,
+            wasted_capacity: (self.buckets.capacity() - self.buckets.len()) * offset_size
+                + (self.bytes.capacity() - self.bytes.len()),
+        }
+    }
+
+    /// `write_to` saves the dictionary. Bucket offsets are
+    /// found again when it is read back.
+    pub fn write_to<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        out.write_all(FrontCodedDict::MAGIC)?;
+        write_u32(out, self.bucket_size as u32)?;
+        write_u64(out, self.len as u64)?;
+        write_bytes(out, &self.bytes)
+    }
+
+    /// `read_from` loads a dictionary saved by `write_to`, decoding
+    /// every key to check that they are well formed and in order.
+    pub fn read_from<R: Read>(input: &mut R) -> io::Result<Self> {
+        expect_magic(input, FrontCodedDict::MAGIC)?;
+        let bucket_size = read_u32(input)? as usize;
+        if bucket_size == 0 {
+            return Err(invalid_data("empty buckets"));
+        }
+        let len = read_u64(input)? as usize;
+        let bytes = read_bytes(input)?;
+        let mut dict = Self {
+            bytes,
+            buckets: Vec::new(),
+            bucket_size,
+            len: 0,
+            symbol: PhantomData,
+        };
+        let mut pos = 0;
+        let mut key = Vec::new();
+        let mut previous = Vec::new();
+        for index in 0..len {
+            if S::from_usize(index).is_none() {
+                return Err(invalid_data("too many keys for the symbol type"));
+            }
+            let is_head = index.is_multiple_of(bucket_size);
+            if is_head {
+                dict.buckets.push(pos as u32);
+            }
+            previous.clone_from(&key);
+            dict.decode(&mut pos, &mut key, is_head)
+                .ok_or_else(|| invalid_data("malformed key"))?;
+            if index > 0 && key <= previous {
+                return Err(invalid_data("keys out of order"));
+            }
+        }
+        if pos != dict.bytes.len() {
+            return Err(invalid_data("trailing bytes"));
+        }
+        dict.len = len;
+        dict.buckets.shrink_to_fit();
+        Ok(dict)
+    }
+}
+
+impl<S: Symbol> From<&DLB<S>> for FrontCodedDict<S> {
+    fn from(dlb: &DLB<S>) -> Self {
+        Self::from_dlb(dlb, FrontCodedDict::DEFAULT_BUCKET_SIZE)
+    }
+}
@@ -0,0 +1,4 @@
+pub use front_coded_dict::FrontCodedDict;
+
+mod front_coded_dict;
+mod varint;
@@ -0,0 +1,33 @@
+//! LEB128 lengths for the front-coded buckets: seven bits per byte,
+//! with the high bit set on every byte but the last.
+
+pub(crate) fn push_varint(out: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// `read_varint` decodes the number at `*pos` and moves past it.
+/// Returns `None` if it runs off the end or overflows a `u32`,
+/// including when it runs on past the five bytes a `u32` needs.
+pub(crate) fn read_varint(bytes: &[u8], pos: &mut usize) -> Option<usize> {
+    let mut value: u64 = 0;
+    let mut shift = 0;
+    loop {
+        let byte = *bytes.get(*pos)?;
+        *pos += 1;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if value > u32::MAX as u64 {
+            return None;
+        }
+        if byte & 0x80 == 0 {
+            return Some(value as usize);
+        }
+        shift += 7;
+        if shift > 28 {
+            return None;
+        }
+    }
+}
@@ -1 +1,9 @@
+pub mod art;
+pub mod dawg;
 pub mod dtrie;
+mod encoding;
+pub mod fcd;
+mod sorted;
+pub mod strie;
+pub mod trie;
+pub mod tst;
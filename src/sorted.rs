@@ -0,0 +1,113 @@
+use crate::dtrie::{BuildError, Symbol};
+use std::cmp::Ordering;
+
+/// `Frame` is a node on the path to the latest key which may still
+/// gain children. It ends `depth` bytes into that key.
+struct Frame<T, S> {
+    depth: usize,
+    id: Option<S>,
+    children: Vec<T>,
+}
+
+/// `SortedBuilder` assembles a path-compressed trie from keys given in
+/// sorted order, in a single pass and without ever splitting a label.
+/// Since the keys are sorted, every node still open lies on the path
+/// to the latest key, and a node is closed once a key turns away from
+/// it. Only then are its label and children known, so the caller's
+/// `make` function sees each node exactly once, after its children.
+pub(crate) struct SortedBuilder<T, S> {
+    previous: Vec<u8>,
+    frames: Vec<Frame<T, S>>,
+    count: usize,
+}
+
+impl<T, S: Symbol> SortedBuilder<T, S> {
+    pub fn new() -> Self {
+        Self {
+            previous: Vec::new(),
+            frames: vec![Frame {
+                depth: 0,
+                id: None,
+                children: Vec::new(),
+            }],
+            count: 0,
+        }
+    }
+
+    /// `push` adds the next key, returning its identifier. Identifiers
+    /// are handed out in the order the keys arrive. `make` is called
+    /// with the label, identifier and children of every node closed
+    /// by the new key.
+    pub fn push<F>(&mut self, key: &[u8], make: F) -> Result<S, BuildError>
+    where
+        F: FnMut(&[u8], Option<S>, Vec<T>) -> T,
+    {
+        let index = self.count;
+        if index > 0 {
+            match key.cmp(&self.previous) {
+                Ordering::Less => return Err(BuildError::Unsorted(index)),
+                Ordering::Equal => return Err(BuildError::Duplicate(index)),
+                Ordering::Greater => {}
+            }
+        }
+        let id = S::from_usize(index).ok_or(BuildError::Exhausted)?;
+        let shared = self
+            .previous
+            .iter()
+            .zip(key.iter())
+            .take_while(|(x, y)| x == y)
+            .count();
+        self.close(shared, make);
+        let top = self.frames.last_mut().unwrap();
+        if top.depth == key.len() {
+            // Only the empty string can end on a node that's already open.
+            top.id = Some(id);
+        } else {
+            self.frames.push(Frame {
+                depth: key.len(),
+                id: Some(id),
+                children: Vec::new(),
+            });
+        }
+        self.previous.truncate(shared);
+        self.previous.extend_from_slice(&key[shared..]);
+        self.count += 1;
+        Ok(id)
+    }
+
+    /// `finish` closes every remaining node, returning the identifier
+    /// of the empty string and the top-level nodes.
+    pub fn finish<F>(mut self, make: F) -> (Option<S>, Vec<T>)
+    where
+        F: FnMut(&[u8], Option<S>, Vec<T>) -> T,
+    {
+        self.close(0, make);
+        let root = self.frames.pop().unwrap();
+        (root.id, root.children)
+    }
+
+    /// `close` finishes every open node deeper than `depth`. If `depth`
+    /// falls partway through a node, a new node is opened there, taking
+    /// the part of the label below `depth` as its child.
+    fn close<F>(&mut self, depth: usize, mut make: F)
+    where
+        F: FnMut(&[u8], Option<S>, Vec<T>) -> T,
+    {
+        while self.frames.last().unwrap().depth > depth {
+            let frame = self.frames.pop().unwrap();
+            // The root frame has depth zero, so there is always a parent.
+            let parent = self.frames.last_mut().unwrap();
+            if parent.depth < depth {
+                let node = make(&self.previous[depth..frame.depth], frame.id, frame.children);
+                self.frames.push(Frame {
+                    depth,
+                    id: None,
+                    children: vec![node],
+                });
+                return;
+            }
+            let label = &self.previous[parent.depth..frame.depth];
+            parent.children.push(make(label, frame.id, frame.children));
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use crate::dtrie::iter::into_string;
+use crate::dtrie::Symbol;
+use crate::strie::StaticTrie;
+
+/// `Iter` walks a `StaticTrie` depth-first, yielding every
+/// key in lexicographic order together with its identifier.
+pub struct Iter<'a, S> {
+    trie: &'a StaticTrie<S>,
+    // Each entry holds a node index and the bytes preceding its label.
+    stack: Vec<(usize, Vec<u8>)>,
+}
+
+impl<'a, S> Iter<'a, S> {
+    pub(crate) fn new(trie: &'a StaticTrie<S>, stack: Vec<(usize, Vec<u8>)>) -> Self {
+        Self { trie, stack }
+    }
+}
+
+impl<'a, S: Symbol> Iterator for Iter<'a, S> {
+    type Item = (String, S);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((index, mut path)) = self.stack.pop() {
+            let node = self.trie.node(index);
+            path.extend_from_slice(self.trie.label(index));
+            for child in node.child_range().rev() {
+                self.stack.push((child, path.clone()));
+            }
+            if let Some(id) = node.id {
+                return Some((into_string(path), id));
+            }
+        }
+        None
+    }
+}
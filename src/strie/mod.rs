@@ -0,0 +1,8 @@
+pub use iter::Iter;
+pub use static_trie::StaticTrie;
+pub use static_trie_builder::StaticTrieBuilder;
+
+mod iter;
+mod static_node;
+mod static_trie;
+mod static_trie_builder;
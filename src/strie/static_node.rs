@@ -0,0 +1,33 @@
+/// `StaticNode` is a node in a frozen trie. Its label is a slice of the
+/// trie's label pool, and its children are stored contiguously, sorted
+/// by their first byte.
+#[derive(Clone, Copy)]
+pub struct StaticNode<S> {
+    pub label_start: u32,
+    pub label_len: u32,
+    pub first_child: u32,
+    pub child_count: u32,
+    pub id: Option<S>,
+}
+
+impl<S> StaticNode<S> {
+    pub fn new(label_start: u32, label_len: u32, id: Option<S>) -> Self {
+        Self {
+            label_start,
+            label_len,
+            first_child: 0,
+            child_count: 0,
+            id,
+        }
+    }
+
+    pub fn label_range(&self) -> std::ops::Range<usize> {
+        let start = self.label_start as usize;
+        start..start + self.label_len as usize
+    }
+
+    pub fn child_range(&self) -> std::ops::Range<usize> {
+        let start = self.first_child as usize;
+        start..start + self.child_count as usize
+    }
+}
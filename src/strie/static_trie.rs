@@ -0,0 +1,394 @@
+use crate::dtrie::identifier::{read_maybe_id, write_maybe_id};
+use crate::dtrie::iter::into_string;
+use crate::dtrie::{BuildError, Identifier, KeyMode, MemoryUsage, Symbol, DLB};
+use crate::encoding::{expect_magic, invalid_data, read_bytes, read_u32, write_bytes, write_u32};
+use crate::strie::iter::Iter;
+use crate::strie::static_node::StaticNode;
+use crate::strie::StaticTrieBuilder;
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+use std::mem::size_of;
+
+/// The root always lives at the front of the node list.
+const ROOT: usize = 0;
+
+/// `StaticTrie` is a read-only, flattened copy of a `DLB`, or one built
+/// straight from sorted keys. Every node's children are stored
+/// together and sorted, letting lookups binary search them.
+/// All labels share a single byte pool, in which equal labels are
+/// stored once and a label which ends another is stored inside it.
+/// Most labels are the tails of leaves, and many of those are
+/// common endings like "ing" or "ness".
+pub struct StaticTrie<S = Identifier> {
+    nodes: Vec<StaticNode<S>>,
+    labels: Vec<u8>,
+    // The parent of every node, used to rebuild keys from identifiers.
+    parents: Vec<u32>,
+    // Every identifier paired with the node completing it, sorted by identifier.
+    ids: Vec<(S, u32)>,
+}
+
+impl StaticTrie {
+    /// `MAGIC` is the header at the start of every serialised `StaticTrie`,
+    /// whatever its symbol type.
+    pub const MAGIC: &'static [u8] = b"SDTS\x01";
+}
+
+impl<S: Symbol> StaticTrie<S> {
+    /// `from_parts` derives the lookup tables from the node list.
+    pub(crate) fn from_parts(mut nodes: Vec<StaticNode<S>>, labels: Vec<u8>) -> Self {
+        nodes.shrink_to_fit();
+        let labels = pack_labels(&mut nodes, &labels);
+        let mut parents = vec![ROOT as u32; nodes.len()];
+        let mut ids = Vec::new();
+        for (index, node) in nodes.iter().enumerate() {
+            for child in node.child_range() {
+                parents[child] = index as u32;
+            }
+            if let Some(id) = node.id {
+                ids.push((id, index as u32));
+            }
+        }
+        ids.sort_unstable();
+        ids.shrink_to_fit();
+        Self {
+            nodes,
+            labels,
+            parents,
+            ids,
+        }
+    }
+
+    /// `map_ids` replaces every identifier in the trie with `f(id)`.
+    pub(crate) fn map_ids<F: Fn(S) -> S>(self, f: F) -> Self {
+        let mut nodes = self.nodes;
+        for node in nodes.iter_mut() {
+            node.id = node.id.map(&f);
+        }
+        Self::from_parts(nodes, self.labels)
+    }
+
+    pub(crate) fn node(&self, index: usize) -> &StaticNode<S> {
+        &self.nodes[index]
+    }
+
+    pub(crate) fn label(&self, index: usize) -> &[u8] {
+        &self.labels[self.nodes[index].label_range()]
+    }
+
+    /// `len` is the number of keys in the trie.
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// `count_nodes` counts the nodes below the root,
+    /// matching `DLB::count_nodes` on the source trie.
+    pub fn count_nodes(&self) -> u64 {
+        (self.nodes.len() - 1) as u64
+    }
+
+    /// `find_child` binary searches a node's children for the one
+    /// `pattern` leads into. Siblings frozen from a `KeyMode::Utf8` trie
+    /// may share a first byte, in which case the one starting with the
+    /// pattern's first character is picked.
+    fn find_child(&self, index: usize, pattern: &[u8]) -> Option<usize> {
+        let range = self.nodes[index].child_range();
+        let start = range.start;
+        let first_byte = |child: &StaticNode<S>| self.labels[child.label_start as usize];
+        let children = &self.nodes[range];
+        let first = children.partition_point(|child| first_byte(child) < pattern[0]);
+        let count = children[first..]
+            .iter()
+            .take_while(|child| first_byte(child) == pattern[0])
+            .count();
+        if count <= 1 {
+            return Some(start + first).filter(|_| count == 1);
+        }
+        let lead = KeyMode::Utf8.lead(pattern);
+        (first..first + count)
+            .find(|&offset| self.label(start + offset).starts_with(lead))
+            .map(|offset| start + offset)
+    }
+
+    pub fn get(&self, s: String) -> Option<S> {
+        let mut pattern = s.as_bytes();
+        let mut index = ROOT;
+        while !pattern.is_empty() {
+            index = self.find_child(index, pattern)?;
+            let label = self.label(index);
+            if !pattern.starts_with(label) {
+                return None;
+            }
+            pattern = &pattern[label.len()..];
+        }
+        self.nodes[index].id
+    }
+
+    pub fn contains(&self, s: String) -> bool {
+        self.get(s).is_some()
+    }
+
+    pub fn resolve(&self, id: S) -> Option<String> {
+        let position = self.ids.binary_search_by_key(&id, |(id, _)| *id).ok()?;
+        let mut index = self.ids[position].1 as usize;
+        let mut labels = Vec::new();
+        while index != ROOT {
+            labels.push(self.label(index));
+            index = self.parents[index] as usize;
+        }
+        let bytes: Vec<u8> = labels.into_iter().rev().flatten().copied().collect();
+        String::from_utf8(bytes).ok()
+    }
+
+    /// `iter` visits every key in lexicographic order.
+    pub fn iter(&self) -> Iter<'_, S> {
+        Iter::new(self, vec![(ROOT, Vec::new())])
+    }
+
+    /// `prefix_iter` visits every key which starts with `prefix`,
+    /// in lexicographic order.
+    pub fn prefix_iter(&self, prefix: String) -> Iter<'_, S> {
+        let mut pattern = prefix.as_bytes();
+        let mut index = ROOT;
+        let mut thus_far = Vec::new();
+        while !pattern.is_empty() {
+            let child = match self.find_child(index, pattern) {
+                Some(child) => child,
+                None => return Iter::new(self, Vec::new()),
+            };
+            let label = self.label(child);
+            let similarity = label.len().min(pattern.len());
+            if label[..similarity] != pattern[..similarity] {
+                return Iter::new(self, Vec::new());
+            }
+            pattern = &pattern[similarity..];
+            if !pattern.is_empty() {
+                thus_far.extend_from_slice(label);
+            }
+            index = child;
+        }
+        Iter::new(self, vec![(index, thus_far)])
+    }
+
+    /// `longest_prefix` is the longest key which `s` starts with, along
+    /// with its identifier, or `None` if no key is a prefix of `s`.
+    pub fn longest_prefix(&self, s: String) -> Option<(String, S)> {
+        let key = s.as_bytes();
+        let mut pattern = key;
+        let mut index = ROOT;
+        let mut longest = self.nodes[ROOT].id.map(|id| (0, id));
+        while !pattern.is_empty() {
+            index = match self.find_child(index, pattern) {
+                Some(child) => child,
+                None => break,
+            };
+            let label = self.label(index);
+            if !pattern.starts_with(label) {
+                break;
+            }
+            pattern = &pattern[label.len()..];
+            if let Some(id) = self.nodes[index].id {
+                longest = Some((key.len() - pattern.len(), id));
+            }
+        }
+        longest.map(|(len, id)| (into_string(key[..len].to_vec()), id))
+    }
+
+    /// `depth_histogram` counts the nodes found at each depth.
+    /// Top-level nodes sit at depth zero.
+    pub fn depth_histogram(&self) -> Vec<u64> {
+        let mut histogram = Vec::new();
+        let mut stack: Vec<(usize, usize)> = self.nodes[ROOT]
+            .child_range()
+            .map(|child| (child, 0))
+            .collect();
+        // Children don't always follow their parents, so walk down from the root.
+        while let Some((index, depth)) = stack.pop() {
+            if histogram.len() <= depth {
+                histogram.resize(depth + 1, 0);
+            }
+            histogram[depth] += 1;
+            stack.extend(
+                self.nodes[index]
+                    .child_range()
+                    .map(|child| (child, depth + 1)),
+            );
+        }
+        histogram
+    }
+
+    /// `raw_label_size` is the number of bytes the labels would take up
+    /// if each were stored separately, rather than sharing their tails.
+    pub fn raw_label_size(&self) -> usize {
+        self.nodes.iter().map(|node| node.label_len as usize).sum()
+    }
+
+    /// `size_of` is the total memory used by this trie,
+    /// including the struct itself and everything on the heap.
+    pub fn size_of(&self) -> usize {
+        size_of::<Self>() + self.heap_size().total()
+    }
+
+    /// `heap_size` breaks down the heap memory owned by this trie.
+    /// The parent and identifier tables count as container overhead.
+    pub fn heap_size(&self) -> MemoryUsage {
+        let node_size = size_of::<StaticNode<S>>();
+        let parent_size = size_of::<u32>();
+        let id_size = size_of::<(S, u32)>();
+        MemoryUsage {
+            nodes: self.nodes.len() * node_size,
+            labels: self.labels.len(),
+            child_containers: self.parents.len() * parent_size + self.ids.len() * id_size,
+            wasted_capacity: (self.nodes.capacity() - self.nodes.len()) * node_size
+                + (self.labels.capacity() - self.labels.len())
+                + (self.parents.capacity() - self.parents.len()) * parent_size
+                + (self.ids.capacity() - self.ids.len()) * id_size,
+        }
+    }
+
+    pub fn write_to<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        out.write_all(StaticTrie::MAGIC)?;
+        write_bytes(out, &self.labels)?;
+        write_u32(out, self.nodes.len() as u32)?;
+        for node in self.nodes.iter() {
+            write_u32(out, node.label_start)?;
+            write_u32(out, node.label_len)?;
+            write_u32(out, node.first_child)?;
+            write_u32(out, node.child_count)?;
+            write_maybe_id(out, node.id)?;
+        }
+        Ok(())
+    }
+
+    pub fn read_from<R: Read>(input: &mut R) -> io::Result<Self> {
+        expect_magic(input, StaticTrie::MAGIC)?;
+        let labels = read_bytes(input)?;
+        let node_count = read_u32(input)? as usize;
+        let mut nodes = Vec::with_capacity(node_count);
+        for _ in 0..node_count {
+            let mut node = StaticNode::new(read_u32(input)?, read_u32(input)?, None);
+            node.first_child = read_u32(input)?;
+            node.child_count = read_u32(input)?;
+            node.id = read_maybe_id(input)?;
+            if node.label_range().end > labels.len() || node.child_range().end > node_count {
+                return Err(invalid_data("node out of bounds"));
+            }
+            nodes.push(node);
+        }
+        if nodes.is_empty() {
+            return Err(invalid_data("missing root node"));
+        }
+        if !is_tree(&nodes, &labels) {
+            return Err(invalid_data("malformed tree"));
+        }
+        let trie = Self::from_parts(nodes, labels);
+        if trie.ids.windows(2).any(|pair| pair[0].0 == pair[1].0) {
+            return Err(invalid_data("duplicate identifier"));
+        }
+        Ok(trie)
+    }
+
+    /// `from_sorted_iter` builds a trie from keys in strictly increasing
+    /// order with a `StaticTrieBuilder`. Identifiers are handed out
+    /// sequentially in the order the keys are given.
+    pub fn from_sorted_iter<I>(keys: I) -> Result<Self, BuildError>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let mut builder = StaticTrieBuilder::default();
+        for key in keys {
+            builder.push(key.as_ref())?;
+        }
+        Ok(builder.finish())
+    }
+}
+
+impl<S: Symbol> From<&DLB<S>> for StaticTrie<S> {
+    fn from(dlb: &DLB<S>) -> Self {
+        let mut nodes = vec![StaticNode::new(0, 0, dlb.empty_id())];
+        let mut labels = Vec::new();
+        let mut queue = VecDeque::new();
+        queue.push_back((ROOT, dlb.top_level()));
+        // The dynamic trie already keeps siblings sorted.
+        while let Some((index, children)) = queue.pop_front() {
+            nodes[index].first_child = nodes.len() as u32;
+            for child in children {
+                let label = child.label();
+                let node = StaticNode::new(labels.len() as u32, label.len() as u32, child.id());
+                labels.extend_from_slice(label);
+                queue.push_back((nodes.len(), child.children()));
+                nodes.push(node);
+            }
+            nodes[index].child_count = nodes.len() as u32 - nodes[index].first_child;
+        }
+        Self::from_parts(nodes, labels)
+    }
+}
+
+/// `is_tree` checks that `nodes` hang from the root as a trie does:
+/// every node is reached from the root exactly once, only the root has
+/// an empty label, and siblings are sorted by their first character.
+fn is_tree<S>(nodes: &[StaticNode<S>], labels: &[u8]) -> bool {
+    if nodes[ROOT].label_len != 0 {
+        return false;
+    }
+    let mut seen = vec![false; nodes.len()];
+    seen[ROOT] = true;
+    let mut stack = vec![ROOT];
+    while let Some(index) = stack.pop() {
+        let mut previous: Option<&[u8]> = None;
+        for child in nodes[index].child_range() {
+            let label = &labels[nodes[child].label_range()];
+            if label.is_empty() || std::mem::replace(&mut seen[child], true) {
+                return false;
+            }
+            let lead = KeyMode::Utf8.lead(label);
+            if previous.is_some_and(|previous| previous >= lead) {
+                return false;
+            }
+            previous = Some(lead);
+            stack.push(child);
+        }
+    }
+    seen.into_iter().all(|seen| seen)
+}
+
+/// `pack_labels` copies the labels of `nodes` out of `labels` into a new
+/// pool, pointing the nodes at their new positions. Reading the labels
+/// backwards and sorting them in descending order puts each label right
+/// after some label it ends, if there is one, so it need only be checked
+/// against the last label stored.
+fn pack_labels<S>(nodes: &mut [StaticNode<S>], labels: &[u8]) -> Vec<u8> {
+    let mut order = Vec::with_capacity(nodes.len());
+    for (index, node) in nodes.iter_mut().enumerate() {
+        if node.label_len == 0 {
+            node.label_start = 0;
+        } else {
+            order.push(index);
+        }
+    }
+    order.sort_unstable_by(|&left, &right| {
+        let left = labels[nodes[left].label_range()].iter().rev();
+        let right = labels[nodes[right].label_range()].iter().rev();
+        right.cmp(left)
+    });
+    let mut pool: Vec<u8> = Vec::new();
+    let mut last = 0..0;
+    for index in order {
+        let label = &labels[nodes[index].label_range()];
+        if pool[last.clone()].ends_with(label) {
+            nodes[index].label_start = (last.end - label.len()) as u32;
+        } else {
+            last = pool.len()..pool.len() + label.len();
+            nodes[index].label_start = last.start as u32;
+            pool.extend_from_slice(label);
+        }
+    }
+    pool.shrink_to_fit();
+    pool
+}
@@ -0,0 +1,73 @@
+use crate::dtrie::{BuildError, Identifier, Symbol};
+use crate::sorted::SortedBuilder;
+use crate::strie::static_node::StaticNode;
+use crate::strie::StaticTrie;
+
+/// `StaticTrieBuilder` lays out a `StaticTrie` directly from keys in
+/// strictly increasing order, without building a `DLB` first. Each node's
+/// children are written out together once the node is finished, so they
+/// stay adjacent and sorted, just as in a frozen trie.
+pub struct StaticTrieBuilder<S = Identifier> {
+    sorted: SortedBuilder<StaticNode<S>, S>,
+    // The root's slot at the front is filled in by `finish`.
+    nodes: Vec<StaticNode<S>>,
+    labels: Vec<u8>,
+}
+
+impl<S: Symbol> Default for StaticTrieBuilder<S> {
+    fn default() -> Self {
+        Self {
+            sorted: SortedBuilder::new(),
+            nodes: vec![StaticNode::new(0, 0, None)],
+            labels: Vec::new(),
+        }
+    }
+}
+
+impl StaticTrieBuilder {
+    /// `new` makes a builder for a trie using `Identifier`s.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<S: Symbol> StaticTrieBuilder<S> {
+    /// `push` adds the next key, returning its identifier. Identifiers
+    /// are handed out sequentially. A key which doesn't sort after the
+    /// previous one is rejected, and the builder is left unchanged.
+    pub fn push(&mut self, key: &str) -> Result<S, BuildError> {
+        let nodes = &mut self.nodes;
+        let labels = &mut self.labels;
+        self.sorted.push(key.as_bytes(), |label, id, children| {
+            place(nodes, labels, label, id, children)
+        })
+    }
+
+    /// `finish` closes the remaining nodes and returns the trie.
+    pub fn finish(self) -> StaticTrie<S> {
+        let mut nodes = self.nodes;
+        let mut labels = self.labels;
+        let (empty, top_level) = self
+            .sorted
+            .finish(|label, id, children| place(&mut nodes, &mut labels, label, id, children));
+        nodes[0] = place(&mut nodes, &mut labels, &[], empty, top_level);
+        StaticTrie::from_parts(nodes, labels)
+    }
+}
+
+/// `place` writes out the children of a finished node and
+/// returns the node, which is placed along with its siblings.
+fn place<S>(
+    nodes: &mut Vec<StaticNode<S>>,
+    labels: &mut Vec<u8>,
+    label: &[u8],
+    id: Option<S>,
+    children: Vec<StaticNode<S>>,
+) -> StaticNode<S> {
+    let mut node = StaticNode::new(labels.len() as u32, label.len() as u32, id);
+    labels.extend_from_slice(label);
+    node.first_child = nodes.len() as u32;
+    node.child_count = children.len() as u32;
+    nodes.extend(children);
+    node
+}
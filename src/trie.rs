@@ -0,0 +1,227 @@
+use crate::art::AdaptiveRadixTrie;
+use crate::dawg::Dawg;
+use crate::dtrie::{MemoryUsage, Symbol, DLB};
+use crate::fcd::FrontCodedDict;
+use crate::strie::StaticTrie;
+use crate::tst::TernarySearchTrie;
+
+/// `Keys` is the iterator the traits return: every key it visits,
+/// together with its identifier.
+pub type Keys<'a, S> = Box<dyn Iterator<Item = (String, S)> + 'a>;
+
+/// `TrieRead` is what every trie in the crate can answer, dynamic or
+/// static, so read-only code can be written once and run against
+/// whichever structure suits its keys. The methods behave as the
+/// inherent methods of the same names.
+pub trait TrieRead {
+    /// `Symbol` is the type of the identifiers given to keys.
+    type Symbol: Symbol;
+
+    fn get(&self, s: String) -> Option<Self::Symbol>;
+
+    fn contains(&self, s: String) -> bool {
+        self.get(s).is_some()
+    }
+
+    /// `prefix_iter` visits every key starting with `prefix`,
+    /// including `prefix` itself, in byte order.
+    fn prefix_iter(&self, prefix: String) -> Keys<'_, Self::Symbol>;
+
+    /// `longest_prefix` is the longest key which `s` starts with, along
+    /// with its identifier, or `None` if no key is a prefix of `s`.
+    fn longest_prefix(&self, s: String) -> Option<(String, Self::Symbol)>;
+}
+
+/// `Interner` is a `TrieRead` which hands out identifiers for new keys,
+/// and can turn them back into keys.
+pub trait Interner: TrieRead {
+    /// `get_or_intern` returns the identifier of `s`, adding it if
+    /// needed. Panics if the symbol type has no identifiers left.
+    fn get_or_intern(&mut self, s: String) -> Self::Symbol;
+
+    fn resolve(&self, id: Self::Symbol) -> Option<String>;
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// `Trie` is what the dynamic tries have in common beyond interning,
+/// for comparing their node layouts on the same keys.
+pub trait Trie: Interner {
+    /// `iter` visits every key along with its identifier, in byte order.
+    fn iter(&self) -> Keys<'_, Self::Symbol>;
+
+    /// `heap_size` breaks down the heap memory owned by the trie.
+    fn heap_size(&self) -> MemoryUsage;
+}
+
+impl<S: Symbol> TrieRead for DLB<S> {
+    type Symbol = S;
+
+    fn get(&self, s: String) -> Option<S> {
+        DLB::get(self, s)
+    }
+
+    fn prefix_iter(&self, prefix: String) -> Keys<'_, S> {
+        Box::new(DLB::prefix_iter(self, prefix))
+    }
+
+    fn longest_prefix(&self, s: String) -> Option<(String, S)> {
+        DLB::longest_prefix(self, s)
+    }
+}
+
+impl<S: Symbol> Interner for DLB<S> {
+    fn get_or_intern(&mut self, s: String) -> S {
+        DLB::get_or_intern(self, s)
+    }
+
+    fn resolve(&self, id: S) -> Option<String> {
+        DLB::resolve(self, id)
+    }
+
+    fn len(&self) -> usize {
+        DLB::len(self)
+    }
+}
+
+impl<S: Symbol> Trie for DLB<S> {
+    fn iter(&self) -> Keys<'_, S> {
+        Box::new(DLB::iter(self))
+    }
+
+    fn heap_size(&self) -> MemoryUsage {
+        DLB::heap_size(self)
+    }
+}
+
+impl<S: Symbol> TrieRead for TernarySearchTrie<S> {
+    type Symbol = S;
+
+    fn get(&self, s: String) -> Option<S> {
+        TernarySearchTrie::get(self, s)
+    }
+
+    fn prefix_iter(&self, prefix: String) -> Keys<'_, S> {
+        Box::new(TernarySearchTrie::prefix_iter(self, prefix))
+    }
+
+    fn longest_prefix(&self, s: String) -> Option<(String, S)> {
+        TernarySearchTrie::longest_prefix(self, s)
+    }
+}
+
+impl<S: Symbol> Interner for TernarySearchTrie<S> {
+    fn get_or_intern(&mut self, s: String) -> S {
+        TernarySearchTrie::get_or_intern(self, s)
+    }
+
+    fn resolve(&self, id: S) -> Option<String> {
+        TernarySearchTrie::resolve(self, id)
+    }
+
+    fn len(&self) -> usize {
+        TernarySearchTrie::len(self)
+    }
+}
+
+impl<S: Symbol> Trie for TernarySearchTrie<S> {
+    fn iter(&self) -> Keys<'_, S> {
+        Box::new(TernarySearchTrie::iter(self))
+    }
+
+    fn heap_size(&self) -> MemoryUsage {
+        TernarySearchTrie::heap_size(self)
+    }
+}
+
+impl<S: Symbol> TrieRead for AdaptiveRadixTrie<S> {
+    type Symbol = S;
+
+    fn get(&self, s: String) -> Option<S> {
+        AdaptiveRadixTrie::get(self, s)
+    }
+
+    fn prefix_iter(&self, prefix: String) -> Keys<'_, S> {
+        Box::new(AdaptiveRadixTrie::prefix_iter(self, prefix))
+    }
+
+    fn longest_prefix(&self, s: String) -> Option<(String, S)> {
+        AdaptiveRadixTrie::longest_prefix(self, s)
+    }
+}
+
+impl<S: Symbol> Interner for AdaptiveRadixTrie<S> {
+    fn get_or_intern(&mut self, s: String) -> S {
+        AdaptiveRadixTrie::get_or_intern(self, s)
+    }
+
+    fn resolve(&self, id: S) -> Option<String> {
+        AdaptiveRadixTrie::resolve(self, id)
+    }
+
+    fn len(&self) -> usize {
+        AdaptiveRadixTrie::len(self)
+    }
+}
+
+impl<S: Symbol> Trie for AdaptiveRadixTrie<S> {
+    fn iter(&self) -> Keys<'_, S> {
+        Box::new(AdaptiveRadixTrie::iter(self))
+    }
+
+    fn heap_size(&self) -> MemoryUsage {
+        AdaptiveRadixTrie::heap_size(self)
+    }
+}
+
+impl<S: Symbol> TrieRead for StaticTrie<S> {
+    type Symbol = S;
+
+    fn get(&self, s: String) -> Option<S> {
+        StaticTrie::get(self, s)
+    }
+
+    fn prefix_iter(&self, prefix: String) -> Keys<'_, S> {
+        Box::new(StaticTrie::prefix_iter(self, prefix))
+    }
+
+    fn longest_prefix(&self, s: String) -> Option<(String, S)> {
+        StaticTrie::longest_prefix(self, s)
+    }
+}
+
+impl<S: Symbol> TrieRead for FrontCodedDict<S> {
+    type Symbol = S;
+
+    fn get(&self, s: String) -> Option<S> {
+        FrontCodedDict::get(self, s)
+    }
+
+    fn prefix_iter(&self, prefix: String) -> Keys<'_, S> {
+        Box::new(FrontCodedDict::prefix_iter(self, prefix))
+    }
+
+    fn longest_prefix(&self, s: String) -> Option<(String, S)> {
+        FrontCodedDict::longest_prefix(self, s)
+    }
+}
+
+impl<S: Symbol> TrieRead for Dawg<S> {
+    type Symbol = S;
+
+    fn get(&self, s: String) -> Option<S> {
+        Dawg::get(self, s)
+    }
+
+    fn prefix_iter(&self, prefix: String) -> Keys<'_, S> {
+        Box::new(Dawg::prefix_iter(self, prefix))
+    }
+
+    fn longest_prefix(&self, s: String) -> Option<(String, S)> {
+        Dawg::longest_prefix(self, s)
+    }
+}
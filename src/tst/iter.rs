@@ -0,0 +1,77 @@
+use crate::dtrie::iter::into_string;
+use crate::dtrie::node_handle::NodeHandle;
+use crate::dtrie::Symbol;
+use crate::tst::tst_node::{TstNode, EQ, HI, LO};
+
+/// `Frame` is a step of the in-order walk. Each one records
+/// how many bytes of the path precede the node it visits.
+enum Frame {
+    /// Walk the subtree rooted at the node.
+    Enter(NodeHandle, usize),
+    /// Take the node's byte, then walk its equal child.
+    Match(NodeHandle, usize),
+}
+
+/// `Iter` walks a ternary search trie in order,
+/// yielding every key together with its identifier.
+pub struct Iter<'a, S> {
+    nodes: &'a [TstNode<S>],
+    // The key ending where the walk starts, such as the empty string,
+    // is yielded first.
+    first: Option<S>,
+    path: Vec<u8>,
+    stack: Vec<Frame>,
+}
+
+impl<'a, S> Iter<'a, S> {
+    /// `new` walks the subtree rooted at `start`, whose keys all begin
+    /// with `path`. If `first` is set, it is `path`'s own identifier.
+    pub(crate) fn new(
+        nodes: &'a [TstNode<S>],
+        path: Vec<u8>,
+        first: Option<S>,
+        start: NodeHandle,
+    ) -> Self {
+        let stack = vec![Frame::Enter(start, path.len())];
+        Self {
+            nodes,
+            first,
+            path,
+            stack,
+        }
+    }
+}
+
+impl<'a, S: Symbol> Iterator for Iter<'a, S> {
+    type Item = (String, S);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(id) = self.first.take() {
+            return Some((into_string(self.path.clone()), id));
+        }
+        while let Some(frame) = self.stack.pop() {
+            match frame {
+                Frame::Enter(handle, depth) => {
+                    if handle.is_nil() {
+                        continue;
+                    }
+                    // Frames are popped from the back, so push them in reverse.
+                    let children = self.nodes[handle.index()].children;
+                    self.stack.push(Frame::Enter(children[HI], depth));
+                    self.stack.push(Frame::Match(handle, depth));
+                    self.stack.push(Frame::Enter(children[LO], depth));
+                }
+                Frame::Match(handle, depth) => {
+                    let node = &self.nodes[handle.index()];
+                    self.path.truncate(depth);
+                    self.path.push(node.byte);
+                    self.stack.push(Frame::Enter(node.children[EQ], depth + 1));
+                    if let Some(id) = node.id {
+                        return Some((into_string(self.path.clone()), id));
+                    }
+                }
+            }
+        }
+        None
+    }
+}
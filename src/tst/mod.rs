@@ -0,0 +1,6 @@
+pub use iter::Iter;
+pub use ternary_search_trie::TernarySearchTrie;
+
+mod iter;
+mod ternary_search_trie;
+mod tst_node;
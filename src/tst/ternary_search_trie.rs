@@ -0,0 +1,257 @@
+use crate::dtrie::iter::into_string;
+use crate::dtrie::node_handle::NodeHandle;
+use crate::dtrie::{Exhausted, Identifier, MemoryUsage, Symbol};
+use crate::tst::iter::Iter;
+use crate::tst::tst_node::{TstNode, EQ, HI, LO};
+use std::cmp::Ordering;
+use std::iter::FromIterator;
+use std::mem::size_of;
+
+/// `TernarySearchTrie` interns keys one byte per node. Where a `DLB`
+/// scans a list of siblings, each node here has a low and a high child,
+/// so the siblings at each depth form a binary search tree. This suits
+/// wide fan-outs, at the cost of a node for every byte, since labels
+/// aren't compressed. Identifiers are handed out in order, starting
+/// from zero, and keys can't be removed.
+#[derive(Clone, Debug)]
+pub struct TernarySearchTrie<S = Identifier> {
+    nodes: Vec<TstNode<S>>,
+    root: NodeHandle,
+    // The node completing each identifier, indexed by identifier.
+    ids: Vec<NodeHandle>,
+    contains_empty: Option<S>,
+}
+
+impl<S: Symbol> Default for TernarySearchTrie<S> {
+    fn default() -> Self {
+        Self {
+            nodes: Vec::new(),
+            root: NodeHandle::NIL,
+            ids: Vec::new(),
+            contains_empty: None,
+        }
+    }
+}
+
+impl<S: Symbol, K: AsRef<str>> FromIterator<K> for TernarySearchTrie<S> {
+    fn from_iter<I: IntoIterator<Item = K>>(keys: I) -> Self {
+        let mut trie = Self::default();
+        for key in keys {
+            trie.get_or_intern(key.as_ref().to_owned());
+        }
+        trie
+    }
+}
+
+impl<S: Symbol> TernarySearchTrie<S> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn count_nodes(&self) -> u64 {
+        self.nodes.len() as u64
+    }
+
+    pub fn contains(&self, s: String) -> bool {
+        self.get(s).is_some()
+    }
+
+    pub fn get(&self, s: String) -> Option<S> {
+        if s.is_empty() {
+            return self.contains_empty;
+        }
+        let handle = self.find(s.as_bytes())?;
+        self.nodes[handle.index()].id
+    }
+
+    /// `find` returns the node holding the last byte of `key`.
+    fn find(&self, key: &[u8]) -> Option<NodeHandle> {
+        let mut handle = self.root;
+        let mut depth = 0;
+        while !handle.is_nil() {
+            let node = &self.nodes[handle.index()];
+            match key[depth].cmp(&node.byte) {
+                Ordering::Less => handle = node.children[LO],
+                Ordering::Greater => handle = node.children[HI],
+                Ordering::Equal => {
+                    depth += 1;
+                    if depth == key.len() {
+                        return Some(handle);
+                    }
+                    handle = node.children[EQ];
+                }
+            }
+        }
+        None
+    }
+
+    /// `get_or_intern` returns the identifier of `s`, adding it if needed.
+    /// Panics if the symbol type has no identifiers left.
+    pub fn get_or_intern(&mut self, s: String) -> S {
+        match self.try_get_or_intern(s) {
+            Ok(id) => id,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// `try_get_or_intern` is `get_or_intern`, but returns an error
+    /// rather than panicking when the identifiers run out.
+    pub fn try_get_or_intern(&mut self, s: String) -> Result<S, Exhausted> {
+        let key = s.as_bytes();
+        if key.is_empty() {
+            if let Some(id) = self.contains_empty {
+                return Ok(id);
+            }
+            let id = self.new_id(NodeHandle::NIL)?;
+            self.contains_empty = Some(id);
+            return Ok(id);
+        }
+        // `branch` is which child of `parent` leads to `handle`.
+        let (mut parent, mut branch) = (NodeHandle::NIL, EQ);
+        let mut handle = self.root;
+        let mut depth = 0;
+        loop {
+            if handle.is_nil() {
+                handle = self.push_node(key[depth], parent, branch);
+            }
+            let node = &self.nodes[handle.index()];
+            branch = match key[depth].cmp(&node.byte) {
+                Ordering::Less => LO,
+                Ordering::Greater => HI,
+                Ordering::Equal => {
+                    depth += 1;
+                    if depth == key.len() {
+                        break;
+                    }
+                    EQ
+                }
+            };
+            parent = handle;
+            handle = node.children[branch];
+        }
+        if let Some(id) = self.nodes[handle.index()].id {
+            return Ok(id);
+        }
+        let id = self.new_id(handle)?;
+        self.nodes[handle.index()].id = Some(id);
+        Ok(id)
+    }
+
+    /// `push_node` adds a node for `byte` as the child
+    /// of `parent` on the given branch.
+    fn push_node(&mut self, byte: u8, parent: NodeHandle, branch: usize) -> NodeHandle {
+        let handle = NodeHandle::new(self.nodes.len());
+        self.nodes.push(TstNode::new(byte, parent));
+        if parent.is_nil() {
+            self.root = handle;
+        } else {
+            self.nodes[parent.index()].children[branch] = handle;
+        }
+        handle
+    }
+
+    /// `new_id` hands out the next identifier for the key ending at `handle`.
+    fn new_id(&mut self, handle: NodeHandle) -> Result<S, Exhausted> {
+        let id = S::from_usize(self.ids.len()).ok_or(Exhausted)?;
+        self.ids.push(handle);
+        Ok(id)
+    }
+
+    /// `resolve` rebuilds the key for `id` by following parent links
+    /// up to the root. A node's byte belongs to the key only when
+    /// the walk arrives at it from its equal child.
+    pub fn resolve(&self, id: S) -> Option<String> {
+        if self.contains_empty == Some(id) {
+            return Some(String::new());
+        }
+        let mut handle = *self.ids.get(id.to_usize())?;
+        let mut bytes = vec![self.nodes[handle.index()].byte];
+        loop {
+            let parent = self.nodes[handle.index()].parent;
+            if parent.is_nil() {
+                break;
+            }
+            let node = &self.nodes[parent.index()];
+            if node.children[EQ] == handle {
+                bytes.push(node.byte);
+            }
+            handle = parent;
+        }
+        bytes.reverse();
+        Some(into_string(bytes))
+    }
+
+    /// `iter` yields every key with its identifier, in byte order.
+    pub fn iter(&self) -> Iter<'_, S> {
+        Iter::new(&self.nodes, Vec::new(), self.contains_empty, self.root)
+    }
+
+    /// `prefix_iter` yields every key starting with `prefix`,
+    /// including `prefix` itself, in byte order.
+    pub fn prefix_iter(&self, prefix: String) -> Iter<'_, S> {
+        if prefix.is_empty() {
+            return self.iter();
+        }
+        match self.find(prefix.as_bytes()) {
+            Some(handle) => {
+                let node = &self.nodes[handle.index()];
+                Iter::new(&self.nodes, prefix.into_bytes(), node.id, node.children[EQ])
+            }
+            None => Iter::new(&self.nodes, Vec::new(), None, NodeHandle::NIL),
+        }
+    }
+
+    /// `longest_prefix` is the longest key which `s` starts with, along
+    /// with its identifier, or `None` if no key is a prefix of `s`.
+    pub fn longest_prefix(&self, s: String) -> Option<(String, S)> {
+        let key = s.as_bytes();
+        let mut longest = self.contains_empty.map(|id| (0, id));
+        let mut handle = self.root;
+        let mut depth = 0;
+        while !handle.is_nil() && depth < key.len() {
+            let node = &self.nodes[handle.index()];
+            handle = match key[depth].cmp(&node.byte) {
+                Ordering::Less => node.children[LO],
+                Ordering::Greater => node.children[HI],
+                Ordering::Equal => {
+                    depth += 1;
+                    if let Some(id) = node.id {
+                        longest = Some((depth, id));
+                    }
+                    node.children[EQ]
+                }
+            };
+        }
+        longest.map(|(len, id)| (into_string(key[..len].to_vec()), id))
+    }
+
+    /// `size_of` is the total memory used by this trie,
+    /// including the struct itself and everything on the heap.
+    pub fn size_of(&self) -> usize {
+        size_of::<Self>() + self.heap_size().total()
+    }
+
+    /// `heap_size` breaks down the heap memory owned by this trie.
+    /// Each byte of a key sits in a node, so no label bytes are
+    /// counted apart from the nodes. The identifier table counts as
+    /// container overhead.
+    pub fn heap_size(&self) -> MemoryUsage {
+        let node_size = size_of::<TstNode<S>>();
+        let handle_size = size_of::<NodeHandle>();
+        MemoryUsage {
+            nodes: self.nodes.len() * node_size,
+            labels: 0,
+            child_containers: self.ids.len() * handle_size,
+            wasted_capacity: (self.nodes.capacity() - self.nodes.len()) * node_size
+                + (self.ids.capacity() - self.ids.len()) * handle_size,
+        }
+    }
+}
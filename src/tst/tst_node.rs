@@ -0,0 +1,30 @@
+use crate::dtrie::node_handle::NodeHandle;
+
+/// `LO`, `EQ` and `HI` index a node's children: the subtrees whose
+/// byte at this depth sorts before, equal to, or after the node's byte.
+pub(crate) const LO: usize = 0;
+pub(crate) const EQ: usize = 1;
+pub(crate) const HI: usize = 2;
+
+/// `TstNode` holds a single byte of some keys. A key passes through the
+/// node's equal child once it has matched the byte, and through the low
+/// or high child when it hasn't.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct TstNode<S> {
+    pub byte: u8,
+    /// `id` is set if a key ends at this node.
+    pub id: Option<S>,
+    pub children: [NodeHandle; 3],
+    pub parent: NodeHandle,
+}
+
+impl<S> TstNode<S> {
+    pub fn new(byte: u8, parent: NodeHandle) -> Self {
+        Self {
+            byte,
+            id: None,
+            children: [NodeHandle::NIL; 3],
+            parent,
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use proptest::collection::vec;
+use proptest::prelude::*;
+use sdtrie::art::AdaptiveRadixTrie;
+use std::collections::BTreeMap;
+
+#[test]
+fn test_layouts_grow_and_shrink() {
+    let mut trie: AdaptiveRadixTrie = AdaptiveRadixTrie::new();
+    let keys: Vec<String> = (0..100u8).map(|b| format!("{}x", b as char)).collect();
+    let ids: Vec<_> = keys
+        .iter()
+        .map(|key| trie.get_or_intern(key.clone()))
+        .collect();
+    // The root holds the 100 leaves.
+    assert_eq!(trie.count_layouts(), [100, 0, 0, 1]);
+    let mut left = keys.len();
+    for (target, layouts) in [
+        (41, [41, 0, 0, 1]),
+        (40, [40, 0, 1, 0]),
+        (12, [12, 1, 0, 0]),
+        (3, [4, 0, 0, 0]),
+    ]
+    .iter()
+    {
+        while left > *target {
+            left -= 1;
+            let removed = trie.remove(keys[left].clone()).unwrap();
+            assert_eq!(removed.id, ids[left]);
+        }
+        assert_eq!(trie.count_layouts(), *layouts);
+        for (key, id) in keys.iter().zip(ids.iter()).take(left) {
+            assert_eq!(trie.get(key.clone()), Some(*id));
+        }
+    }
+    assert_eq!(trie.len(), 3);
+    assert_eq!(trie.remove(keys[50].clone()), None);
+    assert!(!trie.contains_id(ids[50]));
+}
+
+#[test]
+fn test_removal_restores_shape() {
+    let mut trie: AdaptiveRadixTrie = ["fizz", "fang"].iter().collect();
+    let nodes = trie.count_nodes();
+    trie.get_or_intern(String::from("fa"));
+    trie.get_or_intern(String::from("fizzy"));
+    assert_eq!(trie.count_nodes(), nodes + 2);
+    trie.remove(String::from("fa"));
+    trie.remove(String::from("fizzy"));
+    assert_eq!(trie.count_nodes(), nodes);
+    let keys: Vec<String> = trie.iter().map(|(key, _)| key).collect();
+    assert_eq!(keys, ["fang", "fizz"]);
+    // Emptying the trie drops the root too.
+    trie.get_or_intern(String::new());
+    for key in ["", "fang", "fizz"].iter() {
+        trie.remove(key.to_string());
+    }
+    assert_eq!(trie.count_nodes(), 0);
+    assert_eq!(trie.iter().count(), 0);
+    let id = trie.get_or_intern(String::from("fa"));
+    assert_eq!(trie.resolve(id), Some(String::from("fa")));
+}
+
+proptest! {
+    #[test]
+    fn test_matches_a_map(ops in vec((any::<bool>(), "[a-zA-Z0-9]{0,3}"), 0..300)) {
+        let mut trie: AdaptiveRadixTrie = AdaptiveRadixTrie::new();
+        let mut model = BTreeMap::new();
+        for (insert, key) in ops {
+            if insert {
+                let id = trie.get_or_intern(key.clone());
+                prop_assert_eq!(*model.entry(key).or_insert(id), id);
+            } else {
+                let removed = trie.remove(key.clone()).map(|removed| removed.id);
+                prop_assert_eq!(removed, model.remove(&key));
+            }
+        }
+        prop_assert_eq!(trie.len(), model.len());
+        let keys: Vec<(String, _)> = model.iter().map(|(key, id)| (key.clone(), *id)).collect();
+        prop_assert_eq!(trie.iter().collect::<Vec<_>>(), keys);
+        for (key, id) in model.iter() {
+            prop_assert_eq!(trie.resolve(*id), Some(key.clone()));
+        }
+        // Removal leaves the trie as if the keys had never been added.
+        let fresh: AdaptiveRadixTrie = model.keys().collect();
+        prop_assert_eq!(trie.count_nodes(), fresh.count_nodes());
+    }
+}
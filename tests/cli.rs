@@ -0,0 +1,68 @@
+use std::env;
+use std::fs;
+use std::process::Command;
+
+fn sdtrie(args: &[&str]) -> (bool, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_sdtrie"))
+        .args(args)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    (output.status.success(), stdout)
+}
+
+#[test]
+fn test_build_freeze_and_query() {
+    let dir = env::temp_dir().join(format!("sdtrie-cli-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let words = dir.join("words.txt");
+    let dynamic = dir.join("words.trie");
+    let frozen = dir.join("words.strie");
+    fs::write(&words, "foo\nfood\nfang\nbar\n").unwrap();
+    let words = words.to_str().unwrap();
+    let dynamic = dynamic.to_str().unwrap();
+    let frozen = frozen.to_str().unwrap();
+
+    assert!(sdtrie(&["build", words, dynamic]).0);
+    assert!(sdtrie(&["freeze", dynamic, frozen]).0);
+    for trie in [dynamic, frozen].iter() {
+        let (ok, id) = sdtrie(&["get", trie, "food"]);
+        assert!(ok);
+        assert_eq!(id, "1\n");
+        let (ok, key) = sdtrie(&["resolve", trie, id.trim()]);
+        assert!(ok);
+        assert_eq!(key, "food\n");
+        let (ok, listing) = sdtrie(&["prefix", trie, "fo"]);
+        assert!(ok);
+        assert_eq!(listing.lines().count(), 2);
+        let (ok, stats) = sdtrie(&["stats", trie]);
+        assert!(ok);
+        assert!(stats.contains("Nodes:\t\t\t5"));
+        assert!(!sdtrie(&["get", trie, "fo"]).0);
+    }
+    let (ok, dot) = sdtrie(&["dot", dynamic]);
+    assert!(ok && dot.starts_with("digraph"));
+    assert!(!sdtrie(&["dot", frozen]).0);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_build_keeps_whitespace() {
+    let dir = env::temp_dir().join(format!("sdtrie-cli-ws-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let words = dir.join("words.txt");
+    let dynamic = dir.join("words.trie");
+    fs::write(&words, " lead\ntrail \r\nplain\n").unwrap();
+    let words = words.to_str().unwrap();
+    let dynamic = dynamic.to_str().unwrap();
+
+    assert!(sdtrie(&["build", words, dynamic]).0);
+    for key in [" lead", "trail ", "plain"].iter() {
+        assert!(sdtrie(&["get", dynamic, key]).0);
+    }
+    assert!(!sdtrie(&["get", dynamic, "lead"]).0);
+    assert!(!sdtrie(&["get", dynamic, "trail"]).0);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,103 @@
+use proptest::collection::vec;
+use proptest::prelude::*;
+use sdtrie::dtrie::{IdPolicy, DLB};
+use std::collections::BTreeMap;
+
+#[test]
+fn test_top_completions() {
+    let mut dlb = DLB::new();
+    let scores = [
+        ("car", 40),
+        ("card", 90),
+        ("care", 10),
+        ("careful", 70),
+        ("cat", 90),
+        ("dog", 100),
+        ("ca", 0),
+    ];
+    for (key, score) in scores.iter() {
+        let id = dlb.get_or_intern(key.to_string());
+        assert!(dlb.set_score(id, *score));
+    }
+    let keys = |completions: Vec<(String, _, u64)>| -> Vec<(String, u64)> {
+        completions
+            .into_iter()
+            .map(|(key, _, score)| (key, score))
+            .collect()
+    };
+    let expected = |pairs: &[(&str, u64)]| -> Vec<(String, u64)> {
+        pairs.iter().map(|(k, s)| (k.to_string(), *s)).collect()
+    };
+    assert_eq!(
+        keys(dlb.top_k_completions(String::from("ca"), 3)),
+        expected(&[("card", 90), ("cat", 90), ("careful", 70)])
+    );
+    assert_eq!(
+        keys(dlb.top_k_completions(String::from("car"), 10)),
+        expected(&[("card", 90), ("careful", 70), ("car", 40), ("care", 10)])
+    );
+    assert_eq!(
+        keys(dlb.top_k_completions(String::new(), 1)),
+        expected(&[("dog", 100)])
+    );
+    assert!(dlb.top_k_completions(String::from("x"), 3).is_empty());
+
+    // Lowering a score or removing a key takes it out of the running.
+    let card = dlb.get(String::from("card")).unwrap();
+    dlb.set_score(card, 5);
+    assert_eq!(dlb.score(card), Some(5));
+    let cat = dlb.remove(String::from("cat")).unwrap().id;
+    assert_eq!(
+        keys(dlb.top_k_completions(String::from("ca"), 2)),
+        expected(&[("careful", 70), ("car", 40)])
+    );
+    assert!(!dlb.set_score(cat, 1));
+    assert_eq!(dlb.score(cat), None);
+}
+
+proptest! {
+    #[test]
+    fn test_completions_match_a_map(
+        ops in vec((0u8..3, "[ab]{0,4}", 0u64..5), 0..80),
+        prefix in "[ab]{0,2}",
+        k in 0usize..6,
+    ) {
+        let mut dlb: DLB = DLB::with_policy(IdPolicy::Dense);
+        let mut model: BTreeMap<String, u64> = BTreeMap::new();
+        for (op, key, score) in ops {
+            match op {
+                0 => {
+                    let id = dlb.get_or_intern(key.clone());
+                    dlb.set_score(id, score);
+                    model.insert(key, score);
+                }
+                1 => {
+                    dlb.remove(key.clone());
+                    model.remove(&key);
+                }
+                _ => {
+                    dlb.get_or_intern(key.clone());
+                    model.entry(key).or_insert(0);
+                }
+            }
+        }
+        let mut bytes = Vec::new();
+        dlb.write_to(&mut bytes).unwrap();
+        let read: DLB = DLB::read_from(&mut bytes.as_slice()).unwrap();
+
+        let mut expected: Vec<(String, u64)> = model
+            .into_iter()
+            .filter(|(key, _)| key.starts_with(&prefix))
+            .collect();
+        expected.sort_by(|(a, x), (b, y)| y.cmp(x).then(a.cmp(b)));
+        expected.truncate(k);
+        for trie in [&dlb, &read].iter() {
+            let found: Vec<(String, u64)> = trie
+                .top_k_completions(prefix.clone(), k)
+                .into_iter()
+                .map(|(key, _, score)| (key, score))
+                .collect();
+            prop_assert_eq!(&found, &expected);
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use proptest::collection::vec;
+use proptest::prelude::*;
+use sdtrie::dtrie::{IdPolicy, Lowercase, DLB};
+use std::collections::BTreeMap;
+
+fn tokens() -> Vec<&'static str> {
+    "the cat sat on the mat the cat ran to the hat then the man sat"
+        .split(' ')
+        .collect()
+}
+
+#[test]
+fn test_counts_occurrences() {
+    let mut dlb = DLB::new();
+    for token in tokens() {
+        dlb.intern_counted(token.to_string());
+    }
+    let plain = dlb.get_or_intern(String::from("thesaurus"));
+    assert_eq!(dlb.count(String::from("the")), 5);
+    assert_eq!(dlb.count(String::from("cat")), 2);
+    assert_eq!(dlb.count(String::from("thesaurus")), 0);
+    assert_eq!(dlb.count(String::from("th")), 0);
+    assert_eq!(dlb.count(String::from("dog")), 0);
+
+    assert_eq!(dlb.prefix_count(String::from("th")), 6);
+    assert_eq!(dlb.prefix_count(String::from("the")), 6);
+    assert_eq!(dlb.prefix_count(String::from("ma")), 2);
+    assert_eq!(dlb.prefix_count(String::from("x")), 0);
+    assert_eq!(dlb.prefix_count(String::new()), tokens().len() as u64);
+
+    let top: Vec<(String, u64)> = dlb
+        .top_k(4)
+        .into_iter()
+        .map(|(key, _, count)| (key, count))
+        .collect();
+    let expected = [("the", 5), ("cat", 2), ("sat", 2), ("hat", 1)];
+    let expected: Vec<(String, u64)> = expected.iter().map(|(k, c)| (k.to_string(), *c)).collect();
+    assert_eq!(top, expected);
+    // Asking for more keys than there are returns all of them.
+    let all = dlb.top_k(100);
+    assert_eq!(all.len(), dlb.len());
+    assert_eq!(all.last().map(|(_, id, _)| *id), Some(plain));
+
+    dlb.remove(String::from("the"));
+    assert_eq!(dlb.prefix_count(String::from("th")), 1);
+    assert_eq!(dlb.top_k(1)[0].0, "cat");
+}
+
+#[test]
+fn test_counts_survive_saving() {
+    let mut dlb: DLB = DLB::with_normalizer(Lowercase);
+    for token in ["", "A", "a", "B", ""].iter() {
+        dlb.intern_counted(token.to_string());
+    }
+    let mut bytes = Vec::new();
+    dlb.write_to(&mut bytes).unwrap();
+    let mut read: DLB = DLB::read_from(&mut bytes.as_slice()).unwrap();
+    read.set_normalizer(Lowercase);
+    assert_eq!(read.count(String::new()), 2);
+    assert_eq!(read.count(String::from("A")), 2);
+    assert_eq!(read.prefix_count(String::new()), 5);
+    assert_eq!(read.top_k(2), dlb.top_k(2));
+    assert_eq!(read.top_k(1)[0].0, "");
+}
+
+proptest! {
+    #[test]
+    fn test_counts_match_a_map(
+        dense in any::<bool>(),
+        ops in vec((any::<bool>(), "[ab]{0,4}"), 0..80),
+        k in 0usize..8,
+    ) {
+        let policy = if dense { IdPolicy::Dense } else { IdPolicy::Sequential };
+        let mut dlb: DLB = DLB::with_policy(policy);
+        let mut model: BTreeMap<String, u64> = BTreeMap::new();
+        for (insert, key) in ops {
+            if insert {
+                dlb.intern_counted(key.clone());
+                *model.entry(key).or_insert(0) += 1;
+            } else {
+                dlb.remove(key.clone());
+                model.remove(&key);
+            }
+        }
+        for prefix in ["", "a", "ab", "b", "ba", "bab"].iter() {
+            let expected: u64 = model
+                .iter()
+                .filter(|(key, _)| key.starts_with(prefix))
+                .map(|(_, count)| count)
+                .sum();
+            prop_assert_eq!(dlb.prefix_count(prefix.to_string()), expected);
+        }
+        for (key, count) in model.iter() {
+            prop_assert_eq!(dlb.count(key.clone()), *count);
+        }
+        let mut expected: Vec<(String, u64)> = model.into_iter().collect();
+        expected.sort_by(|(a, x), (b, y)| y.cmp(x).then(a.cmp(b)));
+        expected.truncate(k);
+        let top: Vec<(String, u64)> = dlb
+            .top_k(k)
+            .into_iter()
+            .map(|(key, _, count)| (key, count))
+            .collect();
+        prop_assert_eq!(top, expected);
+    }
+}
@@ -0,0 +1,75 @@
+use sdtrie::dawg::{Dawg, DawgBuilder};
+use sdtrie::dtrie::{BuildError, Identifier, Symbol, DLB};
+use std::fs::File;
+use std::io::BufRead;
+use std::io::BufReader;
+
+#[test]
+fn test_suffixes_are_shared() {
+    let words = ["", "tap", "taps", "top", "tops"];
+    let dawg: Dawg = Dawg::from_sorted_iter(&words).unwrap();
+    // The root, then t, ta|to, tap|top, taps|tops.
+    assert_eq!(dawg.count_states(), 5);
+    assert_eq!(dawg.count_edges(), 5);
+    for (rank, word) in words.iter().enumerate() {
+        let id = dawg.get(word.to_string()).unwrap();
+        assert_eq!(id.to_usize(), rank);
+        assert_eq!(dawg.resolve(id).as_deref(), Some(*word));
+    }
+    assert!(!dawg.contains(String::from("t")));
+    assert!(!dawg.contains(String::from("tapss")));
+    assert_eq!(dawg.resolve(Identifier::from_usize(5).unwrap()), None);
+}
+
+#[test]
+fn test_builder_rejects_unsorted_keys() {
+    let mut builder = DawgBuilder::new();
+    assert_eq!(builder.push("b").map(|id| id.to_usize()), Ok(0));
+    assert_eq!(builder.push("a"), Err(BuildError::Unsorted(1)));
+    assert_eq!(builder.push("b"), Err(BuildError::Duplicate(1)));
+    assert_eq!(builder.push("bc").map(|id| id.to_usize()), Ok(1));
+    let dawg = builder.finish();
+    assert_eq!(dawg.len(), 2);
+    assert!(dawg.contains(String::from("bc")));
+
+    let empty: Dawg = Dawg::from_sorted_iter(Vec::<String>::new()).unwrap();
+    assert!(empty.is_empty());
+    assert!(!empty.contains(String::new()));
+}
+
+#[test]
+fn test_dawg_dictionary() {
+    let f = File::open("dictionaries/alphanumeric.txt").unwrap();
+    let file = BufReader::new(&f);
+    let words: Vec<String> = file
+        .lines()
+        .map(|line| line.unwrap().trim().to_owned())
+        .collect();
+    let dlb: DLB = DLB::from_sorted_iter(&words).unwrap();
+    let dawg = Dawg::from(&dlb);
+    let (trie, _) = dlb.freeze_ranked();
+    assert_eq!(dawg.len(), words.len());
+    // Sharing suffixes makes for a much smaller dictionary.
+    assert!(dawg.size_of() * 4 < trie.size_of());
+    assert_eq!(dawg.heap_size().wasted_capacity, 0);
+    for word in words.iter().step_by(5) {
+        let id = dawg.get(word.clone()).unwrap();
+        assert_eq!(Some(id), trie.get(word.clone()));
+        assert_eq!(dawg.resolve(id).as_ref(), Some(word));
+        let mut misspelt = word.clone();
+        misspelt.push('#');
+        assert!(!dawg.contains(misspelt));
+    }
+
+    let mut bytes = Vec::new();
+    dawg.write_to(&mut bytes).unwrap();
+    let read = Dawg::<Identifier>::read_from(&mut bytes.as_slice()).unwrap();
+    assert_eq!(read.count_states(), dawg.count_states());
+    for word in words.iter().step_by(97) {
+        assert_eq!(read.get(word.clone()), dawg.get(word.clone()));
+    }
+    // An edge back up to the root would make a cycle.
+    let last = bytes.len() - 4;
+    bytes[last..].copy_from_slice(&(dawg.count_states() as u32 - 1).to_le_bytes());
+    assert!(Dawg::<Identifier>::read_from(&mut bytes.as_slice()).is_err());
+}
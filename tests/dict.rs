@@ -1,7 +1,13 @@
 use rand::seq::SliceRandom;
 use rand_chacha::ChaCha8Rng;
 use rand_core::SeedableRng;
-use sdtrie::dtrie::DLB;
+use sdtrie::art::AdaptiveRadixTrie;
+use sdtrie::dawg::Dawg;
+use sdtrie::dtrie::{BuildError, IdPolicy, Sym16, Sym32, Symbol, DLB};
+use sdtrie::fcd::FrontCodedDict;
+use sdtrie::strie::StaticTrie;
+use sdtrie::trie::{Interner, TrieRead};
+use sdtrie::tst::TernarySearchTrie;
 use std::fs::File;
 use std::io::BufRead;
 use std::io::BufReader;
@@ -11,7 +17,7 @@ fn test_count_nodes1() {
     let mut dlb = DLB::new();
     vec!["foo", "bar", "pop", "lizz"]
         .into_iter()
-        .map(|s| String::from(s))
+        .map(String::from)
         .for_each(|s| {
             dlb.get_or_intern(s);
         });
@@ -24,7 +30,7 @@ fn test_count_nodes2() {
     let mut dlb = DLB::new();
     vec!["fizz", "fang"]
         .into_iter()
-        .map(|s| String::from(s))
+        .map(String::from)
         .for_each(|s| {
             dlb.get_or_intern(s);
         });
@@ -37,7 +43,7 @@ fn test_count_nodes3() {
     let mut dlb = DLB::new();
     vec!["foo", "fuh", "fang"]
         .into_iter()
-        .map(|s| String::from(s))
+        .map(String::from)
         .for_each(|s| {
             dlb.get_or_intern(s);
         });
@@ -50,7 +56,7 @@ fn test_count_nodes4() {
     let mut dlb = DLB::new();
     vec!["foo", "fuh", "fizz", "fang"]
         .into_iter()
-        .map(|s| String::from(s))
+        .map(String::from)
         .for_each(|s| {
             dlb.get_or_intern(s);
         });
@@ -58,22 +64,61 @@ fn test_count_nodes4() {
     assert_eq!(count, 5);
 }
 
+/// `for_each_interner` runs a check, generic over `Interner + Default`,
+/// against every trie which can intern keys.
+macro_rules! for_each_interner {
+    ($check:ident $(, $arg:expr)*) => {
+        $check::<DLB>($($arg),*);
+        $check::<TernarySearchTrie>($($arg),*);
+        $check::<AdaptiveRadixTrie>($($arg),*);
+    };
+}
+
+/// `for_each_reader` builds every trie, dynamic or static, from `words`,
+/// and runs a check, generic over `TrieRead`, against each of them. The
+/// static forms are frozen from the `DLB`, so they hand out ranks.
+macro_rules! for_each_reader {
+    ($words:expr, $check:ident) => {
+        let words: &[String] = $words;
+        let dlb: DLB = intern_all(words);
+        let tst: TernarySearchTrie = intern_all(words);
+        let art: AdaptiveRadixTrie = intern_all(words);
+        $check(&dlb, words);
+        $check(&tst, words);
+        $check(&art, words);
+        $check(&StaticTrie::from(&dlb), words);
+        $check(&FrontCodedDict::from(&dlb), words);
+        $check(&Dawg::from(&dlb), words);
+    };
+}
+
+fn intern_all<T: Interner + Default>(words: &[String]) -> T {
+    let mut trie = T::default();
+    for word in words.iter() {
+        trie.get_or_intern(word.clone());
+    }
+    trie
+}
+
+fn to_strings(words: &[&str]) -> Vec<String> {
+    words.iter().map(|word| word.to_string()).collect()
+}
+
+/// `check_resolves` interns `words` in order, then
+/// resolves each of them from its identifier.
+fn check_resolves<T: Interner + Default>(words: &[&str]) {
+    let mut trie = T::default();
+    let ids: Vec<_> = words
+        .iter()
+        .map(|word| trie.get_or_intern(word.to_string()))
+        .collect();
+    for (word, id) in words.iter().zip(ids) {
+        assert_eq!(trie.resolve(id), Some(word.to_string()));
+    }
+}
+
 #[test]
 fn test_broken_case1() {
-    let mut dlb = DLB::new();
-    let expected1 = String::from("1080");
-    let expected2 = String::from("10-point");
-    let expected3 = String::from("10th");
-
-    let id1 = dlb.get_or_intern(expected1.clone());
-    let id2 = dlb.get_or_intern(expected2.clone());
-    let id3 = dlb.get_or_intern(expected3.clone());
-    let observed1 = dlb.resolve(id1);
-    let observed2 = dlb.resolve(id2);
-    let observed3 = dlb.resolve(id3);
-    assert_eq!(Some(expected1), observed1);
-    assert_eq!(Some(expected2), observed2);
-    assert_eq!(Some(expected3), observed3);
     // Expecting:
     // (10) -> (80)
     // (10) -> (-point)
@@ -81,170 +126,87 @@ fn test_broken_case1() {
     //
     // Observed:
     // (10) -> (thth80)
+    for_each_interner!(check_resolves, &["1080", "10-point", "10th"]);
 }
 
 #[test]
 fn test_aaa_case1() {
-    let mut dlb = DLB::new();
-    let expected1 = String::from("a");
-    let expected2 = String::from("aa");
-    let expected3 = String::from("aaa");
-
-    let id1 = dlb.get_or_intern(expected1.clone());
-    let id2 = dlb.get_or_intern(expected2.clone());
-    let id3 = dlb.get_or_intern(expected3.clone());
-    let observed1 = dlb.resolve(id1);
-    let observed2 = dlb.resolve(id2);
-    let observed3 = dlb.resolve(id3);
-    assert_eq!(Some(expected1), observed1);
-    assert_eq!(Some(expected2), observed2);
-    assert_eq!(Some(expected3), observed3);
+    for_each_interner!(check_resolves, &["a", "aa", "aaa"]);
 }
 
 #[test]
 fn test_aaa_case2() {
-    let mut dlb = DLB::new();
-    let expected1 = String::from("a");
-    let expected2 = String::from("aaa");
-    let expected3 = String::from("aa");
-
-    let id1 = dlb.get_or_intern(expected1.clone());
-    let id2 = dlb.get_or_intern(expected2.clone());
-    let id3 = dlb.get_or_intern(expected3.clone());
-    let observed1 = dlb.resolve(id1);
-    let observed2 = dlb.resolve(id2);
-    let observed3 = dlb.resolve(id3);
-    assert_eq!(Some(expected1), observed1);
-    assert_eq!(Some(expected2), observed2);
-    assert_eq!(Some(expected3), observed3);
+    for_each_interner!(check_resolves, &["a", "aaa", "aa"]);
 }
 
 #[test]
 fn test_aaa_case3() {
-    let mut dlb = DLB::new();
-    let expected1 = String::from("aa");
-    let expected2 = String::from("a");
-    let expected3 = String::from("aaa");
-
-    let id1 = dlb.get_or_intern(expected1.clone());
-    let id2 = dlb.get_or_intern(expected2.clone());
-    let id3 = dlb.get_or_intern(expected3.clone());
-    let observed1 = dlb.resolve(id1);
-    let observed2 = dlb.resolve(id2);
-    let observed3 = dlb.resolve(id3);
-    assert_eq!(Some(expected1), observed1);
-    assert_eq!(Some(expected2), observed2);
-    assert_eq!(Some(expected3), observed3);
+    for_each_interner!(check_resolves, &["aa", "a", "aaa"]);
 }
 
 #[test]
 fn test_aaa_case4() {
-    let mut dlb = DLB::new();
-    let expected1 = String::from("aa");
-    let expected2 = String::from("aaa");
-    let expected3 = String::from("a");
-
-    let id1 = dlb.get_or_intern(expected1.clone());
-    let id2 = dlb.get_or_intern(expected2.clone());
-    let id3 = dlb.get_or_intern(expected3.clone());
-    let observed1 = dlb.resolve(id1);
-    let observed2 = dlb.resolve(id2);
-    let observed3 = dlb.resolve(id3);
-    assert_eq!(Some(expected1), observed1);
-    assert_eq!(Some(expected2), observed2);
-    assert_eq!(Some(expected3), observed3);
+    for_each_interner!(check_resolves, &["aa", "aaa", "a"]);
 }
 
 #[test]
 fn test_aaa_case5() {
-    let mut dlb = DLB::new();
-    let expected1 = String::from("aaa");
-    let expected2 = String::from("a");
-    let expected3 = String::from("aa");
-
-    let id1 = dlb.get_or_intern(expected1.clone());
-    let id2 = dlb.get_or_intern(expected2.clone());
-    let id3 = dlb.get_or_intern(expected3.clone());
-    let observed1 = dlb.resolve(id1);
-    let observed2 = dlb.resolve(id2);
-    let observed3 = dlb.resolve(id3);
-    assert_eq!(Some(expected1), observed1);
-    assert_eq!(Some(expected2), observed2);
-    assert_eq!(Some(expected3), observed3);
+    for_each_interner!(check_resolves, &["aaa", "a", "aa"]);
 }
 
 #[test]
 fn test_aaa_case6() {
-    let mut dlb = DLB::new();
-    let expected1 = String::from("aaa");
-    let expected2 = String::from("aa");
-    let expected3 = String::from("a");
-
-    let id1 = dlb.get_or_intern(expected1.clone());
-    let id2 = dlb.get_or_intern(expected2.clone());
-    let id3 = dlb.get_or_intern(expected3.clone());
-    let observed1 = dlb.resolve(id1);
-    let observed2 = dlb.resolve(id2);
-    let observed3 = dlb.resolve(id3);
-    assert_eq!(Some(expected1), observed1);
-    assert_eq!(Some(expected2), observed2);
-    assert_eq!(Some(expected3), observed3);
+    for_each_interner!(check_resolves, &["aaa", "aa", "a"]);
 }
 
-#[test]
-fn test_contains_dictionary() {
+/// `read_dictionary` reads the first `count` words of the
+/// dictionary, shuffled.
+fn read_dictionary(count: usize) -> Vec<String> {
     let mut rng: ChaCha8Rng = SeedableRng::seed_from_u64(100);
-    let mut dlb = DLB::new();
     let f = File::open("dictionaries/alphanumeric.txt").unwrap();
     let file = BufReader::new(&f);
-    let mut words = Vec::with_capacity(466551);
+    let mut words = Vec::with_capacity(count);
     println!("Reading file.");
-    for line in file.lines().take(22000) {
+    for line in file.lines().take(count) {
         let content = line.unwrap().trim().to_owned();
         words.push(content);
     }
-
     words.as_mut_slice().shuffle(&mut rng);
+    words
+}
 
-    for val in words.iter() {
-        dlb.get_or_intern(val.to_string());
-    }
-
+fn check_contains<T: TrieRead>(trie: &T, words: &[String]) {
     for (idx, word) in words.iter().enumerate() {
         let expected = true;
-        let observed = dlb.contains(word.to_string());
+        let observed = trie.contains(word.to_string());
         if expected != observed {
             println!("Failed to recover word \"{}\" on step {}", word, idx);
         }
         assert_eq!(expected, observed);
+        // A key is its own longest prefix, whatever follows it.
+        let (key, id) = trie.longest_prefix(format!("{}\n", word)).unwrap();
+        assert_eq!(key, *word);
+        assert_eq!(trie.get(key), Some(id));
     }
 }
 
 #[test]
-fn test_reads_dictionary() {
-    let mut rng: ChaCha8Rng = SeedableRng::seed_from_u64(100);
-    let mut dlb = DLB::new();
-    let f = File::open("dictionaries/alphanumeric.txt").unwrap();
-    let file = BufReader::new(&f);
-    let mut words = Vec::with_capacity(466551);
-    println!("Reading file.");
-    for line in file.lines().take(22000) {
-        let content = line.unwrap().trim().to_owned();
-        println!("Reading line {}", content);
-        words.push(content);
-    }
-    words.as_mut_slice().shuffle(&mut rng);
+fn test_contains_dictionary() {
+    for_each_reader!(&read_dictionary(22000), check_contains);
+}
 
-    let mut tokens = Vec::with_capacity(466551);
+fn check_reads_dictionary<T: Interner + Default>(words: &[String]) {
+    let mut trie = T::default();
+    let mut tokens = Vec::with_capacity(words.len());
     for val in words.iter() {
-        let token = dlb.get_or_intern(val.to_string());
+        let token = trie.get_or_intern(val.to_string());
         tokens.push(token);
-        // println!("Gen token {}", token);
     }
+    assert_eq!(trie.len(), words.len());
 
     for (idx, token) in tokens.into_iter().enumerate() {
         let expected = Some(words[idx].clone());
-        let observed = dlb.resolve(token);
+        let observed = trie.resolve(token);
         if expected != observed {
             println!(
                 "Found \"{}\" with token {} when looking for \"{}\"",
@@ -256,3 +218,267 @@ fn test_reads_dictionary() {
         assert_eq!(expected, observed);
     }
 }
+
+#[test]
+fn test_reads_dictionary() {
+    for_each_interner!(check_reads_dictionary, &read_dictionary(22000));
+}
+
+fn check_prefix_iter<T: TrieRead>(trie: &T, _: &[String]) {
+    let observed: Vec<String> = trie
+        .prefix_iter(String::from("foo"))
+        .map(|(key, id)| {
+            assert_eq!(trie.get(key.clone()), Some(id));
+            key
+        })
+        .collect();
+    assert_eq!(observed, vec!["foo", "food", "fool"]);
+    // A prefix ending partway through an edge still finds its keys.
+    assert_eq!(trie.prefix_iter(String::from("fa")).count(), 1);
+    assert_eq!(trie.prefix_iter(String::from("fx")).count(), 0);
+    assert_eq!(trie.prefix_iter(String::new()).count(), 5);
+}
+
+#[test]
+fn test_prefix_iter() {
+    let words = to_strings(&["foo", "food", "fool", "fang", "bar"]);
+    for_each_reader!(&words, check_prefix_iter);
+}
+
+fn check_longest_prefix<T: TrieRead>(trie: &T, words: &[String]) {
+    let longest = |s: &str| trie.longest_prefix(s.to_string()).map(|(key, _)| key);
+    assert_eq!(longest("abx").as_deref(), Some("a"));
+    assert_eq!(longest("abcd").as_deref(), Some("abc"));
+    assert_eq!(longest("abc").as_deref(), Some("abc"));
+    assert_eq!(longest("b").as_deref(), Some(""));
+    assert_eq!(longest("").as_deref(), Some(""));
+    let (key, id) = trie.longest_prefix(String::from("abcd")).unwrap();
+    assert_eq!(trie.get(key), Some(id));
+    if words.contains(&String::from("日本")) {
+        assert_eq!(longest("日本語").as_deref(), Some("日本"));
+    }
+}
+
+fn check_no_prefix<T: TrieRead>(trie: &T, _: &[String]) {
+    assert_eq!(trie.longest_prefix(String::from("b")), None);
+    assert_eq!(trie.longest_prefix(String::new()), None);
+    assert!(trie.longest_prefix(String::from("ab")).is_some());
+}
+
+#[test]
+fn test_longest_prefix() {
+    let words = to_strings(&["", "a", "abc", "日本"]);
+    for_each_reader!(&words, check_longest_prefix);
+    let words = to_strings(&["a", "abc"]);
+    for_each_reader!(&words, check_no_prefix);
+}
+
+#[test]
+fn test_serialization_round_trip() {
+    let mut dlb = DLB::new();
+    let words = ["", "a", "aa", "aaa", "1080", "10-point", "10th"];
+    let ids: Vec<_> = words
+        .iter()
+        .map(|word| dlb.get_or_intern(word.to_string()))
+        .collect();
+
+    let mut buffer = Vec::new();
+    dlb.write_to(&mut buffer).unwrap();
+    let mut restored = DLB::read_from(&mut buffer.as_slice()).unwrap();
+
+    for (word, id) in words.iter().zip(ids.iter()) {
+        assert_eq!(restored.get(word.to_string()), Some(*id));
+        assert_eq!(restored.resolve(*id), Some(word.to_string()));
+    }
+    assert_eq!(restored.count_nodes(), dlb.count_nodes());
+    // Fresh identifiers continue where the original left off.
+    let next = restored.get_or_intern(String::from("b"));
+    assert!(ids.iter().all(|id| *id < next));
+}
+
+#[test]
+fn test_dot_output() {
+    let mut dlb = DLB::new();
+    dlb.get_or_intern(String::from("fizz"));
+    dlb.get_or_intern(String::from("fang"));
+    let mut out = Vec::new();
+    dlb.write_dot(&mut out).unwrap();
+    let dot = String::from_utf8(out).unwrap();
+    assert!(dot.starts_with("digraph DLB {"));
+    assert!(dot.contains("[label=\"f\"]"));
+    assert!(dot.contains("[label=\"izz\\n#0\", peripheries=2]"));
+    assert_eq!(dot.matches("->").count(), 3);
+}
+
+#[test]
+fn test_narrow_symbols() {
+    let mut dlb = DLB::<Sym32>::default();
+    let words = ["", "fizz", "fang", "foo", "food"];
+    let ids: Vec<Sym32> = words
+        .iter()
+        .map(|word| dlb.get_or_intern(word.to_string()))
+        .collect();
+    let indices: Vec<usize> = ids.iter().map(|id| id.to_usize()).collect();
+    assert_eq!(indices, vec![0, 1, 2, 3, 4]);
+
+    let mut buffer = Vec::new();
+    dlb.write_to(&mut buffer).unwrap();
+    let restored = DLB::<Sym32>::read_from(&mut buffer.as_slice()).unwrap();
+    let trie = restored.freeze();
+    for (word, id) in words.iter().zip(ids) {
+        assert_eq!(trie.get(word.to_string()), Some(id));
+        assert_eq!(trie.resolve(id), Some(word.to_string()));
+    }
+    // A file written with wide identifiers is rejected if they don't fit.
+    let mut wide = DLB::new();
+    for i in 0..=u16::MAX as u32 {
+        wide.get_or_intern(i.to_string());
+    }
+    let mut buffer = Vec::new();
+    wide.write_to(&mut buffer).unwrap();
+    assert!(DLB::<Sym16>::read_from(&mut buffer.as_slice()).is_err());
+}
+
+#[test]
+fn test_dense_removal_dictionary() {
+    let mut rng: ChaCha8Rng = SeedableRng::seed_from_u64(100);
+    let f = File::open("dictionaries/alphanumeric.txt").unwrap();
+    let file = BufReader::new(&f);
+    let mut words: Vec<String> = file
+        .lines()
+        .take(5000)
+        .map(|line| line.unwrap().trim().to_owned())
+        .collect();
+    words.shuffle(&mut rng);
+
+    let mut dlb: DLB = DLB::with_policy(IdPolicy::Dense);
+    // A side table indexed by identifier, kept in step with the trie.
+    let mut keys = Vec::new();
+    for word in words.iter() {
+        let id = dlb.get_or_intern(word.clone());
+        assert_eq!(id.to_usize(), keys.len());
+        keys.push(word.clone());
+    }
+    words.shuffle(&mut rng);
+    let (gone, kept) = words.split_at(words.len() / 2);
+    for word in gone.iter() {
+        let removed = dlb.remove(word.clone()).unwrap();
+        assert_eq!(keys.swap_remove(removed.id.to_usize()), *word);
+    }
+
+    assert_eq!(dlb.len(), kept.len());
+    for (index, key) in keys.iter().enumerate() {
+        let id = dlb.get(key.clone()).unwrap();
+        assert_eq!(id.to_usize(), index);
+        assert_eq!(dlb.resolve(id).as_ref(), Some(key));
+    }
+    for word in gone.iter() {
+        assert!(!dlb.contains(word.clone()));
+    }
+
+    let mut buffer = Vec::new();
+    dlb.write_to(&mut buffer).unwrap();
+    let mut restored: DLB = DLB::read_from(&mut buffer.as_slice()).unwrap();
+    assert_eq!(restored.policy(), IdPolicy::Dense);
+    assert_eq!(restored.len(), keys.len());
+    assert_eq!(restored.count_nodes(), dlb.count_nodes());
+    let id = restored.get_or_intern(gone[0].clone());
+    assert_eq!(id.to_usize(), keys.len());
+}
+
+#[test]
+fn test_recycled_churn() {
+    let mut dlb: DLB<Sym32> = DLB::with_policy(IdPolicy::Recycled);
+    let mut stale = Vec::new();
+    for round in 0..50 {
+        let ids: Vec<Sym32> = (0..100)
+            .map(|i| dlb.get_or_intern(format!("{}-{}", round, i)))
+            .collect();
+        for (i, id) in ids.into_iter().enumerate() {
+            dlb.remove(format!("{}-{}", round, i)).unwrap();
+            stale.push(id);
+        }
+    }
+    dlb.get_or_intern(String::from("survivor"));
+    assert_eq!(dlb.len(), 1);
+    assert!(stale.iter().all(|id| dlb.resolve(*id).is_none()));
+
+    let mut buffer = Vec::new();
+    dlb.write_to(&mut buffer).unwrap();
+    let mut restored = DLB::<Sym32>::read_from(&mut buffer.as_slice()).unwrap();
+    let id = restored.get_or_intern(String::from("newcomer"));
+    assert!(!stale.contains(&id));
+    assert_eq!(restored.resolve(id), Some(String::from("newcomer")));
+    assert!(stale.iter().all(|id| !restored.contains_id(*id)));
+}
+
+#[test]
+fn test_recycled_churn_reuses_memory() {
+    let mut dlb: DLB = DLB::with_policy(IdPolicy::Recycled);
+    let keys: Vec<String> = (0..10)
+        .map(|i| format!("{}-pneumonoultramicroscopicsilicovolcanoconiosis", i))
+        .collect();
+    let mut settled = None;
+    for round in 0..1000 {
+        for key in keys.iter() {
+            dlb.get_or_intern(key.clone());
+        }
+        // Once the first round has sized the buffers,
+        // every later round fits in the same memory.
+        let usage = dlb.heap_size();
+        if round > 0 {
+            assert_eq!(usage, *settled.get_or_insert(usage));
+        }
+        for key in keys.iter() {
+            dlb.remove(key.clone()).unwrap();
+        }
+        assert_eq!(dlb.heap_size().labels, 0);
+    }
+    assert_eq!(dlb.len(), 0);
+}
+
+#[test]
+fn test_from_sorted_dictionary() {
+    let f = File::open("dictionaries/alphanumeric.txt").unwrap();
+    let file = BufReader::new(&f);
+    let words: Vec<String> = file
+        .lines()
+        .take(22000)
+        .map(|line| line.unwrap().trim().to_owned())
+        .collect();
+    let built: DLB = DLB::from_sorted_iter(&words).unwrap();
+    let mut interned = DLB::new();
+    for word in words.iter() {
+        interned.get_or_intern(word.clone());
+    }
+    // Both ways of building hand out the same identifiers and end up
+    // with the same shape.
+    assert_eq!(built.len(), words.len());
+    assert_eq!(built.count_nodes(), interned.count_nodes());
+    assert_eq!(built.depth_histogram(), interned.depth_histogram());
+    assert!(built.iter().eq(interned.iter()));
+    for (index, word) in words.iter().enumerate() {
+        let id = built.get(word.clone()).unwrap();
+        assert_eq!(id.to_usize(), index);
+        assert_eq!(built.resolve(id).as_ref(), Some(word));
+    }
+}
+
+#[test]
+fn test_from_sorted_rejects_bad_input() {
+    let unsorted: Result<DLB, _> = DLB::from_sorted_iter(vec!["a", "c", "b"]);
+    assert_eq!(unsorted.err(), Some(BuildError::Unsorted(2)));
+    let duplicate: Result<DLB, _> = DLB::from_sorted_iter(vec!["", "a", "a"]);
+    assert_eq!(duplicate.err(), Some(BuildError::Duplicate(2)));
+    let small: Result<DLB<Sym16>, _> =
+        DLB::from_sorted_iter((0..=u16::MAX).map(|n| format!("{:05}", n)));
+    assert_eq!(small.err(), Some(BuildError::Exhausted));
+
+    // A trie built from sorted keys can still be changed afterwards.
+    let mut dlb: DLB = DLB::from_sorted_iter(vec!["", "ab", "abc", "abd", "b"]).unwrap();
+    assert_eq!(dlb.get(String::new()).map(|id| id.to_usize()), Some(0));
+    assert_eq!(dlb.get_or_intern(String::from("a")).to_usize(), 5);
+    assert!(dlb.remove(String::from("abc")).is_some());
+    let keys: Vec<String> = dlb.iter().map(|(key, _)| key).collect();
+    assert_eq!(keys, vec!["", "a", "ab", "abd", "b"]);
+}
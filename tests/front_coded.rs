@@ -0,0 +1,94 @@
+use sdtrie::dtrie::{BuildError, Identifier, Symbol, DLB};
+use sdtrie::fcd::FrontCodedDict;
+use std::fs::File;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::ErrorKind;
+
+#[test]
+fn test_every_bucket_size() {
+    let words = ["", "a", "ab", "abc", "abd", "b", "ba", "bab", "c"];
+    for bucket_size in 1..=words.len() + 1 {
+        let dict: FrontCodedDict = FrontCodedDict::from_sorted_iter(&words, bucket_size).unwrap();
+        assert_eq!(dict.len(), words.len());
+        for (rank, word) in words.iter().enumerate() {
+            let id = dict.get(word.to_string()).unwrap();
+            assert_eq!(id.to_usize(), rank);
+            assert_eq!(dict.resolve(id).as_deref(), Some(*word));
+        }
+        for missing in ["aa", "abe", "bb", "d", "0"].iter() {
+            assert_eq!(dict.get(missing.to_string()), None);
+        }
+        let keys: Vec<String> = dict.iter().map(|(key, _)| key).collect();
+        assert_eq!(keys, words);
+        assert_eq!(
+            dict.resolve(Identifier::from_usize(words.len()).unwrap()),
+            None
+        );
+    }
+}
+
+#[test]
+fn test_rejects_bad_input() {
+    let unsorted: Result<FrontCodedDict, _> = FrontCodedDict::from_sorted_iter(&["b", "a"], 4);
+    assert_eq!(unsorted.err(), Some(BuildError::Unsorted(1)));
+    let duplicate: Result<FrontCodedDict, _> = FrontCodedDict::from_sorted_iter(&["a", "a"], 4);
+    assert_eq!(duplicate.err(), Some(BuildError::Duplicate(1)));
+
+    let dict: FrontCodedDict = FrontCodedDict::from_sorted_iter(&["ab", "abc", "b"], 2).unwrap();
+    let mut bytes = Vec::new();
+    dict.write_to(&mut bytes).unwrap();
+    let read = FrontCodedDict::<Identifier>::read_from(&mut bytes.as_slice()).unwrap();
+    assert!(read.iter().eq(dict.iter()));
+    // Claiming "abc" shares three bytes with "ab" is caught.
+    let shared = bytes.len() - 5;
+    assert_eq!(bytes[shared], 2);
+    bytes[shared] = 3;
+    assert!(FrontCodedDict::<Identifier>::read_from(&mut bytes.as_slice()).is_err());
+}
+
+#[test]
+fn test_rejects_overlong_lengths() {
+    // A single key whose length never stops continuing, even though
+    // every group adds nothing to it.
+    let mut bytes = FrontCodedDict::MAGIC.to_vec();
+    bytes.extend_from_slice(&1u32.to_le_bytes());
+    bytes.extend_from_slice(&1u64.to_le_bytes());
+    let mut key = vec![0x80; 20];
+    key.extend_from_slice(&[0x01, b'a']);
+    bytes.extend_from_slice(&(key.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&key);
+    let err = FrontCodedDict::<Identifier>::read_from(&mut bytes.as_slice()).err();
+    assert_eq!(err.map(|err| err.kind()), Some(ErrorKind::InvalidData));
+}
+
+#[test]
+fn test_front_coded_dictionary() {
+    let f = File::open("dictionaries/alphanumeric.txt").unwrap();
+    let file = BufReader::new(&f);
+    let words: Vec<String> = file
+        .lines()
+        .map(|line| line.unwrap().trim().to_owned())
+        .collect();
+    let dlb: DLB = DLB::from_sorted_iter(&words).unwrap();
+    let (trie, _) = dlb.freeze_ranked();
+    let dict = FrontCodedDict::from(&dlb);
+    assert_eq!(dict.bucket_size(), FrontCodedDict::DEFAULT_BUCKET_SIZE);
+    assert_eq!(dict.heap_size().wasted_capacity, 0);
+    assert!(dict.size_of() * 4 < trie.size_of());
+    // Bigger buckets keep fewer keys whole.
+    let smaller = FrontCodedDict::from_dlb(&dlb, 64);
+    assert!(smaller.size_of() < dict.size_of());
+    for word in words.iter().step_by(7) {
+        let id = dict.get(word.clone()).unwrap();
+        assert_eq!(Some(id), trie.get(word.clone()));
+        assert_eq!(dict.resolve(id).as_ref(), Some(word));
+        assert_eq!(smaller.get(word.clone()), Some(id));
+    }
+
+    let mut bytes = Vec::new();
+    dict.write_to(&mut bytes).unwrap();
+    let read = FrontCodedDict::<Identifier>::read_from(&mut bytes.as_slice()).unwrap();
+    assert_eq!(read.len(), dict.len());
+    assert_eq!(read.size_of(), dict.size_of());
+}
@@ -0,0 +1,222 @@
+use sdtrie::art::AdaptiveRadixTrie;
+use sdtrie::dtrie::{Lowercase, DLB};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::fs::File;
+use std::io::BufRead;
+use std::io::BufReader;
+
+/// `CountingAllocator` tracks the number of live heap bytes
+/// allocated by the current thread, so concurrently running
+/// tests don't disturb each other's measurements.
+struct CountingAllocator;
+
+thread_local! {
+    static LIVE_BYTES: Cell<isize> = const { Cell::new(0) };
+}
+
+fn record(delta: isize) {
+    // The thread-local may already be gone while a thread is shutting down.
+    let _ = LIVE_BYTES.try_with(|live| live.set(live.get() + delta));
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        record(layout.size() as isize);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        record(-(layout.size() as isize));
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn live_bytes() -> isize {
+    LIVE_BYTES.with(|live| live.get())
+}
+
+/// `measure` builds a trie from the given words and returns it
+/// together with the number of heap bytes it kept alive.
+fn measure(words: &[String]) -> (DLB, usize) {
+    let before = live_bytes();
+    let mut dlb = DLB::new();
+    for word in words.iter() {
+        dlb.get_or_intern(word.clone());
+    }
+    let after = live_bytes();
+    (dlb, (after - before) as usize)
+}
+
+fn to_strings(words: &[&str]) -> Vec<String> {
+    words.iter().map(|s| String::from(*s)).collect()
+}
+
+#[test]
+fn test_empty_trie_owns_no_heap() {
+    let (dlb, allocated) = measure(&[]);
+    assert_eq!(allocated, 0);
+    assert_eq!(dlb.heap_size().total(), 0);
+}
+
+#[test]
+fn test_leaves_match_allocator() {
+    let words = to_strings(&["foo", "bar", "pop", "lizz"]);
+    let (dlb, allocated) = measure(&words);
+    let usage = dlb.heap_size();
+    assert_eq!(usage.total(), allocated);
+    // Short labels are stored inside the nodes.
+    assert_eq!(usage.labels, 0);
+}
+
+#[test]
+fn test_long_labels_match_allocator() {
+    let words = to_strings(&[
+        "pneumonoultramicroscopicsilicovolcanoconiosis",
+        "pneumonoultramicroscopic",
+        "supercalifragilisticexpialidocious",
+    ]);
+    let (dlb, allocated) = measure(&words);
+    let usage = dlb.heap_size();
+    assert_eq!(usage.total(), allocated);
+    // Long labels go to the pool. Splitting one reuses its pool bytes
+    // rather than copying them, so the first word is only stored once.
+    assert_eq!(usage.labels, 45 + 34);
+}
+
+#[test]
+fn test_splits_match_allocator() {
+    let words = to_strings(&["1080", "10-point", "10th", "a", "aaa", "aa", "fizz", "fang"]);
+    let (dlb, allocated) = measure(&words);
+    let usage = dlb.heap_size();
+    assert_eq!(usage.total(), allocated);
+    // Children are linked through the node arena rather than containers,
+    // leaving only the table of which node holds each identifier.
+    assert_eq!(usage.child_containers, words.len() * 4);
+    assert!(usage.wasted_capacity > 0);
+}
+
+#[test]
+fn test_dictionary_matches_allocator() {
+    let f = File::open("dictionaries/alphanumeric.txt").unwrap();
+    let file = BufReader::new(&f);
+    let words: Vec<String> = file
+        .lines()
+        .take(5000)
+        .map(|line| line.unwrap().trim().to_owned())
+        .collect();
+
+    let (dlb, allocated) = measure(&words);
+    let usage = dlb.heap_size();
+    assert_eq!(usage.total(), allocated);
+    assert!(dlb.size_of() > allocated);
+}
+
+#[test]
+fn test_counts_match_allocator() {
+    let words = to_strings(&["1080", "10-point", "10th", "a", "aaa", "aa", "fizz", "fang"]);
+    let before = live_bytes();
+    let mut dlb = DLB::new();
+    for word in words.iter().chain(words.iter().take(3)) {
+        dlb.intern_counted(word.clone());
+    }
+    dlb.remove(String::from("aa"));
+    let allocated = (live_bytes() - before) as usize;
+    assert_eq!(dlb.heap_size().total(), allocated);
+}
+
+#[test]
+fn test_scores_match_allocator() {
+    let words = to_strings(&["1080", "10-point", "10th", "a", "aaa", "aa", "fizz", "fang"]);
+    let before = live_bytes();
+    let mut dlb = DLB::new();
+    for (score, word) in words.iter().enumerate() {
+        let id = dlb.get_or_intern(word.clone());
+        dlb.set_score(id, score as u64);
+    }
+    dlb.remove(String::from("aa"));
+    let allocated = (live_bytes() - before) as usize;
+    assert_eq!(dlb.heap_size().total(), allocated);
+}
+
+#[test]
+fn test_indexed_keys_match_allocator() {
+    let words = to_strings(&["1080", "10-point", "10th", "a", "aaa", "aa", "fizz", "fang"]);
+    let (mut dlb, _) = measure(&words);
+    let before = live_bytes() - dlb.heap_size().total() as isize;
+    for word in words.iter().step_by(2) {
+        let id = dlb.get(word.clone()).unwrap();
+        assert_eq!(&dlb[id], word.as_str());
+    }
+    dlb.remove(String::from("aa"));
+    dlb.get_or_intern(String::from("fig"));
+    let allocated = (live_bytes() - before) as usize;
+    assert_eq!(dlb.heap_size().total(), allocated);
+}
+
+#[test]
+fn test_normalizer_matches_allocator() {
+    let words = to_strings(&["Foo", "BAR", "pop", "Lizz", "PneumonoUltramicroscopic"]);
+    let before = live_bytes();
+    let mut dlb: DLB = DLB::with_normalizer(Lowercase);
+    for word in words.iter() {
+        dlb.get_or_intern(word.clone());
+    }
+    dlb.remove(String::from("bar"));
+    let allocated = (live_bytes() - before) as usize;
+    assert_eq!(dlb.heap_size().total(), allocated);
+}
+
+#[test]
+fn test_removals_match_allocator() {
+    let words = to_strings(&[
+        "interoperability-1-characteristically",
+        "interoperability-2-characteristically",
+        "interoperability-3-characteristically",
+        "pneumonoultramicroscopicsilicovolcanoconiosis",
+    ]);
+    let (mut dlb, allocated) = measure(&words);
+    let before = live_bytes() - allocated as isize;
+    dlb.remove(words[0].clone());
+    dlb.remove(words[1].clone());
+    let usage = dlb.heap_size();
+    assert_eq!(usage.total(), (live_bytes() - before) as usize);
+    // Merging the shared prefix into the last sibling copies both
+    // labels, so only the copy counts as label bytes.
+    assert_eq!(usage.labels, 37 + 45);
+    assert!(usage.wasted_capacity >= 20 + 20 + 17 + 20);
+
+    // Once most of the pool is dead, it is compacted.
+    dlb.remove(words[3].clone());
+    let usage = dlb.heap_size();
+    assert_eq!(usage.total(), (live_bytes() - before) as usize);
+    assert_eq!(usage.labels, 37);
+}
+
+#[test]
+fn test_art_matches_allocator() {
+    let f = File::open("dictionaries/alphanumeric.txt").unwrap();
+    let file = BufReader::new(&f);
+    let words: Vec<String> = file
+        .lines()
+        .take(5000)
+        .map(|line| line.unwrap().trim().to_owned())
+        .collect();
+
+    let before = live_bytes();
+    let mut trie: AdaptiveRadixTrie = AdaptiveRadixTrie::new();
+    for word in words.iter() {
+        trie.get_or_intern(word.clone());
+    }
+    for word in words.iter().step_by(3) {
+        trie.remove(word.clone());
+    }
+    let allocated = (live_bytes() - before) as usize;
+    let usage = trie.heap_size();
+    assert_eq!(usage.total(), allocated);
+    // The wider nodes are boxed, so they count as child containers.
+    assert!(usage.child_containers > trie.len() * 4);
+}
@@ -0,0 +1,122 @@
+use sdtrie::dtrie::{
+    AsciiFold, IdPolicy, KeyMode, KeyNormalizer, Lowercase, Nfc, NfkcCasefold, Symbol, DLB,
+};
+
+#[test]
+fn test_normalizers() {
+    assert_eq!(Lowercase.normalize("HeLLo Σ"), "hello σ");
+    assert_eq!(AsciiFold.normalize("Crème Brûlée"), "Creme Brulee");
+    assert_eq!(AsciiFold.normalize("Æsir straße ﬁ"), "AEsir strasse fi");
+    assert_eq!(Nfc.normalize("e\u{301}"), "é");
+    assert_eq!(NfkcCasefold.normalize("ＦＵＬＬ Straße"), "full strasse");
+    assert_eq!(
+        NfkcCasefold.normalize("ΣΊΣΥΦΟΣ"),
+        NfkcCasefold.normalize("σίσυφος")
+    );
+    // Keys already in normal form are borrowed rather than copied.
+    for key in ["plain", "é", ""].iter() {
+        let normalizers: [&dyn KeyNormalizer; 3] = [&Lowercase, &Nfc, &NfkcCasefold];
+        for normalizer in normalizers.iter() {
+            assert!(matches!(
+                normalizer.normalize(key),
+                std::borrow::Cow::Borrowed(_)
+            ));
+        }
+    }
+}
+
+#[test]
+fn test_lookups_are_normalised() {
+    let mut dlb: DLB = DLB::with_normalizer(Lowercase);
+    let id = dlb.get_or_intern(String::from("Hello"));
+    assert_eq!(dlb.get_or_intern(String::from("HELLO")), id);
+    assert_eq!(dlb.get(String::from("hello")), Some(id));
+    assert!(dlb.contains(String::from("hElLo")));
+    assert_eq!(dlb.len(), 1);
+    // The first spelling is the one kept.
+    assert_eq!(dlb.resolve(id), Some(String::from("Hello")));
+    let keys: Vec<String> = dlb
+        .prefix_iter(String::from("HE"))
+        .map(|(key, _)| key)
+        .collect();
+    assert_eq!(keys, vec![String::from("hello")]);
+
+    assert_eq!(
+        dlb.remove(String::from("HELLO")).map(|removed| removed.id),
+        Some(id)
+    );
+    assert!(dlb.is_empty());
+    let id = dlb.get_or_intern(String::from("hello"));
+    assert_eq!(dlb.resolve(id), Some(String::from("hello")));
+}
+
+#[test]
+fn test_unicode_forms_match() {
+    let mut dlb: DLB = DLB::with_options(IdPolicy::Sequential, KeyMode::Utf8);
+    dlb.set_normalizer(NfkcCasefold);
+    let id = dlb.get_or_intern(String::from("Cafe\u{301}"));
+    assert_eq!(dlb.get(String::from("CAFÉ")), Some(id));
+    assert_eq!(dlb.get(String::from("ｃａｆé")), Some(id));
+    assert_eq!(dlb.resolve(id), Some(String::from("Cafe\u{301}")));
+}
+
+#[test]
+fn test_dense_removal_moves_spellings() {
+    let mut dlb: DLB = DLB::with_policy(IdPolicy::Dense);
+    dlb.set_normalizer(|key: &str| key.to_uppercase());
+    for key in ["alpha", "beta", "gamma"].iter() {
+        dlb.get_or_intern(key.to_string());
+    }
+    let removed = dlb.remove(String::from("Alpha")).unwrap();
+    assert_eq!(removed.moved.map(|moved| moved.to_usize()), Some(2));
+    assert_eq!(dlb.resolve(removed.id), Some(String::from("gamma")));
+    assert_eq!(dlb.get(String::from("gamma")), Some(removed.id));
+    let id = dlb.get_or_intern(String::from("delta"));
+    assert_eq!(dlb.resolve(id), Some(String::from("delta")));
+}
+
+#[test]
+fn test_spellings_survive_saving_and_union() {
+    let mut dlb: DLB = DLB::with_normalizer(AsciiFold);
+    let naive = dlb.get_or_intern(String::from("naïve"));
+    let plain = dlb.get_or_intern(String::from("plain"));
+
+    let mut bytes = Vec::new();
+    dlb.write_to(&mut bytes).unwrap();
+    let mut read: DLB = DLB::read_from(&mut bytes.as_slice()).unwrap();
+    assert_eq!(read.resolve(naive), Some(String::from("naïve")));
+    assert_eq!(read.resolve(plain), Some(String::from("plain")));
+    read.set_normalizer(AsciiFold);
+    assert_eq!(read.get(String::from("naïve")), Some(naive));
+
+    let (union, remap) = DLB::new().union(&dlb);
+    let id = remap.get(naive).unwrap();
+    assert_eq!(union.resolve(id), Some(String::from("naïve")));
+    assert_eq!(union.get(String::from("naive")), Some(id));
+}
+
+#[test]
+fn test_rename_keeps_spellings() {
+    let mut dlb: DLB = DLB::with_normalizer(Lowercase);
+    let bar = dlb.get_or_intern(String::from("FooBar"));
+    let baz = dlb.get_or_intern(String::from("foobaz"));
+    assert_eq!(
+        dlb.rename_prefix(String::from("FOO"), String::from("Qux")),
+        Ok(2)
+    );
+    // The renamed prefix is spelt as given, and the rest as it was.
+    assert_eq!(dlb.resolve(bar), Some(String::from("QuxBar")));
+    assert_eq!(&dlb[bar], "QuxBar");
+    assert_eq!(dlb.get(String::from("quxbar")), Some(bar));
+    assert_eq!(dlb.resolve(baz), Some(String::from("quxbaz")));
+
+    let mut dlb: DLB = DLB::with_options(IdPolicy::Sequential, KeyMode::Utf8);
+    dlb.set_normalizer(NfkcCasefold);
+    let noir = dlb.get_or_intern(String::from("Cafe\u{301}Noir"));
+    assert_eq!(
+        dlb.rename_prefix(String::from("CAFÉ"), String::from("Thé")),
+        Ok(1)
+    );
+    assert_eq!(dlb.resolve(noir), Some(String::from("ThéNoir")));
+    assert_eq!(dlb.get(String::from("THÉNOIR")), Some(noir));
+}
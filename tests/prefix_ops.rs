@@ -0,0 +1,140 @@
+use proptest::collection::vec;
+use proptest::prelude::*;
+use sdtrie::dtrie::{Conflict, IdPolicy, KeyMode, DLB};
+use std::collections::BTreeMap;
+
+fn build(words: &[&str]) -> DLB {
+    words.iter().collect()
+}
+
+fn keys(dlb: &DLB) -> Vec<String> {
+    dlb.iter().map(|(key, _)| key).collect()
+}
+
+#[test]
+fn test_count_and_remove_prefix() {
+    let mut dlb = build(&["", "car", "card", "care", "cat", "dog"]);
+    assert_eq!(dlb.count_prefix(String::from("car")), 3);
+    assert_eq!(dlb.count_prefix(String::from("ca")), 4);
+    assert_eq!(dlb.count_prefix(String::from("cars")), 0);
+    assert_eq!(dlb.count_prefix(String::from("x")), 0);
+    assert_eq!(dlb.count_prefix(String::new()), 6);
+
+    assert_eq!(dlb.remove_prefix(String::from("car")), 3);
+    assert_eq!(keys(&dlb), ["", "cat", "dog"]);
+    assert_eq!(dlb.count_nodes(), build(&["", "cat", "dog"]).count_nodes());
+    assert_eq!(dlb.remove_prefix(String::from("car")), 0);
+    assert_eq!(dlb.remove_prefix(String::new()), 3);
+    assert!(dlb.is_empty());
+    assert_eq!(dlb.count_nodes(), 0);
+}
+
+#[test]
+fn test_rename_prefix_keeps_ids() {
+    let mut dlb = build(&["car", "card", "care", "cat"]);
+    let card = dlb.get(String::from("card")).unwrap();
+    dlb.set_score(card, 7);
+    dlb.intern_counted(String::from("card"));
+    assert_eq!(
+        dlb.rename_prefix(String::from("car"), String::from("bus")),
+        Ok(3)
+    );
+    assert_eq!(dlb, build(&["bus", "busd", "buse", "cat"]));
+    assert_eq!(dlb.get(String::from("busd")), Some(card));
+    assert_eq!(dlb.resolve(card), Some(String::from("busd")));
+    assert_eq!(dlb.score(card), Some(7));
+    assert_eq!(dlb.count(String::from("busd")), 1);
+
+    // Keys may not land on keys which aren't moving with them.
+    let before = dlb.clone();
+    let conflict = Conflict {
+        key: String::from("cat"),
+    };
+    assert_eq!(
+        dlb.rename_prefix(String::from("bus"), String::from("cat")),
+        Err(conflict)
+    );
+    assert_eq!(dlb, before);
+    // But they may land on keys which are.
+    assert_eq!(
+        dlb.rename_prefix(String::from("bu"), String::from("b")),
+        Ok(3)
+    );
+    assert_eq!(dlb, build(&["bs", "bsd", "bse", "cat"]));
+}
+
+proptest! {
+    #[test]
+    fn test_prefix_ops_match_a_map(
+        utf8 in any::<bool>(),
+        dense in any::<bool>(),
+        words in vec("[aé日]{0,4}", 0..40),
+        ops in vec((0..3u8, "[aé日]{0,3}", "[aé日]{0,3}"), 0..8),
+    ) {
+        let mode = if utf8 { KeyMode::Utf8 } else { KeyMode::Bytes };
+        let policy = if dense { IdPolicy::Dense } else { IdPolicy::Sequential };
+        let mut dlb: DLB = DLB::with_options(policy, mode);
+        let mut model = BTreeMap::new();
+        for (score, word) in words.into_iter().enumerate() {
+            let id = dlb.intern_counted(word.clone());
+            dlb.set_score(id, score as u64);
+            let count = model.get(&word).map_or(0, |&(_, count, _)| count);
+            model.insert(word, (id, count + 1, score as u64));
+        }
+        for (op, old, new) in ops {
+            let under: Vec<String> = model.keys().filter(|key| key.starts_with(&old)).cloned().collect();
+            prop_assert_eq!(dlb.count_prefix(old.clone()), under.len());
+            match op {
+                0 => {}
+                1 => {
+                    prop_assert_eq!(dlb.remove_prefix(old.clone()), under.len());
+                    for key in under.iter() {
+                        model.remove(key);
+                    }
+                    if dense {
+                        // Removal moves identifiers under the dense policy.
+                        for (key, entry) in model.iter_mut() {
+                            entry.0 = dlb.get(key.clone()).unwrap();
+                        }
+                    }
+                }
+                _ => {
+                    let moved: Vec<_> = under
+                        .iter()
+                        .map(|key| (format!("{}{}", new, &key[old.len()..]), model[key]))
+                        .collect();
+                    let blocked = moved
+                        .iter()
+                        .find(|(key, _)| model.contains_key(key) && !key.starts_with(&old));
+                    match blocked {
+                        Some((key, _)) => {
+                            prop_assert!(dlb.rename_prefix(old.clone(), new.clone()).is_err());
+                            prop_assert!(model.contains_key(key));
+                        }
+                        None => {
+                            prop_assert_eq!(dlb.rename_prefix(old.clone(), new.clone()), Ok(under.len()));
+                            for key in under.iter() {
+                                model.remove(key);
+                            }
+                            model.extend(moved);
+                        }
+                    }
+                }
+            }
+            let keys: Vec<(String, _)> = model.iter().map(|(key, entry)| (key.clone(), entry.0)).collect();
+            prop_assert_eq!(dlb.iter().collect::<Vec<_>>(), keys);
+            for (rank, (key, &(id, count, score))) in model.iter().enumerate() {
+                prop_assert_eq!(dlb.resolve(id), Some(key.clone()));
+                prop_assert_eq!(&dlb[id], key.as_str());
+                prop_assert_eq!(dlb.count(key.clone()), count);
+                prop_assert_eq!(dlb.score(id), Some(score));
+                prop_assert_eq!(dlb.rank(key.clone()), rank);
+            }
+            let total: u64 = model.values().map(|&(_, count, _)| count).sum();
+            prop_assert_eq!(dlb.prefix_count(String::new()), total);
+            let mut fresh: DLB = DLB::with_options(policy, mode);
+            fresh.extend(model.keys());
+            prop_assert_eq!(dlb.count_nodes(), fresh.count_nodes());
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use proptest::collection::vec;
+use proptest::prelude::*;
+use sdtrie::dtrie::{IdPolicy, KeyMode, DLB};
+use std::collections::BTreeSet;
+
+#[test]
+fn test_rank_and_select() {
+    let words = ["", "car", "card", "care", "cat", "dog", "do"];
+    let dlb: DLB = words.iter().collect();
+    let sorted: BTreeSet<&str> = words.iter().cloned().collect();
+    for (rank, word) in sorted.iter().enumerate() {
+        assert_eq!(dlb.rank(word.to_string()), rank);
+        let (key, id) = dlb.select(rank).unwrap();
+        assert_eq!(key, *word);
+        assert_eq!(dlb.get(key), Some(id));
+    }
+    assert_eq!(dlb.select(words.len()), None);
+    // Missing keys are ranked by where they would go.
+    assert_eq!(dlb.rank(String::from("ca")), 1);
+    assert_eq!(dlb.rank(String::from("cards")), 3);
+    assert_eq!(dlb.rank(String::from("cb")), 5);
+    assert_eq!(dlb.rank(String::from("zebra")), words.len());
+}
+
+proptest! {
+    #[test]
+    fn test_rank_and_select_match_a_set(
+        utf8 in any::<bool>(),
+        ops in vec((any::<bool>(), "[aé日]{0,4}"), 0..80),
+        probes in vec("[aé日]{0,5}", 0..10),
+    ) {
+        let mode = if utf8 { KeyMode::Utf8 } else { KeyMode::Bytes };
+        let mut dlb: DLB = DLB::with_options(IdPolicy::Dense, mode);
+        let mut model = BTreeSet::new();
+        for (insert, key) in ops {
+            if insert {
+                dlb.get_or_intern(key.clone());
+                model.insert(key);
+            } else {
+                dlb.remove(key.clone());
+                model.remove(&key);
+            }
+        }
+        let mut bytes = Vec::new();
+        dlb.write_to(&mut bytes).unwrap();
+        let read: DLB = DLB::read_from(&mut bytes.as_slice()).unwrap();
+        let sorted: DLB = DLB::from_sorted_iter(model.iter()).unwrap();
+        for trie in [&dlb, &read, &sorted].iter() {
+            for (rank, key) in model.iter().enumerate() {
+                prop_assert_eq!(trie.rank(key.clone()), rank);
+                let (found, id) = trie.select(rank).unwrap();
+                prop_assert_eq!(&found, key);
+                prop_assert_eq!(trie.get(found), Some(id));
+            }
+            prop_assert_eq!(trie.select(model.len()), None);
+            for probe in probes.iter() {
+                prop_assert_eq!(trie.rank(probe.clone()), model.range::<String, _>(..probe.clone()).count());
+            }
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use rand::seq::SliceRandom;
+use rand_chacha::ChaCha8Rng;
+use rand_core::SeedableRng;
+use sdtrie::dtrie::{IdPolicy, Symbol, DLB};
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::BufRead;
+use std::io::BufReader;
+
+fn keys(dlb: &DLB) -> Vec<String> {
+    dlb.iter().map(|(key, _)| key).collect()
+}
+
+#[test]
+fn test_set_ops_on_prefixes() {
+    // Labels which part ways, run into each other and end partway through.
+    let left: DLB = vec!["", "ab", "abcd", "b", "car", "cart"]
+        .into_iter()
+        .collect();
+    let right: DLB = vec!["a", "abc", "abcd", "car", "carton", "d"]
+        .into_iter()
+        .collect();
+
+    let (union, remap) = left.union(&right);
+    assert_eq!(
+        keys(&union),
+        vec!["", "a", "ab", "abc", "abcd", "b", "car", "cart", "carton", "d"]
+    );
+    for (key, id) in left.iter() {
+        assert_eq!(union.get(key), Some(id));
+    }
+    assert_eq!(remap.len(), right.len());
+    for (key, id) in right.iter() {
+        assert_eq!(union.get(key), remap.get(id));
+    }
+
+    let (intersection, remap) = left.intersection(&right);
+    assert_eq!(keys(&intersection), vec!["abcd", "car"]);
+    for (key, id) in intersection.iter() {
+        assert_eq!(left.get(key.clone()), Some(id));
+        assert_eq!(remap.get(right.get(key).unwrap()), Some(id));
+    }
+    assert_eq!(remap.len(), 2);
+
+    let difference = left.difference(&right);
+    assert_eq!(keys(&difference), vec!["", "ab", "b", "cart"]);
+    let difference = right.difference(&left);
+    assert_eq!(keys(&difference), vec!["a", "abc", "carton", "d"]);
+}
+
+#[test]
+fn test_set_ops_match_btreeset() {
+    let mut rng: ChaCha8Rng = SeedableRng::seed_from_u64(37);
+    let f = File::open("dictionaries/alphanumeric.txt").unwrap();
+    let file = BufReader::new(&f);
+    let mut words: Vec<String> = file
+        .lines()
+        .take(12000)
+        .map(|line| line.unwrap().trim().to_owned())
+        .collect();
+    words.shuffle(&mut rng);
+    let left_words: BTreeSet<String> = words[..8000].iter().cloned().collect();
+    let right_words: BTreeSet<String> = words[4000..].iter().cloned().collect();
+
+    for policy in [IdPolicy::Sequential, IdPolicy::Dense, IdPolicy::Recycled].iter() {
+        let mut left: DLB = DLB::with_policy(*policy);
+        left.extend(words[..8000].iter());
+        let mut right: DLB = DLB::with_policy(*policy);
+        right.extend(words[4000..].iter());
+
+        let mut union = left.clone();
+        let remap = union.union_with(&right);
+        let expected: Vec<&String> = left_words.union(&right_words).collect();
+        assert!(union
+            .iter()
+            .map(|(key, _)| key)
+            .eq(expected.into_iter().cloned()));
+        for (key, id) in right.iter() {
+            assert_eq!(union.get(key), remap.get(id));
+        }
+
+        let mut intersection = left.clone();
+        let remap = intersection.intersect_with(&right);
+        let expected: Vec<&String> = left_words.intersection(&right_words).collect();
+        assert!(intersection
+            .iter()
+            .map(|(key, _)| key)
+            .eq(expected.into_iter().cloned()));
+        assert_eq!(remap.len(), intersection.len());
+        for (key, id) in intersection.iter() {
+            assert_eq!(remap.get(right.get(key.clone()).unwrap()), Some(id));
+            assert_eq!(intersection.resolve(id), Some(key));
+        }
+        if *policy == IdPolicy::Dense {
+            let mut ids: Vec<usize> = intersection.iter().map(|(_, id)| id.to_usize()).collect();
+            ids.sort_unstable();
+            assert_eq!(ids, (0..intersection.len()).collect::<Vec<_>>());
+        }
+
+        let mut difference = left.clone();
+        difference.difference_with(&right);
+        let expected: Vec<&String> = left_words.difference(&right_words).collect();
+        assert!(difference
+            .iter()
+            .map(|(key, _)| key)
+            .eq(expected.into_iter().cloned()));
+        assert_eq!(difference.count_nodes(), {
+            let rebuilt: DLB = difference.iter().map(|(key, _)| key).collect();
+            rebuilt.count_nodes()
+        });
+    }
+}
@@ -0,0 +1,261 @@
+use sdtrie::dtrie::{BuildError, Identifier, Symbol, DLB};
+use sdtrie::strie::{StaticTrie, StaticTrieBuilder};
+use std::fs::File;
+use std::io::BufRead;
+use std::io::BufReader;
+
+fn build(words: &[&str]) -> DLB {
+    words.iter().collect()
+}
+
+#[test]
+fn test_freeze_preserves_identifiers() {
+    let words = ["", "foo", "food", "fang", "1080", "10-point", "10th"];
+    let dlb = build(&words);
+    let trie = dlb.freeze();
+    assert_eq!(trie.len(), words.len());
+    assert_eq!(trie.count_nodes(), dlb.count_nodes());
+    for word in words.iter() {
+        let id = dlb.get(word.to_string()).unwrap();
+        assert_eq!(trie.get(word.to_string()), Some(id));
+        assert_eq!(trie.resolve(id), Some(word.to_string()));
+    }
+    for missing in ["f", "fo", "foods", "10", "x"].iter() {
+        assert_eq!(trie.get(missing.to_string()), None);
+    }
+}
+
+#[test]
+fn test_static_iter_is_sorted() {
+    let trie = build(&["pop", "bar", "foo", "food", "fa", "lizz"]).freeze();
+    let keys: Vec<String> = trie.iter().map(|(key, _)| key).collect();
+    assert_eq!(keys, vec!["bar", "fa", "foo", "food", "lizz", "pop"]);
+    let keys: Vec<String> = trie
+        .prefix_iter(String::from("fo"))
+        .map(|(key, _)| key)
+        .collect();
+    assert_eq!(keys, vec!["foo", "food"]);
+    assert_eq!(trie.prefix_iter(String::from("fob")).count(), 0);
+}
+
+#[test]
+fn test_static_round_trip() {
+    let trie = build(&["a", "aa", "aaa", "b"]).freeze();
+    let mut buffer = Vec::new();
+    trie.write_to(&mut buffer).unwrap();
+    let restored = StaticTrie::read_from(&mut buffer.as_slice()).unwrap();
+    let expected: Vec<_> = trie.iter().collect();
+    let observed: Vec<_> = restored.iter().collect();
+    assert_eq!(expected, observed);
+    assert!(StaticTrie::<Identifier>::read_from(&mut &buffer[..8]).is_err());
+}
+
+#[test]
+fn test_freeze_dictionary() {
+    let f = File::open("dictionaries/alphanumeric.txt").unwrap();
+    let file = BufReader::new(&f);
+    let words: Vec<String> = file
+        .lines()
+        .take(20000)
+        .map(|line| line.unwrap().trim().to_owned())
+        .collect();
+    let mut dlb = DLB::new();
+    let ids: Vec<_> = words
+        .iter()
+        .map(|word| dlb.get_or_intern(word.clone()))
+        .collect();
+    let trie = dlb.freeze();
+    // The arena is already compact, so freezing trades a little space
+    // for sorted children and parent links, but leaves no slack behind.
+    assert_eq!(trie.heap_size().wasted_capacity, 0);
+    // Leaf tails repeat endlessly, so sharing them shrinks the labels
+    // to a fraction of their size.
+    assert!(trie.heap_size().labels * 4 < trie.raw_label_size());
+    for (word, id) in words.iter().zip(ids) {
+        assert_eq!(trie.get(word.clone()), Some(id));
+        assert_eq!(trie.resolve(id).as_ref(), Some(word));
+    }
+}
+
+#[test]
+fn test_freeze_ranked() {
+    let words = ["food", "", "fang", "10th", "foo", "1080", "10-point", "f"];
+    let mut dlb = build(&words);
+    dlb.remove(String::from("f"));
+    let (trie, remap) = dlb.freeze_ranked();
+    assert_eq!(remap.len(), dlb.len());
+
+    let keys: Vec<String> = trie.iter().map(|(key, _)| key).collect();
+    let ranks: Vec<usize> = trie.iter().map(|(_, id)| id.to_usize()).collect();
+    assert_eq!(
+        keys,
+        vec!["", "10-point", "1080", "10th", "fang", "foo", "food"]
+    );
+    assert_eq!(ranks, (0..keys.len()).collect::<Vec<_>>());
+    for (key, old) in dlb.iter() {
+        let new = remap.get(old).unwrap();
+        assert_eq!(trie.get(key.clone()), Some(new));
+        assert_eq!(trie.resolve(new), Some(key));
+    }
+    // Keys sharing a prefix have consecutive identifiers.
+    let prefixed: Vec<usize> = trie
+        .prefix_iter(String::from("fo"))
+        .map(|(_, id)| id.to_usize())
+        .collect();
+    assert_eq!(prefixed, vec![5, 6]);
+}
+
+#[test]
+fn test_build_sorted_dictionary() {
+    let f = File::open("dictionaries/alphanumeric.txt").unwrap();
+    let file = BufReader::new(&f);
+    let words: Vec<String> = file
+        .lines()
+        .map(|line| line.unwrap().trim().to_owned())
+        .collect();
+    let trie: StaticTrie = StaticTrie::from_sorted_iter(&words).unwrap();
+    let frozen = DLB::from_sorted_iter(&words).unwrap().freeze();
+    assert_eq!(trie.len(), words.len());
+    assert_eq!(trie.count_nodes(), frozen.count_nodes());
+    assert_eq!(trie.depth_histogram(), frozen.depth_histogram());
+    assert_eq!(trie.heap_size().wasted_capacity, 0);
+    for (index, word) in words.iter().enumerate().step_by(7) {
+        let id = trie.get(word.clone()).unwrap();
+        assert_eq!(id.to_usize(), index);
+        assert_eq!(trie.resolve(id).as_ref(), Some(word));
+    }
+    let prefixed: Vec<_> = trie.prefix_iter(String::from("zym")).collect();
+    let expected: Vec<_> = frozen.prefix_iter(String::from("zym")).collect();
+    assert_eq!(prefixed, expected);
+}
+
+#[test]
+fn test_builder_rejects_unsorted_keys() {
+    let mut builder = StaticTrieBuilder::new();
+    assert_eq!(builder.push("").map(|id| id.to_usize()), Ok(0));
+    assert_eq!(builder.push("foo").map(|id| id.to_usize()), Ok(1));
+    assert_eq!(builder.push("foo"), Err(BuildError::Duplicate(2)));
+    assert_eq!(builder.push("fa"), Err(BuildError::Unsorted(2)));
+    // Rejected keys leave the builder as it was.
+    assert_eq!(builder.push("food").map(|id| id.to_usize()), Ok(2));
+    let trie = builder.finish();
+    let keys: Vec<String> = trie.iter().map(|(key, _)| key).collect();
+    assert_eq!(keys, vec!["", "foo", "food"]);
+
+    let mut round_trip = Vec::new();
+    trie.write_to(&mut round_trip).unwrap();
+    let read = StaticTrie::<Identifier>::read_from(&mut round_trip.as_slice()).unwrap();
+    assert_eq!(
+        read.get(String::from("food")),
+        trie.get(String::from("food"))
+    );
+}
+
+#[test]
+fn test_labels_share_tails() {
+    // The labels are "x", "bri", "g", "ng", "sing" and "ing".
+    // "ing", "ng" and "g" all end "sing", so need no space of their own.
+    let dlb = build(&["xsinging", "xsing", "xbring", "xbrig"]);
+    let trie = dlb.freeze();
+    let keys: Vec<String> = trie.iter().map(|(key, _)| key).collect();
+    assert_eq!(keys, vec!["xbrig", "xbring", "xsing", "xsinging"]);
+    for (key, id) in dlb.iter() {
+        assert_eq!(trie.get(key.clone()), Some(id));
+        assert_eq!(trie.resolve(id), Some(key));
+    }
+    assert_eq!(trie.raw_label_size(), 14);
+    assert_eq!(trie.heap_size().labels, 8);
+}
+
+/// `encode` writes a static trie file by hand. Each node is its label
+/// start and length, first child, child count and identifier.
+fn encode(labels: &[u8], nodes: &[(u32, u32, u32, u32, Option<u64>)]) -> Vec<u8> {
+    let mut bytes = StaticTrie::MAGIC.to_vec();
+    bytes.extend_from_slice(&(labels.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(labels);
+    bytes.extend_from_slice(&(nodes.len() as u32).to_le_bytes());
+    for &(start, len, first, count, id) in nodes {
+        for field in [start, len, first, count].iter() {
+            bytes.extend_from_slice(&field.to_le_bytes());
+        }
+        match id {
+            Some(id) => {
+                bytes.push(1);
+                bytes.extend_from_slice(&(id + 1).to_le_bytes());
+            }
+            None => bytes.push(0),
+        }
+    }
+    bytes
+}
+
+#[test]
+fn test_read_rejects_malformed_trees() {
+    let read = |bytes: Vec<u8>| StaticTrie::<Identifier>::read_from(&mut bytes.as_slice());
+    let keys: Vec<String> = read(encode(
+        b"ab",
+        &[
+            (0, 0, 1, 2, None),
+            (0, 1, 0, 0, Some(0)),
+            (1, 1, 0, 0, Some(1)),
+        ],
+    ))
+    .unwrap()
+    .iter()
+    .map(|(key, _)| key)
+    .collect();
+    assert_eq!(keys, ["a", "b"]);
+
+    let malformed = vec![
+        // The root is its own child.
+        encode(b"a", &[(0, 0, 0, 1, None)]),
+        // A node is its own child.
+        encode(b"a", &[(0, 0, 1, 1, None), (0, 1, 1, 1, Some(0))]),
+        // A node is a child of two parents.
+        encode(
+            b"ab",
+            &[
+                (0, 0, 1, 2, None),
+                (0, 1, 2, 1, None),
+                (1, 1, 0, 0, Some(0)),
+            ],
+        ),
+        // A node can't be reached from the root.
+        encode(b"a", &[(0, 0, 0, 0, None), (0, 1, 0, 0, Some(0))]),
+        // A child has an empty label, starting past the end of the pool.
+        encode(b"a", &[(0, 0, 1, 1, None), (1, 0, 0, 0, Some(0))]),
+        // The root has a label.
+        encode(b"a", &[(0, 1, 0, 0, None)]),
+        // Siblings are out of order.
+        encode(
+            b"ab",
+            &[
+                (0, 0, 1, 2, None),
+                (1, 1, 0, 0, Some(0)),
+                (0, 1, 0, 0, Some(1)),
+            ],
+        ),
+        // Siblings share a first byte.
+        encode(
+            b"a",
+            &[
+                (0, 0, 1, 2, None),
+                (0, 1, 0, 0, Some(0)),
+                (0, 1, 0, 0, Some(1)),
+            ],
+        ),
+        // Two keys share an identifier.
+        encode(
+            b"ab",
+            &[
+                (0, 0, 1, 2, None),
+                (0, 1, 0, 0, Some(0)),
+                (1, 1, 0, 0, Some(0)),
+            ],
+        ),
+    ];
+    for bytes in malformed {
+        let err = read(bytes).err().unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,64 @@
+use proptest::collection::vec;
+use proptest::prelude::*;
+use sdtrie::art::AdaptiveRadixTrie;
+use sdtrie::dawg::Dawg;
+use sdtrie::dtrie::DLB;
+use sdtrie::fcd::FrontCodedDict;
+use sdtrie::strie::StaticTrie;
+use sdtrie::trie::{Interner, TrieRead};
+use sdtrie::tst::TernarySearchTrie;
+use std::collections::BTreeSet;
+
+/// `check_reader` compares a trie against the set of keys it was built
+/// from. Identifiers differ between tries, so only their keys are compared.
+fn check_reader<T: TrieRead>(trie: &T, model: &BTreeSet<String>, probes: &[String]) {
+    for probe in probes {
+        let expected: Vec<&String> = model
+            .iter()
+            .filter(|key| key.starts_with(probe.as_str()))
+            .collect();
+        let found: Vec<(String, T::Symbol)> = trie.prefix_iter(probe.clone()).collect();
+        assert_eq!(
+            found.iter().map(|(key, _)| key).collect::<Vec<_>>(),
+            expected
+        );
+        for (key, id) in found {
+            assert_eq!(trie.get(key), Some(id));
+        }
+        let longest = model
+            .iter()
+            .rev()
+            .find(|key| probe.starts_with(key.as_str()));
+        let found = trie.longest_prefix(probe.clone());
+        assert_eq!(found.as_ref().map(|(key, _)| key), longest);
+        assert_eq!(trie.contains(probe.clone()), model.contains(probe));
+    }
+}
+
+proptest! {
+    #[test]
+    fn test_readers_match_a_set(
+        words in vec("[ab日]{0,5}", 0..60),
+        probes in vec("[ab日]{0,6}", 0..20),
+        bucket_size in 1..5usize,
+    ) {
+        let model: BTreeSet<String> = words.iter().cloned().collect();
+        let mut dlb: DLB = DLB::new();
+        let mut tst: TernarySearchTrie = TernarySearchTrie::new();
+        let mut art: AdaptiveRadixTrie = AdaptiveRadixTrie::new();
+        for word in words {
+            dlb.get_or_intern(word.clone());
+            tst.get_or_intern(word.clone());
+            art.get_or_intern(word);
+        }
+        prop_assert_eq!(Interner::len(&dlb), model.len());
+        prop_assert_eq!(Interner::len(&tst), model.len());
+        prop_assert_eq!(Interner::len(&art), model.len());
+        check_reader(&dlb, &model, &probes);
+        check_reader(&tst, &model, &probes);
+        check_reader(&art, &model, &probes);
+        check_reader(&StaticTrie::from(&dlb), &model, &probes);
+        check_reader(&FrontCodedDict::from_dlb(&dlb, bucket_size), &model, &probes);
+        check_reader(&Dawg::from(&dlb), &model, &probes);
+    }
+}
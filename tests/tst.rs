@@ -0,0 +1,92 @@
+use proptest::collection::vec;
+use proptest::prelude::*;
+use rand::seq::SliceRandom;
+use rand_chacha::ChaCha8Rng;
+use rand_core::SeedableRng;
+use sdtrie::dtrie::DLB;
+use sdtrie::trie::{Interner, Trie};
+use sdtrie::tst::TernarySearchTrie;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+#[test]
+fn test_shares_prefixes() {
+    let trie: TernarySearchTrie = ["cat", "car", "cart", "dog", ""].iter().collect();
+    // "ca" is shared, the "r" of "car" hangs below the "t" of "cat",
+    // and "cart" adds one more "t".
+    assert_eq!(trie.count_nodes(), 8);
+    assert_eq!(trie.len(), 5);
+    let keys: Vec<String> = trie.iter().map(|(key, _)| key).collect();
+    assert_eq!(keys, ["", "car", "cart", "cat", "dog"]);
+    for (key, id) in trie.iter() {
+        assert_eq!(trie.resolve(id), Some(key));
+    }
+    assert_eq!(trie.get(String::from("ca")), None);
+    assert_eq!(trie.get(String::from("carts")), None);
+    let cars: Vec<String> = trie
+        .prefix_iter(String::from("car"))
+        .map(|(key, _)| key)
+        .collect();
+    assert_eq!(cars, ["car", "cart"]);
+}
+
+/// `intern_all` interns `words` through the `Trie` trait,
+/// checking that every key can be found again.
+fn intern_all<T: Trie + Default>(words: &[String]) -> T {
+    let mut trie = T::default();
+    let ids: Vec<_> = words
+        .iter()
+        .map(|word| trie.get_or_intern(word.clone()))
+        .collect();
+    for (word, id) in words.iter().zip(ids) {
+        assert_eq!(trie.get(word.clone()), Some(id));
+        assert_eq!(trie.resolve(id).as_ref(), Some(word));
+    }
+    trie
+}
+
+fn keys<T: Trie>(trie: &T, prefix: &str) -> Vec<String> {
+    trie.prefix_iter(prefix.to_owned())
+        .map(|(key, _)| key)
+        .collect()
+}
+
+#[test]
+fn test_matches_dlb_on_dictionary() {
+    let mut rng: ChaCha8Rng = SeedableRng::seed_from_u64(100);
+    let f = File::open("dictionaries/alphanumeric.txt").unwrap();
+    let mut words: Vec<String> = BufReader::new(&f)
+        .lines()
+        .take(22000)
+        .map(|line| line.unwrap().trim().to_owned())
+        .collect();
+    words.shuffle(&mut rng);
+
+    let dlb: DLB = intern_all(&words);
+    let tst: TernarySearchTrie = intern_all(&words);
+    assert_eq!(Interner::len(&tst), Interner::len(&dlb));
+    assert_eq!(Trie::iter(&tst).count(), words.len());
+    for prefix in ["", "1", "ab", "abs", "zzz"].iter() {
+        assert_eq!(keys(&tst, prefix), keys(&dlb, prefix));
+    }
+}
+
+proptest! {
+    #[test]
+    fn test_matches_dlb(
+        words in vec("[ab日]{0,5}", 0..60),
+        prefixes in vec("[ab日]{0,3}", 0..10),
+    ) {
+        let mut dlb: DLB = DLB::new();
+        let mut tst: TernarySearchTrie = TernarySearchTrie::new();
+        for word in words {
+            let ids = (dlb.get_or_intern(word.clone()), tst.get_or_intern(word));
+            // Both hand out identifiers in order.
+            prop_assert_eq!(ids.0, ids.1);
+        }
+        prop_assert_eq!(Trie::iter(&tst).collect::<Vec<_>>(), Trie::iter(&dlb).collect::<Vec<_>>());
+        for prefix in prefixes {
+            prop_assert_eq!(keys(&tst, &prefix), keys(&dlb, &prefix));
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use proptest::collection::vec;
+use proptest::prelude::*;
+use sdtrie::dtrie::{IdPolicy, KeyMode, DLB};
+use std::collections::BTreeSet;
+
+/// Characters chosen to share leading bytes: Latin-1 letters all start
+/// with 0xc3, the CJK ones with 0xe4 or 0xe6, and the emoji with 0xf0 0x9f.
+const WORD: &str = "[aeéèêëñ日本曜語中文😀😁🙂]{0,6}";
+
+fn build(mode: KeyMode, words: &[String]) -> DLB {
+    let mut dlb = DLB::with_options(IdPolicy::Sequential, mode);
+    dlb.extend(words.iter());
+    dlb
+}
+
+proptest! {
+    #[test]
+    fn test_utf8_mode_round_trips(words in vec(WORD, 0..40), probes in vec(WORD, 0..10)) {
+        let dlb = build(KeyMode::Utf8, &words);
+        let expected: BTreeSet<&String> = words.iter().collect();
+        prop_assert_eq!(dlb.len(), expected.len());
+        let keys: Vec<String> = dlb.iter().map(|(key, _)| key).collect();
+        prop_assert!(keys.iter().eq(expected.iter().cloned()));
+        for word in words.iter() {
+            let id = dlb.get(word.clone()).unwrap();
+            prop_assert_eq!(dlb.resolve(id), Some(word.clone()));
+        }
+        for probe in probes.iter() {
+            prop_assert_eq!(dlb.contains(probe.clone()), expected.contains(probe));
+            let prefixed: Vec<String> = dlb.prefix_iter(probe.clone()).map(|(key, _)| key).collect();
+            let wanted: Vec<String> = expected
+                .iter()
+                .filter(|key| key.starts_with(probe.as_str()))
+                .map(|key| key.to_string())
+                .collect();
+            prop_assert_eq!(prefixed, wanted);
+        }
+
+        // Every label is whole, so the trie survives the checks made when reading.
+        let mut bytes = Vec::new();
+        dlb.write_to(&mut bytes).unwrap();
+        let read: DLB = DLB::read_from(&mut bytes.as_slice()).unwrap();
+        prop_assert_eq!(read.mode(), KeyMode::Utf8);
+        prop_assert!(read == dlb);
+
+        let trie = dlb.freeze();
+        for (key, id) in dlb.iter() {
+            prop_assert_eq!(trie.get(key.clone()), Some(id));
+            prop_assert_eq!(trie.resolve(id), Some(key));
+        }
+        for probe in probes.iter() {
+            prop_assert_eq!(trie.contains(probe.clone()), expected.contains(probe));
+        }
+    }
+
+    #[test]
+    fn test_utf8_mode_removal(words in vec(WORD, 0..40), doomed in vec(any::<prop::sample::Index>(), 0..20)) {
+        let mut dlb = build(KeyMode::Utf8, &words);
+        let mut expected: BTreeSet<String> = words.iter().cloned().collect();
+        for index in doomed {
+            if words.is_empty() {
+                break;
+            }
+            let word = index.get(&words);
+            prop_assert_eq!(dlb.remove(word.clone()).is_some(), expected.remove(word));
+        }
+        let keys: Vec<String> = dlb.iter().map(|(key, _)| key).collect();
+        prop_assert!(keys.iter().eq(expected.iter()));
+        // Removal leaves the trie as if the keys had never been added.
+        let rebuilt = build(KeyMode::Utf8, &keys);
+        prop_assert_eq!(dlb.count_nodes(), rebuilt.count_nodes());
+    }
+
+    #[test]
+    fn test_set_ops_across_modes(left in vec(WORD, 0..30), right in vec(WORD, 0..30)) {
+        let left_set: BTreeSet<String> = left.iter().cloned().collect();
+        let right_set: BTreeSet<String> = right.iter().cloned().collect();
+        for (left_mode, right_mode) in [
+            (KeyMode::Utf8, KeyMode::Utf8),
+            (KeyMode::Utf8, KeyMode::Bytes),
+            (KeyMode::Bytes, KeyMode::Utf8),
+        ].iter() {
+            let ours = build(*left_mode, &left);
+            let theirs = build(*right_mode, &right);
+            let (union, remap) = ours.union(&theirs);
+            prop_assert!(union.iter().map(|(key, _)| key).eq(left_set.union(&right_set).cloned()));
+            for (key, id) in theirs.iter() {
+                prop_assert_eq!(union.get(key), remap.get(id));
+            }
+            let (intersection, _) = ours.intersection(&theirs);
+            prop_assert!(intersection
+                .iter()
+                .map(|(key, _)| key)
+                .eq(left_set.intersection(&right_set).cloned()));
+            let difference = ours.difference(&theirs);
+            prop_assert!(difference
+                .iter()
+                .map(|(key, _)| key)
+                .eq(left_set.difference(&right_set).cloned()));
+        }
+    }
+}